@@ -209,34 +209,26 @@ impl ParsedConfig {
     }
 
     /// Computes gas credit and limits bought for the provided balances.
-    pub fn compute_gas_params(
-        &self,
-        account_balance: &Tokens,
-        msg_balance_remaining: &Tokens,
-        is_special: bool,
-        is_masterchain: bool,
-        is_tx_ordinary: bool,
-        is_in_msg_external: bool,
-    ) -> GasParams {
-        let prices = self.gas_prices(is_masterchain);
+    pub fn compute_gas_params(&self, input: &GasParamsInput<'_>) -> GasParams {
+        let prices = self.gas_prices(input.is_masterchain);
 
-        let gas_max = if is_special {
+        let gas_max = if input.is_special {
             prices.special_gas_limit
         } else {
-            gas_bought_for(prices, account_balance)
+            gas_bought_for(prices, input.account_balance)
         };
 
-        let gas_limit = if !is_tx_ordinary || is_special {
+        let gas_limit = if !input.is_tx_ordinary || input.is_special {
             // May use all gas that can be bought using remaining balance.
             gas_max
         } else {
             // Use only gas bought using remaining message balance.
             // If the message is "accepted" by the smart contract,
             // the gas limit will be set to `gas_max`.
-            std::cmp::min(gas_bought_for(prices, msg_balance_remaining), gas_max)
+            std::cmp::min(gas_bought_for(prices, input.msg_balance_remaining), gas_max)
         };
 
-        let gas_credit = if is_tx_ordinary && is_in_msg_external {
+        let gas_credit = if input.is_tx_ordinary && input.is_in_msg_external {
             // External messages carry no balance,
             // give them some credit to check whether they are accepted.
             std::cmp::min(prices.gas_credit, gas_max)
@@ -253,6 +245,17 @@ impl ParsedConfig {
     }
 }
 
+/// Account and message state for [`ParsedConfig::compute_gas_params`].
+#[derive(Debug, Clone, Copy)]
+pub struct GasParamsInput<'a> {
+    pub account_balance: &'a Tokens,
+    pub msg_balance_remaining: &'a Tokens,
+    pub is_special: bool,
+    pub is_masterchain: bool,
+    pub is_tx_ordinary: bool,
+    pub is_in_msg_external: bool,
+}
+
 fn gas_bought_for(prices: &GasLimitsPrices, balance: &Tokens) -> u64 {
     let balance = balance.into_inner();
     if balance == 0 || balance < prices.flat_gas_price as u128 {
@@ -10,7 +10,7 @@ use everscale_types::models::{
 use everscale_types::num::{Tokens, Uint15, VarUint56};
 use everscale_types::prelude::*;
 
-pub use self::config::ParsedConfig;
+pub use self::config::{GasParamsInput, ParsedConfig};
 pub use self::error::{TxError, TxResult};
 use self::util::new_varuint56_truncate;
 pub use self::util::{ExtStorageStat, OwnedExtStorageStat, StorageStatLimits};
@@ -283,6 +283,8 @@ pub struct ExecutorParams {
     pub block_lt: u64,
     /// VM behaviour modifiers.
     pub vm_modifiers: tycho_vm::BehaviourModifiers,
+    /// Gas prices of basic VM operations.
+    pub vm_gas_prices: tycho_vm::GasPrices,
     /// Prevent [`Frozen`] accounts from being deleted
     /// when their storage due is too high.
     ///
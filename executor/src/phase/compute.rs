@@ -13,7 +13,7 @@ use crate::util::{
     check_state_limits_diff, new_varuint24_truncate, new_varuint56_truncate, unlikely,
     StateLimitsResult,
 };
-use crate::ExecutorState;
+use crate::{ExecutorState, GasParamsInput};
 
 /// Compute phase input context.
 pub struct ComputePhaseContext<'a> {
@@ -130,14 +130,14 @@ impl ExecutorState<'_> {
         let gas = if unlikely(ctx.force_accept) {
             tycho_vm::GasParams::getter()
         } else {
-            self.config.compute_gas_params(
-                &self.balance.tokens,
-                &msg_balance_remaining.tokens,
-                self.is_special,
+            self.config.compute_gas_params(&GasParamsInput {
+                account_balance: &self.balance.tokens,
+                msg_balance_remaining: &msg_balance_remaining.tokens,
+                is_special: self.is_special,
                 is_masterchain,
-                ctx.input.is_ordinary(),
-                is_external,
-            )
+                is_tx_ordinary: ctx.input.is_ordinary(),
+                is_in_msg_external: is_external,
+            })
         };
         if gas.limit == 0 && gas.credit == 0 {
             res.compute_phase = ComputePhase::Skipped(SkippedComputePhase {
@@ -272,6 +272,7 @@ impl ExecutorState<'_> {
             .with_init_selector(false)
             .with_raw_stack(stack)
             .with_gas(gas)
+            .with_gas_prices(self.params.vm_gas_prices)
             .with_modifiers(self.params.vm_modifiers)
             .build();
 
@@ -778,6 +779,43 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn ext_in_accept_custom_gas_prices() -> Result<()> {
+        let mut params = make_default_params();
+        params.vm_gas_prices = tycho_vm::GasPrices {
+            build_cell: 1000,
+            ..tycho_vm::GasPrices::DEFAULT
+        };
+        let config = make_default_config();
+        let mut state = ExecutorState::new_active(
+            &params,
+            &config,
+            &STUB_ADDR,
+            OK_BALANCE,
+            Cell::empty_cell(),
+            tvmasm!("ACCEPT NEWC INT 0xdeafbeaf STUR 32 ENDC POP c5"),
+        );
+
+        let msg = state.receive_in_msg(empty_ext_in_msg(&state.address))?;
+
+        let compute_phase = state.compute_phase(ComputePhaseContext {
+            input: TransactionInput::Ordinary(&msg),
+            storage_fee: Tokens::ZERO,
+            force_accept: false,
+        })?;
+        assert!(compute_phase.accepted);
+
+        let ComputePhase::Executed(compute_phase) = compute_phase.compute_phase else {
+            panic!("expected executed compute phase");
+        };
+
+        // The same code as in `ext_in_accept_simple` with a more expensive cell build.
+        assert!(compute_phase.success);
+        assert_eq!(compute_phase.gas_used, 1150);
+
+        Ok(())
+    }
+
     #[test]
     fn internal_accept_simple() -> Result<()> {
         let params = make_default_params();
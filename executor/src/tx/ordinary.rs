@@ -443,4 +443,77 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn send_messages_in_action_order() -> Result<()> {
+        let params = make_default_params();
+        let config = make_default_config();
+
+        let code = tvmasm!(
+            r#"
+            ACCEPT
+            NEWC
+            // int_msg_info$0 ihr_disabled:Bool bounce:Bool bounced:Bool src:MsgAddress -> 010000
+            INT 0b010000 STUR 6
+            MYADDR STSLICER
+            INT 1000 STGRAMS
+            // extra, fees, lt, time, init and body
+            INT 107 STZEROES
+            ENDC INT 1 SENDRAWMSG
+            NEWC
+            INT 0b010000 STUR 6
+            MYADDR STSLICER
+            INT 2000 STGRAMS
+            INT 107 STZEROES
+            ENDC INT 1 SENDRAWMSG
+            "#
+        );
+        let mut state = ExecutorState::new_active(
+            &params,
+            &config,
+            &STUB_ADDR,
+            Tokens::new(1_000_000_000),
+            Cell::empty_cell(),
+            code,
+        );
+
+        let info = state.run_ordinary_transaction(
+            false,
+            make_message(
+                IntMsgInfo {
+                    src: STUB_ADDR.into(),
+                    dst: STUB_ADDR.into(),
+                    value: CurrencyCollection::new(1_000_000),
+                    ..Default::default()
+                },
+                None,
+                None,
+            ),
+        )?;
+
+        assert!(!info.aborted);
+        let ComputePhase::Executed(compute_phase) = info.compute_phase else {
+            panic!("expected an executed compute phase");
+        };
+        assert!(compute_phase.success);
+
+        // Both SENDRAWMSG actions from c5 are applied
+        let action_phase = info.action_phase.unwrap();
+        assert!(action_phase.success);
+        assert_eq!(action_phase.total_actions, 2);
+        assert_eq!(action_phase.messages_created, 2);
+
+        // ... in the order in which they were sent
+        let values = state
+            .out_msgs
+            .iter()
+            .map(|msg| match msg.load()?.info {
+                MsgInfo::Int(info) => Ok(info.value.tokens),
+                _ => panic!("expected an internal outbound message"),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        assert_eq!(values, [Tokens::new(1000), Tokens::new(2000)]);
+
+        Ok(())
+    }
 }
@@ -12,6 +12,9 @@ struct VmInstrArgs {
 
     #[darling(with = "parse_expr::preserve_str_literal")]
     fmt: syn::Expr,
+    /// Opcode mnemonic, required if `fmt` is not a string literal.
+    #[darling(default)]
+    name: Option<String>,
 
     #[darling(default)]
     args: HashMap<String, syn::Expr>,
@@ -227,6 +230,22 @@ fn process_instr_definition(
     };
 
     let function_name = function.sig.ident.clone();
+    let name = match (instr.name, &instr.fmt) {
+        (Some(name), _) => name,
+        (
+            None,
+            syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Str(lit),
+                ..
+            }),
+        ) => lit.value(),
+        (None, fmt) => {
+            return Err(Error::custom(
+                "Opcode with a non-literal `fmt` must have an explicit `name`",
+            )
+            .with_span(fmt));
+        }
+    };
     let fmt = match instr.fmt {
         syn::Expr::Tuple(items) => items.elems.into_token_stream(),
         syn::Expr::Lit(expr) if matches!(&expr.lit, syn::Lit::Str(..)) => expr.into_token_stream(),
@@ -438,6 +457,7 @@ fn process_instr_definition(
     };
 
     let wrapper_func_name = quote::format_ident!("{function_name}_wrapper");
+    let dump_func_name = quote::format_ident!("{function_name}_dump");
     let wrapper_func = match &ty {
         OpcodeTy::Simple { .. } => {
             if let Some(cond) = instr.cond {
@@ -449,37 +469,54 @@ fn process_instr_definition(
             quote! {
                 fn #wrapper_func_name(st: &mut ::tycho_vm::state::VmState) -> ::tycho_vm::error::VmResult<i32> {
                     #(#arg_definitions)*
-                    vm_log_op!(#fmt);
+                    vm_log_op!(st, #fmt);
                     #function_name(st, #(#arg_idents),*)
                 }
+
+                #[allow(unused_variables)]
+                fn #dump_func_name() -> String {
+                    #(#arg_definitions)*
+                    ::std::fmt::format(format_args!(#fmt))
+                }
             }
         }
         OpcodeTy::Fixed { .. } | OpcodeTy::FixedRange { .. } => {
-            let cond = instr.cond.map(|cond| {
-                quote! { vm_ensure!(#cond, InvalidOpcode); }
-            });
+            let (cond, dump_cond) = match instr.cond {
+                Some(cond) => (
+                    Some(quote! { vm_ensure!(#cond, InvalidOpcode); }),
+                    Some(quote! { if !(#cond) { return None; } }),
+                ),
+                None => (None, None),
+            };
 
             quote! {
                 fn #wrapper_func_name(st: &mut ::tycho_vm::state::VmState, args: u32) -> ::tycho_vm::error::VmResult<i32> {
                     #(#arg_definitions)*
                     #cond
-                    vm_log_op!(#fmt);
+                    vm_log_op!(st, #fmt);
                     #function_name(st, #(#arg_idents),*)
                 }
+
+                #[allow(unused_variables)]
+                fn #dump_func_name(args: u32) -> Option<String> {
+                    #(#arg_definitions)*
+                    #dump_cond
+                    Some(::std::fmt::format(format_args!(#fmt)))
+                }
             }
         }
     };
 
     let expr_add = match ty {
         OpcodeTy::Simple { opcode, bits } => quote! {
-            #opcodes_arg.add_simple(#opcode, #bits, #wrapper_func_name)
+            #opcodes_arg.add_simple(#name, #opcode, #bits, #wrapper_func_name, #dump_func_name)
         },
         OpcodeTy::Fixed {
             opcode,
             opcode_bits,
             arg_bits,
         } => quote! {
-            #opcodes_arg.add_fixed(#opcode, #opcode_bits, #arg_bits, #wrapper_func_name)
+            #opcodes_arg.add_fixed(#name, #opcode, #opcode_bits, #arg_bits, #wrapper_func_name, #dump_func_name)
         },
         OpcodeTy::FixedRange {
             opcode_min,
@@ -487,7 +524,7 @@ fn process_instr_definition(
             total_bits,
             arg_bits,
         } => quote! {
-            #opcodes_arg.add_fixed_range(#opcode_min, #opcode_max, #total_bits, #arg_bits, #wrapper_func_name)
+            #opcodes_arg.add_fixed_range(#name, #opcode_min, #opcode_max, #total_bits, #arg_bits, #wrapper_func_name, #dump_func_name)
         },
     };
 
@@ -0,0 +1,58 @@
+// Gas vectors shared by the `instruction_gas_vectors` unit test
+// and the `gas_vectors` benchmark. Included as an expression which
+// evaluates to a list of `(group, code, cell, stack, gas, exit_code)`.
+//
+// Expected gas is computed by hand from the TON gas schedule:
+// an instruction costs 10 + its length in bits, implicit RET costs 5
+// and an exception costs 50.
+{
+    macro_rules! gas_vectors {
+        ($($group:literal => {
+            $($code:literal, [$($stack:tt)*] => gas: $gas:literal, exit_code: $exit_code:literal;)*
+        })*) => {
+            vec![$($((
+                $group,
+                $code,
+                Boc::decode(tvmasm!($code)).unwrap(),
+                tuple![$($stack)*],
+                $gas as u64,
+                $exit_code as i32,
+            )),*),*]
+        };
+    }
+
+    gas_vectors! {
+        "arith" => {
+            // 8-bit PUSHINT x2, ADD, RET
+            "INT 1 INT 2 ADD", [] => gas: 59, exit_code: 0;
+            "INT 3 INT 4 MUL", [] => gas: 59, exit_code: 0;
+            // 8-bit PUSHINT x2, 16-bit DIV, RET
+            "INT 7 INT 2 DIV", [] => gas: 67, exit_code: 0;
+            "INC", [int 1] => gas: 23, exit_code: 0;
+            // 24-bit PUSHINT, RET
+            "PUSHINT 1000", [] => gas: 39, exit_code: 0;
+            "ADDCONST 10", [int 1] => gas: 31, exit_code: 0;
+            "MULCONST 3", [int 1] => gas: 31, exit_code: 0;
+            // 8-bit PUSHINT x2, 16-bit DIV, exception
+            "INT 5 INT 0 DIV", [] => gas: 112, exit_code: 4;
+            // 16-bit PUSHPOW2, DUP, ADD, exception
+            "PUSHPOW2 255 DUP ADD", [] => gas: 112, exit_code: 4;
+            "ADD", [] => gas: 68, exit_code: 2;
+        }
+        "stack" => {
+            "SWAP", [int 1, int 2] => gas: 23, exit_code: 0;
+            "XCHG s1,s2", [int 1, int 2, int 3] => gas: 23, exit_code: 0;
+            "DUP DROP", [int 1] => gas: 41, exit_code: 0;
+            "OVER", [int 1, int 2] => gas: 23, exit_code: 0;
+            // PUSH s1 (long form)
+            "@inline x{5601}", [int 1, int 2] => gas: 31, exit_code: 0;
+            "ROT", [int 1, int 2, int 3] => gas: 23, exit_code: 0;
+            "BLKSWAP 1, 2", [int 1, int 2, int 3] => gas: 31, exit_code: 0;
+            "XCHG s2,s3", [int 1, int 2, int 3, int 4] => gas: 31, exit_code: 0;
+            "INT 3 ROLL", [int 1, int 2, int 3, int 4] => gas: 41, exit_code: 0;
+            "2DUP", [int 1, int 2] => gas: 23, exit_code: 0;
+            "DEPTH", [] => gas: 23, exit_code: 0;
+            "SWAP", [int 1] => gas: 68, exit_code: 2;
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use everscale_asm_macros::tvmasm;
+use everscale_types::boc::Boc;
+use tycho_vm::{codepage0, tuple, GasParams, SmcInfoBase, VmState};
+
+fn lookup_benchmark(c: &mut Criterion) {
+    let cp = codepage0();
+
+    c.bench_function("opcode_lookup", |b| {
+        b.iter(|| {
+            for opcode in (0..0x1000000).step_by(4099) {
+                _ = black_box(cp.lookup(black_box(opcode)).range());
+            }
+        });
+    });
+}
+
+fn arith_loop_benchmark(c: &mut Criterion) {
+    let code = Boc::decode(tvmasm!(
+        r#"
+        INT 1000
+        PUSHCONT {
+            INC
+            INT 2
+            ADD
+            INT 3
+            SUB
+        }
+        REPEAT
+        "#
+    ))
+    .unwrap();
+
+    c.bench_function("arith_loop", |b| {
+        b.iter(|| {
+            let mut vm_state = VmState::builder()
+                .with_smc_info(SmcInfoBase::new())
+                .with_stack(tuple![int 0])
+                .with_code(code.clone())
+                .with_gas(GasParams::getter())
+                .build();
+
+            let result = vm_state.run();
+            _ = black_box(result);
+        });
+    });
+}
+
+criterion_group!(benches, lookup_benchmark, arith_loop_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,63 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use everscale_asm_macros::tvmasm;
+use everscale_types::boc::Boc;
+use everscale_types::cell::Cell;
+use tycho_vm::{tuple, GasParams, RcStackValue, SmcInfoBase, VmState};
+
+/// Code with an input stack and the expected gas and exit code.
+type GasVector = (
+    &'static str,
+    &'static str,
+    Cell,
+    Vec<RcStackValue>,
+    u64,
+    i32,
+);
+
+fn gas_vectors() -> Vec<GasVector> {
+    include!("common/gas_vectors.rs")
+}
+
+fn run((_, _, code, stack, ..): &GasVector) -> (i32, u64) {
+    let mut vm_state = VmState::builder()
+        .with_smc_info(SmcInfoBase::new())
+        .with_stack(stack.clone())
+        .with_code(code.clone())
+        .with_gas(GasParams::getter())
+        .build();
+
+    let exit_code = !vm_state.run();
+    (exit_code, vm_state.gas.consumed())
+}
+
+fn gas_vectors_benchmark(c: &mut Criterion) {
+    let vectors = gas_vectors();
+    for vector in &vectors {
+        let (_, code, _, _, gas, exit_code) = vector;
+        let (actual_exit_code, actual_gas) = run(vector);
+        assert_eq!(
+            (actual_exit_code, actual_gas),
+            (*exit_code, *gas),
+            "`{code}`: expected gas {gas} (exit code {exit_code}), \
+            actual gas {actual_gas} (exit code {actual_exit_code})"
+        );
+    }
+
+    for name in ["arith", "stack"] {
+        let vectors = vectors
+            .iter()
+            .filter(|(group, ..)| *group == name)
+            .collect::<Vec<_>>();
+
+        c.bench_function(&format!("gas_vectors_{name}"), |b| {
+            b.iter(|| {
+                for vector in &vectors {
+                    _ = black_box(run(vector));
+                }
+            });
+        });
+    }
+}
+
+criterion_group!(benches, gas_vectors_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,44 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use everscale_asm_macros::tvmasm;
+use everscale_types::boc::Boc;
+use num_bigint::BigInt;
+use tycho_vm::{GasParams, InlineInt, Int257, RcStackValue, SafeRc, SmcInfoBase, VmState};
+
+fn inc_benchmark(c: &mut Criterion) {
+    let code = Boc::decode(tvmasm!(
+        r#"
+        PUSHINT 1000000
+        PUSHCONT {
+            INC
+        }
+        REPEAT
+        "#
+    ))
+    .unwrap();
+
+    let values: [(&str, fn() -> RcStackValue); 2] = [
+        ("inc_bigint", || SafeRc::new_dyn_value(BigInt::from(0))),
+        ("inc_int257", || {
+            SafeRc::new_dyn_value(InlineInt::new(Int257::ZERO))
+        }),
+    ];
+
+    for (name, make_value) in values {
+        c.bench_function(name, |b| {
+            b.iter(|| {
+                let mut vm_state = VmState::builder()
+                    .with_smc_info(SmcInfoBase::new())
+                    .with_stack(vec![make_value()])
+                    .with_code(code.clone())
+                    .with_gas(GasParams::unlimited())
+                    .build();
+
+                let result = vm_state.run();
+                _ = black_box(result);
+            });
+        });
+    }
+}
+
+criterion_group!(benches, inc_benchmark);
+criterion_main!(benches);
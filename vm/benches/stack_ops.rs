@@ -0,0 +1,39 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use everscale_asm_macros::tvmasm;
+use everscale_types::boc::Boc;
+use tycho_vm::{tuple, GasParams, SmcInfoBase, VmState};
+
+fn vm_benchmark(c: &mut Criterion) {
+    let code = Boc::decode(tvmasm!(
+        r#"
+        INT 1000
+        PUSHCONT {
+            SWAP
+            XCHG s2
+            XCHG s1,s3
+            XCHG s3
+            SWAP
+            XCHG s2,s3
+        }
+        REPEAT
+        "#
+    ))
+    .unwrap();
+
+    c.bench_function("swap_heavy", |b| {
+        b.iter(|| {
+            let mut vm_state = VmState::builder()
+                .with_smc_info(SmcInfoBase::new())
+                .with_stack(tuple![int 1, int 2, int 3, int 4])
+                .with_code(code.clone())
+                .with_gas(GasParams::getter())
+                .build();
+
+            let result = vm_state.run();
+            _ = black_box(result);
+        });
+    });
+}
+
+criterion_group!(benches, vm_benchmark);
+criterion_main!(benches);
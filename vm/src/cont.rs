@@ -27,7 +27,7 @@ pub struct ControlData {
 impl ControlData {
     pub fn require_nargs(&self, copy: usize) -> VmResult<()> {
         if matches!(self.nargs, Some(nargs) if (nargs as usize) < copy) {
-            vm_bail!(StackUnderflow(copy as _))
+            vm_bail!(stack_underflow(copy as _))
         }
         Ok(())
     }
@@ -1,24 +1,79 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashSet, VecDeque};
 
 use anyhow::Result;
+use everscale_types::cell::LoadMode;
 use everscale_types::prelude::*;
 
-use crate::error::VmResult;
+use crate::error::{DecodeError, VmResult};
+use crate::gas::{GasConsumer, GasParams, LibraryProvider};
 use crate::state::VmState;
+use crate::util::OwnedCellSlice;
 
 /// Opcode description.
 pub trait Opcode: Send + Sync {
     /// Opcode range aligned to 24 bits.
     fn range(&self) -> (u32, u32);
 
+    /// Opcode mnemonic or format string (used for diagnostics).
+    fn name(&self) -> &'static str {
+        "<unnamed>"
+    }
+
+    /// Opcode length in bits including fixed-size arguments
+    /// (variable-length immediates are not included).
+    fn bits(&self) -> u16 {
+        0
+    }
+
+    /// Continuations embedded into this opcode.
+    fn embedded_conts(&self) -> EmbeddedConts {
+        EmbeddedConts::None
+    }
+
     /// Execute this opcode.
     fn dispatch(&self, st: &mut VmState, opcode: u32, bits: u16) -> VmResult<i32>;
+
+    /// Format this opcode and skip it in the code.
+    ///
+    /// Returns `None` if the opcode is malformed or cannot be formatted.
+    fn dump(&self, _: &mut CellSlice<'_>, _: u32, _: u16) -> Option<String> {
+        None
+    }
+}
+
+/// Continuations embedded into an opcode (see [`Opcode::embedded_conts`]).
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub enum EmbeddedConts {
+    /// Opcode has no continuations.
+    #[default]
+    None,
+    /// Each reference of the opcode is a continuation (e.g. `CALLREF`).
+    Refs,
+    /// Opcode data after the fixed-size part is a continuation (e.g. `PUSHCONT`).
+    Inline,
+}
+
+/// Opcode metadata.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct OpcodeInfo<'a> {
+    /// Start of the opcode range aligned to 24 bits (inclusive).
+    pub min: u32,
+    /// End of the opcode range aligned to 24 bits (exclusive).
+    pub max: u32,
+    /// Opcode length in bits (see [`Opcode::bits`]).
+    pub bits: u16,
+    /// Opcode mnemonic or format string.
+    pub name: &'a str,
 }
 
 /// Code page.
 pub struct DispatchTable {
     id: u16,
-    opcodes: Vec<(u32, Box<dyn Opcode>)>,
+    /// Sorted opcodes with a flag whether the range is unassigned.
+    opcodes: Vec<(u32, Box<dyn Opcode>, bool)>,
+    /// Slot of the opcode containing each first byte prefix
+    /// (with an extra slot for the end).
+    first_byte: Box<[u32; 257]>,
 }
 
 impl DispatchTable {
@@ -35,10 +90,17 @@ impl DispatchTable {
     }
 
     pub fn lookup(&self, opcode: u32) -> &dyn Opcode {
+        self.lookup_slot(opcode).1.as_ref()
+    }
+
+    fn lookup_slot(&self, opcode: u32) -> &(u32, Box<dyn Opcode>, bool) {
         debug_assert!(!self.opcodes.is_empty());
 
-        let mut i = 0;
-        let mut j = self.opcodes.len();
+        // NOTE: The search is narrowed down to opcodes which
+        // intersect with the same first byte prefix.
+        let b = std::cmp::min(opcode >> FIRST_BYTE_SHIFT, 0xff) as usize;
+        let mut i = self.first_byte[b] as usize;
+        let mut j = self.first_byte[b + 1] as usize + 1;
         while j - i > 1 {
             let k = (j + i) >> 1;
             if self.opcodes[k].0 <= opcode {
@@ -47,16 +109,164 @@ impl DispatchTable {
                 j = k;
             }
         }
-        self.opcodes[i].1.as_ref()
+        &self.opcodes[i]
+    }
+
+    /// Returns an iterator over all implemented opcodes in ascending order.
+    pub fn opcodes(&self) -> impl Iterator<Item = OpcodeInfo<'_>> {
+        self.opcodes
+            .iter()
+            .filter(|(_, _, unassigned)| !unassigned)
+            .map(|(min, opcode, _)| OpcodeInfo {
+                min: *min,
+                max: opcode.range().1,
+                bits: opcode.bits(),
+                name: opcode.name(),
+            })
     }
 
     pub fn dispatch(&self, st: &mut VmState) -> VmResult<i32> {
         let (opcode, bits) = Self::get_opcode_from_slice(&st.code.apply());
         let op = self.lookup(opcode);
+        Self::dispatch_op(op, st, opcode, bits)
+    }
+
+    pub(crate) fn dispatch_op(
+        op: &dyn Opcode,
+        st: &mut VmState,
+        opcode: u32,
+        bits: u16,
+    ) -> VmResult<i32> {
+        let depth = st.stack.depth();
         op.dispatch(st, opcode, bits)
+            .map_err(|e| e.with_op_context(op.name(), depth, st.stack.depth()))
+    }
+
+    /// Disassembles the code into a list of `(cell, offset, instruction)` items.
+    ///
+    /// Offset is the bit position of the instruction in the cell with the
+    /// specified hash. Embedded continuations are listed after the code
+    /// which contains them. Stops at the first malformed or unknown opcode.
+    pub fn disassemble(
+        &self,
+        code: &OwnedCellSlice,
+        libraries: &dyn LibraryProvider,
+    ) -> Vec<(HashBytes, u16, String)> {
+        let mut result = Vec::new();
+        _ = self.decode(code, libraries, |cell, offset, text| {
+            result.push((*cell, offset, text));
+        });
+        result
+    }
+
+    /// Checks that all opcodes of the code can be decoded.
+    ///
+    /// Nothing is executed and no gas is consumed. Implicit jumps and
+    /// continuations embedded into opcodes (see [`Opcode::embedded_conts`])
+    /// are loaded the same way as during the execution, including libraries.
+    pub fn validate(
+        &self,
+        code: &OwnedCellSlice,
+        libraries: &dyn LibraryProvider,
+    ) -> Result<(), DecodeError> {
+        self.decode(code, libraries, |_, _, _| {})
     }
 
-    fn get_opcode_from_slice(slice: &CellSlice<'_>) -> (u32, u16) {
+    fn decode<F>(
+        &self,
+        code: &OwnedCellSlice,
+        libraries: &dyn LibraryProvider,
+        mut f: F,
+    ) -> Result<(), DecodeError>
+    where
+        F: FnMut(&HashBytes, u16, String),
+    {
+        // NOTE: Gas is consumed from a temporary consumer.
+        let gas = GasConsumer::with_libraries(GasParams::unlimited(), libraries);
+        let load = |cell: Cell, code: &OwnedCellSlice| {
+            gas.load_cell_as_slice(cell, LoadMode::Full).map_err(|_| {
+                let cell = *code.cell().repr_hash();
+                let offset = code.range().offset_bits();
+                match gas.missing_library() {
+                    Some(hash) => DecodeError::MissingLibrary { cell, offset, hash },
+                    None => DecodeError::ExoticCell { cell, offset },
+                }
+            })
+        };
+
+        let mut visited = HashSet::new();
+        let mut queue = VecDeque::from([code.clone()]);
+        while let Some(mut code) = queue.pop_front() {
+            loop {
+                let range = code.range();
+                if range.is_data_empty() {
+                    if range.is_refs_empty() {
+                        break;
+                    }
+
+                    // Implicit JMPREF
+                    let Some(next) = code.cell().reference_cloned(range.offset_refs()) else {
+                        break;
+                    };
+                    code = ok!(load(next, &code));
+                    continue;
+                }
+
+                let cell = code.cell().repr_hash();
+                let offset = range.offset_bits();
+
+                let mut slice = code.apply();
+                let (opcode, bits) = Self::get_opcode_from_slice(&slice);
+                let (_, op, unassigned) = self.lookup_slot(opcode);
+                if *unassigned {
+                    return Err(DecodeError::UnknownOpcode {
+                        cell: *cell,
+                        offset,
+                    });
+                }
+                let Some(text) = op.dump(&mut slice, opcode, bits) else {
+                    return Err(DecodeError::MalformedOpcode {
+                        cell: *cell,
+                        offset,
+                        name: op.name().to_owned(),
+                    });
+                };
+                let next_range = slice.range();
+
+                match op.embedded_conts() {
+                    EmbeddedConts::None => {}
+                    EmbeddedConts::Refs => {
+                        for i in range.offset_refs()..next_range.offset_refs() {
+                            let Some(child) = code.cell().reference_cloned(i) else {
+                                continue;
+                            };
+                            if visited.insert(*child.repr_hash()) {
+                                queue.push_back(ok!(load(child, &code)));
+                            }
+                        }
+                    }
+                    EmbeddedConts::Inline => {
+                        let mut cont = range;
+                        let ok = cont.skip_first(op.bits(), 0).is_ok()
+                            && cont
+                                .only_first(
+                                    next_range.offset_bits() - cont.offset_bits(),
+                                    next_range.offset_refs() - cont.offset_refs(),
+                                )
+                                .is_ok();
+                        debug_assert!(ok);
+                        queue.push_back(OwnedCellSlice::from((code.cell().clone(), cont)));
+                    }
+                }
+
+                f(cell, offset, text);
+                code.set_range(next_range);
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn get_opcode_from_slice(slice: &CellSlice<'_>) -> (u32, u16) {
         let bits = std::cmp::min(MAX_OPCODE_BITS, slice.size_bits());
         let opcode = (slice.get_uint(0, bits).unwrap() as u32) << (MAX_OPCODE_BITS - bits);
         (opcode, bits)
@@ -83,10 +293,11 @@ impl Opcodes {
                         opcode_min: upto,
                         opcode_max: min,
                     }) as Box<_>,
+                    true,
                 ));
             }
 
-            opcodes.push((k, opcode));
+            opcodes.push((k, opcode, false));
             upto = max;
         }
 
@@ -97,20 +308,42 @@ impl Opcodes {
                     opcode_min: upto,
                     opcode_max: MAX_OPCODE,
                 }),
+                true,
             ));
         }
 
         opcodes.shrink_to_fit();
 
+        let mut first_byte = Box::new([0; 257]);
+        let mut slot = 0;
+        for (b, item) in first_byte.iter_mut().enumerate().take(256) {
+            let prefix = (b as u32) << FIRST_BYTE_SHIFT;
+            while slot + 1 < opcodes.len() && opcodes[slot + 1].0 <= prefix {
+                slot += 1;
+            }
+            *item = slot as u32;
+        }
+        first_byte[256] = (opcodes.len() - 1) as u32;
+
         DispatchTable {
             id: self.id,
             opcodes,
+            first_byte,
         }
     }
 
-    pub fn add_simple(&mut self, opcode: u32, bits: u16, exec: FnExecInstrSimple) -> Result<()> {
+    pub fn add_simple(
+        &mut self,
+        name: &'static str,
+        opcode: u32,
+        bits: u16,
+        exec: FnExecInstrSimple,
+        dump: FnDumpInstrSimple,
+    ) -> Result<()> {
         let remaining_bits = MAX_OPCODE_BITS - bits;
         self.add_opcode(Box::new(SimpleOpcode {
+            name,
+            dump,
             opcode_min: opcode << remaining_bits,
             opcode_max: (opcode + 1) << remaining_bits,
             opcode_bits: bits,
@@ -120,14 +353,18 @@ impl Opcodes {
 
     pub fn add_fixed(
         &mut self,
+        name: &'static str,
         opcode: u32,
         opcode_bits: u16,
         arg_bits: u16,
         exec: FnExecInstrArg,
+        dump: FnDumpInstrArg,
     ) -> Result<()> {
         let remaining_bits = MAX_OPCODE_BITS - opcode_bits;
         self.add_opcode(Box::new(FixedOpcode {
+            name,
             exec,
+            dump,
             opcode_min: opcode << remaining_bits,
             opcode_max: (opcode + 1) << remaining_bits,
             total_bits: opcode_bits + arg_bits,
@@ -136,15 +373,19 @@ impl Opcodes {
 
     pub fn add_fixed_range(
         &mut self,
+        name: &'static str,
         opcode_min: u32,
         opcode_max: u32,
         total_bits: u16,
         _arg_bits: u16,
         exec: FnExecInstrArg,
+        dump: FnDumpInstrArg,
     ) -> Result<()> {
         let remaining_bits = MAX_OPCODE_BITS - total_bits;
         self.add_opcode(Box::new(FixedOpcode {
+            name,
             exec,
+            dump,
             opcode_min: opcode_min << remaining_bits,
             opcode_max: opcode_max << remaining_bits,
             total_bits,
@@ -153,33 +394,86 @@ impl Opcodes {
 
     pub fn add_ext(
         &mut self,
+        name: &'static str,
+        opcode: u32,
+        opcode_bits: u16,
+        arg_bits: u16,
+        exec: FnExecInstrFull,
+        dump: FnDumpInstrFull,
+    ) -> Result<()> {
+        let remaining_bits = MAX_OPCODE_BITS - opcode_bits;
+        self.add_opcode(Box::new(ExtOpcode {
+            name,
+            exec,
+            dump,
+            opcode_min: opcode << remaining_bits,
+            opcode_max: (opcode + 1) << remaining_bits,
+            total_bits: opcode_bits + arg_bits,
+            conts: EmbeddedConts::None,
+        }))
+    }
+
+    /// Adds an extended opcode whose references are continuations.
+    pub fn add_ext_with_ref_conts(
+        &mut self,
+        name: &'static str,
+        opcode: u32,
+        opcode_bits: u16,
+        exec: FnExecInstrFull,
+        dump: FnDumpInstrFull,
+    ) -> Result<()> {
+        let remaining_bits = MAX_OPCODE_BITS - opcode_bits;
+        self.add_opcode(Box::new(ExtOpcode {
+            name,
+            exec,
+            dump,
+            opcode_min: opcode << remaining_bits,
+            opcode_max: (opcode + 1) << remaining_bits,
+            total_bits: opcode_bits,
+            conts: EmbeddedConts::Refs,
+        }))
+    }
+
+    /// Adds an extended opcode which is followed by an inline continuation.
+    pub fn add_ext_with_inline_cont(
+        &mut self,
+        name: &'static str,
         opcode: u32,
         opcode_bits: u16,
         arg_bits: u16,
         exec: FnExecInstrFull,
+        dump: FnDumpInstrFull,
     ) -> Result<()> {
         let remaining_bits = MAX_OPCODE_BITS - opcode_bits;
         self.add_opcode(Box::new(ExtOpcode {
+            name,
             exec,
+            dump,
             opcode_min: opcode << remaining_bits,
             opcode_max: (opcode + 1) << remaining_bits,
             total_bits: opcode_bits + arg_bits,
+            conts: EmbeddedConts::Inline,
         }))
     }
 
     pub fn add_ext_range(
         &mut self,
+        name: &'static str,
         opcode_min: u32,
         opcode_max: u32,
         total_bits: u16,
         exec: FnExecInstrFull,
+        dump: FnDumpInstrFull,
     ) -> Result<()> {
         let remaining_bits = MAX_OPCODE_BITS - total_bits;
         self.add_opcode(Box::new(ExtOpcode {
+            name,
             exec,
+            dump,
             opcode_min: opcode_min << remaining_bits,
             opcode_max: opcode_max << remaining_bits,
             total_bits,
+            conts: EmbeddedConts::None,
         }))
     }
 
@@ -188,10 +482,14 @@ impl Opcodes {
         debug_assert!(min < max);
         debug_assert!(max <= MAX_OPCODE);
 
-        if let Some((other_min, _)) = self.opcodes.range(min..).next() {
+        if let Some((other_min, other)) = self.opcodes.range(min..).next() {
             anyhow::ensure!(
                 max <= *other_min,
-                "Opcode overlaps with next min: {other_min:06x}"
+                "Opcode `{}` {} overlaps with next `{}` {}",
+                opcode.name(),
+                DisplayRange(min, max),
+                other.name(),
+                DisplayRange(*other_min, other.range().1),
             );
         }
 
@@ -201,7 +499,11 @@ impl Opcodes {
             debug_assert!(prev_min == *k);
             anyhow::ensure!(
                 prev_max <= min,
-                "Opcode overlaps with prev max: {prev_max:06x}"
+                "Opcode `{}` {} overlaps with prev `{}` {}",
+                opcode.name(),
+                DisplayRange(min, max),
+                prev.name(),
+                DisplayRange(prev_min, prev_max),
             );
         }
 
@@ -223,13 +525,15 @@ impl Opcode for DummyOpcode {
     }
 
     fn dispatch(&self, st: &mut VmState, _: u32, _: u16) -> VmResult<i32> {
-        st.gas.try_consume(GAS_PER_INSTRUCTION)?;
+        st.gas.try_consume_instruction_gas(0)?;
         vm_bail!(InvalidOpcode);
     }
 }
 
 struct SimpleOpcode {
+    name: &'static str,
     exec: FnExecInstrSimple,
+    dump: FnDumpInstrSimple,
     opcode_min: u32,
     opcode_max: u32,
     opcode_bits: u16,
@@ -240,17 +544,34 @@ impl Opcode for SimpleOpcode {
         (self.opcode_min, self.opcode_max)
     }
 
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn bits(&self) -> u16 {
+        self.opcode_bits
+    }
+
     fn dispatch(&self, st: &mut VmState, _: u32, bits: u16) -> VmResult<i32> {
-        st.gas
-            .try_consume(GAS_PER_INSTRUCTION + self.opcode_bits as u64 * GAS_PER_BIT)?;
+        st.gas.try_consume_instruction_gas(self.opcode_bits)?;
         vm_ensure!(bits >= self.opcode_bits, InvalidOpcode);
         st.code.range_mut().skip_first(self.opcode_bits, 0)?;
         (self.exec)(st)
     }
+
+    fn dump(&self, code: &mut CellSlice<'_>, _: u32, bits: u16) -> Option<String> {
+        if bits < self.opcode_bits {
+            return None;
+        }
+        code.skip_first(self.opcode_bits, 0).ok()?;
+        Some((self.dump)())
+    }
 }
 
 struct FixedOpcode {
+    name: &'static str,
     exec: FnExecInstrArg,
+    dump: FnDumpInstrArg,
     opcode_min: u32,
     opcode_max: u32,
     total_bits: u16,
@@ -261,20 +582,39 @@ impl Opcode for FixedOpcode {
         (self.opcode_min, self.opcode_max)
     }
 
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn bits(&self) -> u16 {
+        self.total_bits
+    }
+
     fn dispatch(&self, st: &mut VmState, opcode: u32, bits: u16) -> VmResult<i32> {
-        st.gas
-            .try_consume(GAS_PER_INSTRUCTION + self.total_bits as u64 * GAS_PER_BIT)?;
+        st.gas.try_consume_instruction_gas(self.total_bits)?;
         vm_ensure!(bits >= self.total_bits, InvalidOpcode);
         st.code.range_mut().skip_first(self.total_bits, 0)?;
         (self.exec)(st, opcode >> (MAX_OPCODE_BITS - self.total_bits))
     }
+
+    fn dump(&self, code: &mut CellSlice<'_>, opcode: u32, bits: u16) -> Option<String> {
+        if bits < self.total_bits {
+            return None;
+        }
+        let res = (self.dump)(opcode >> (MAX_OPCODE_BITS - self.total_bits))?;
+        code.skip_first(self.total_bits, 0).ok()?;
+        Some(res)
+    }
 }
 
 struct ExtOpcode {
+    name: &'static str,
     exec: FnExecInstrFull,
+    dump: FnDumpInstrFull,
     opcode_min: u32,
     opcode_max: u32,
     total_bits: u16,
+    conts: EmbeddedConts,
 }
 
 impl Opcode for ExtOpcode {
@@ -282,9 +622,20 @@ impl Opcode for ExtOpcode {
         (self.opcode_min, self.opcode_max)
     }
 
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn bits(&self) -> u16 {
+        self.total_bits
+    }
+
+    fn embedded_conts(&self) -> EmbeddedConts {
+        self.conts
+    }
+
     fn dispatch(&self, st: &mut VmState, opcode: u32, bits: u16) -> VmResult<i32> {
-        st.gas
-            .try_consume(GAS_PER_INSTRUCTION + self.total_bits as u64 * GAS_PER_BIT)?;
+        st.gas.try_consume_instruction_gas(self.total_bits)?;
         vm_ensure!(bits >= self.total_bits, InvalidOpcode);
         (self.exec)(
             st,
@@ -292,6 +643,25 @@ impl Opcode for ExtOpcode {
             self.total_bits,
         )
     }
+
+    fn dump(&self, code: &mut CellSlice<'_>, opcode: u32, bits: u16) -> Option<String> {
+        if bits < self.total_bits {
+            return None;
+        }
+        (self.dump)(
+            code,
+            opcode >> (MAX_OPCODE_BITS - self.total_bits),
+            self.total_bits,
+        )
+    }
+}
+
+struct DisplayRange(u32, u32);
+
+impl std::fmt::Display for DisplayRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{:06x}..{:06x})", self.0, self.1)
+    }
 }
 
 /// Fn pointer for a simple opcode.
@@ -303,18 +673,34 @@ pub type FnExecInstrArg = fn(&mut VmState, u32) -> VmResult<i32>;
 /// Fn pointer for an extended opcode.
 pub type FnExecInstrFull = fn(&mut VmState, u32, u16) -> VmResult<i32>;
 
+/// Fn pointer for a simple opcode formatter.
+pub type FnDumpInstrSimple = fn() -> String;
+
+/// Fn pointer for a formatter of an opcode with a single argument.
+///
+/// Returns `None` if the argument is invalid.
+pub type FnDumpInstrArg = fn(u32) -> Option<String>;
+
+/// Fn pointer for an extended opcode formatter.
+///
+/// Must skip the opcode with all of its inline data and references.
+pub type FnDumpInstrFull = fn(&mut CellSlice<'_>, u32, u16) -> Option<String>;
+
 const MAX_OPCODE_BITS: u16 = 24;
 const MAX_OPCODE: u32 = 1 << MAX_OPCODE_BITS;
-
-const GAS_PER_INSTRUCTION: u64 = 10;
-const GAS_PER_BIT: u64 = 1;
+const FIRST_BYTE_SHIFT: u16 = MAX_OPCODE_BITS - 8;
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+
+    use everscale_types::cell::CellType;
+    use everscale_types::models::SimpleLib;
+
     use super::*;
     use crate::cont::QuitCont;
     use crate::error::VmError;
-    use crate::gas::{GasConsumer, GasParams};
+    use crate::gas::NoLibraries;
     use crate::saferc::SafeRc;
     use crate::smc_info::VmVersion;
 
@@ -332,10 +718,15 @@ mod tests {
             quit0: SafeRc::from(QuitCont { exit_code: 0 }),
             quit1: SafeRc::from(QuitCont { exit_code: 0 }),
             gas: GasConsumer::new(GasParams::getter()),
-            cp: Box::leak(Box::new(cp)),
+            cp: &cp,
+            codepages: &[],
             debug: None,
+            on_step: None,
+            step_limit: None,
             modifiers: Default::default(),
             version: VmVersion::LATEST_TON,
+            #[cfg(feature = "tracing")]
+            log_ops: true,
         };
 
         let dummy = state.cp.lookup(0x800000);
@@ -350,31 +741,398 @@ mod tests {
         // Simple overlap
         {
             let mut cp = DispatchTable::builder(123);
-            cp.add_simple(0xab, 8, |_| Ok(0)).unwrap();
-            cp.add_simple(0xab, 8, |_| Ok(0)).unwrap_err();
+            cp.add_simple("A", 0xab, 8, |_| Ok(0), String::new).unwrap();
+            cp.add_simple("A", 0xab, 8, |_| Ok(0), String::new)
+                .unwrap_err();
         }
 
         // Range-simple overlap
         {
             let mut cp = DispatchTable::builder(123);
-            cp.add_simple(0xab, 8, |_| Ok(0)).unwrap();
-            cp.add_fixed_range(0xa0, 0xaf, 8, 4, |_, _| Ok(0))
+            cp.add_simple("A", 0xab, 8, |_| Ok(0), String::new).unwrap();
+            cp.add_fixed_range("B", 0xa0, 0xaf, 8, 4, |_, _| Ok(0), |_| None)
                 .unwrap_err();
         }
 
         // Simple-range overlap
         {
             let mut cp = DispatchTable::builder(123);
-            cp.add_fixed_range(0xa0, 0xaf, 8, 4, |_, _| Ok(0)).unwrap();
-            cp.add_simple(0xab, 8, |_| Ok(0)).unwrap_err();
+            cp.add_fixed_range("B", 0xa0, 0xaf, 8, 4, |_, _| Ok(0), |_| None)
+                .unwrap();
+            cp.add_simple("A", 0xab, 8, |_| Ok(0), String::new)
+                .unwrap_err();
         }
 
         // Range-range overlap
         {
             let mut cp = DispatchTable::builder(123);
-            cp.add_fixed_range(0xa0, 0xaf, 8, 4, |_, _| Ok(0)).unwrap();
-            cp.add_fixed_range(0xa4, 0xa7, 8, 2, |_, _| Ok(0))
+            cp.add_fixed_range("B", 0xa0, 0xaf, 8, 4, |_, _| Ok(0), |_| None)
+                .unwrap();
+            cp.add_fixed_range("C", 0xa4, 0xa7, 8, 2, |_, _| Ok(0), |_| None)
                 .unwrap_err();
         }
     }
+
+    #[test]
+    fn opcode_overlap_error_names_both_opcodes() {
+        let mut cp = DispatchTable::builder(123);
+        cp.add_simple("NOP", 0x00, 8, |_| Ok(0), String::new)
+            .unwrap();
+        cp.add_simple("SWAP", 0x01, 8, |_| Ok(0), String::new)
+            .unwrap();
+
+        // Overlaps with the next opcode
+        let err = cp
+            .add_fixed_range("XCHG s{i}", 0x0, 0x2, 4, 4, |_, _| Ok(0), |_| None)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Opcode `XCHG s{i}` [000000..200000) overlaps with next `NOP` [000000..010000)"
+        );
+
+        // Overlaps with the previous opcode
+        let err = cp
+            .add_fixed_range("DUP", 0x018, 0x01c, 12, 2, |_, _| Ok(0), |_| None)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Opcode `DUP` [018000..01c000) overlaps with prev `SWAP` [010000..020000)"
+        );
+    }
+
+    #[test]
+    fn lookup_matches_opcode_ranges() {
+        let cp = crate::instr::codepage0();
+
+        for (min, opcode, _) in &cp.opcodes {
+            let (_, max) = opcode.range();
+            assert_eq!(cp.lookup(*min).range(), (*min, max));
+            assert_eq!(cp.lookup(max - 1).range(), (*min, max));
+        }
+
+        for opcode in (0..MAX_OPCODE).step_by(997) {
+            let (min, max) = cp.lookup(opcode).range();
+            assert!(min <= opcode && opcode < max);
+        }
+
+        let empty = DispatchTable::builder(123).build();
+        assert_eq!(empty.lookup(0xabcdef).range(), (0, MAX_OPCODE));
+    }
+
+    #[test]
+    fn opcodes_metadata() {
+        assert_eq!(DispatchTable::builder(123).build().opcodes().count(), 0);
+
+        let cp = crate::instr::codepage0();
+        let opcodes = cp.opcodes().collect::<Vec<_>>();
+        for op in &opcodes {
+            assert!(op.min < op.max, "{op:?}");
+            assert!(op.bits > 0 && op.bits <= MAX_OPCODE_BITS, "{op:?}");
+            assert!(!op.name.is_empty(), "{op:?}");
+            assert_eq!(cp.lookup(op.min).name(), op.name);
+        }
+        for pair in opcodes.windows(2) {
+            assert!(pair[0].max <= pair[1].min);
+        }
+
+        let find = |name: &str| {
+            *opcodes
+                .iter()
+                .find(|op| op.name == name)
+                .unwrap_or_else(|| panic!("opcode {name} not found"))
+        };
+        let expected = [
+            ("ADD", 0xa0, 8),
+            ("SUB", 0xa1, 8),
+            ("SUBR", 0xa2, 8),
+            ("NEGATE", 0xa3, 8),
+            ("INC", 0xa4, 8),
+            ("DEC", 0xa5, 8),
+            ("ADDINT {y}", 0xa6, 16),
+            ("MULINT {y}", 0xa7, 16),
+            ("MUL", 0xa8, 8),
+        ];
+        for (name, prefix, bits) in expected {
+            assert_eq!(find(name), OpcodeInfo {
+                min: prefix << 16,
+                max: (prefix + 1) << 16,
+                bits,
+                name,
+            });
+        }
+
+        let expected = [
+            ("DIV", 0xa90, 16),
+            ("RSHIFT", 0xa92, 16),
+            ("RSHIFT#", 0xa93, 24),
+            ("MULDIV", 0xa98, 16),
+            ("MULRSHIFT", 0xa9a, 16),
+            ("LSHIFTDIV", 0xa9c, 16),
+        ];
+        for (name, prefix, bits) in expected {
+            assert_eq!(find(name), OpcodeInfo {
+                min: prefix << 12,
+                max: (prefix + 1) << 12,
+                bits,
+                name,
+            });
+        }
+    }
+
+    #[test]
+    fn disassemble_code() -> anyhow::Result<()> {
+        let cp = crate::instr::codepage0();
+
+        let code = Boc::decode(tvmasm!("INT 1 INT 1000 ADD"))?;
+        let hash = *code.repr_hash();
+        assert_eq!(
+            cp.disassemble(&OwnedCellSlice::new_allow_exotic(code), &NoLibraries),
+            [
+                (hash, 0, "PUSHINT 1".to_owned()),
+                (hash, 8, "PUSHINT 1000".to_owned()),
+                (hash, 32, "ADD".to_owned()),
+            ]
+        );
+
+        // Implicit jump to the first reference
+        let child = CellBuilder::build_from(0xa0u8)?;
+        let code = {
+            let mut b = CellBuilder::new();
+            b.store_u8(0x71)?;
+            b.store_reference(child.clone())?;
+            b.build()?
+        };
+        let hash = *code.repr_hash();
+        assert_eq!(
+            cp.disassemble(&OwnedCellSlice::new_allow_exotic(code), &NoLibraries),
+            [
+                (hash, 0, "PUSHINT 1".to_owned()),
+                (*child.repr_hash(), 0, "ADD".to_owned())
+            ]
+        );
+
+        // Inline continuation
+        let code = Boc::decode(tvmasm!("PUSHCONT { INT 2 } EXECUTE"))?;
+        let hash = *code.repr_hash();
+        let ops = cp.disassemble(&OwnedCellSlice::new_allow_exotic(code), &NoLibraries);
+        assert_eq!(ops[1..], [
+            (hash, 16, "EXECUTE".to_owned()),
+            (hash, 8, "PUSHINT 2".to_owned()),
+        ]);
+
+        // Truncated opcode
+        let code = {
+            let mut b = CellBuilder::new();
+            b.store_u8(0x71)?;
+            b.store_u16(0x8100)?;
+            b.build()?
+        };
+        let hash = *code.repr_hash();
+        let ops = cp.disassemble(&OwnedCellSlice::new_allow_exotic(code), &NoLibraries);
+        assert_eq!(ops, [(hash, 0, "PUSHINT 1".to_owned())]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_code() -> anyhow::Result<()> {
+        let cp = crate::instr::codepage0();
+        let validate =
+            |code: Cell| cp.validate(&OwnedCellSlice::new_allow_exotic(code), &NoLibraries);
+
+        validate(Boc::decode(tvmasm!(
+            "INT 1 INT 1000 ADD PUSHCONT { INT 2 } EXECUTE"
+        ))?)?;
+        validate(Cell::empty_cell())?;
+
+        // Implicit jump to the first reference
+        let child = CellBuilder::build_from(0x8100u16)?;
+        let code = {
+            let mut b = CellBuilder::new();
+            b.store_u8(0x71)?;
+            b.store_reference(child.clone())?;
+            b.build()?
+        };
+        assert_eq!(
+            validate(code),
+            Err(DecodeError::MalformedOpcode {
+                cell: *child.repr_hash(),
+                offset: 0,
+                name: "PUSHINT {x}".to_owned(),
+            })
+        );
+
+        // Truncated PUSHINT
+        let code = {
+            let mut b = CellBuilder::new();
+            b.store_u8(0x71)?;
+            b.store_u16(0x8100)?;
+            b.build()?
+        };
+        assert_eq!(
+            validate(code.clone()),
+            Err(DecodeError::MalformedOpcode {
+                cell: *code.repr_hash(),
+                offset: 8,
+                name: "PUSHINT {x}".to_owned(),
+            })
+        );
+        let err = validate(Boc::decode(tvmasm!("@inline x{8210}"))?).unwrap_err();
+        assert!(matches!(err, DecodeError::MalformedOpcode {
+            offset: 0,
+            ..
+        }));
+
+        // Unknown opcode
+        let empty = DispatchTable::builder(123).build();
+        let code = CellBuilder::build_from(0x71u8)?;
+        assert_eq!(
+            empty.validate(
+                &OwnedCellSlice::new_allow_exotic(code.clone()),
+                &NoLibraries
+            ),
+            Err(DecodeError::UnknownOpcode {
+                cell: *code.repr_hash(),
+                offset: 0,
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_code_conts() -> anyhow::Result<()> {
+        let cp = crate::instr::codepage0();
+
+        let truncated = {
+            let mut b = CellBuilder::new();
+            b.store_u8(0x71)?;
+            b.store_u16(0x8100)?;
+            b.build()?
+        };
+        let with_ref = |opcode: &[u8], child: Cell| {
+            let mut b = CellBuilder::new();
+            b.store_raw(opcode, opcode.len() as u16 * 8)?;
+            b.store_reference(child)?;
+            b.build()
+        };
+        let malformed = |cell: &Cell, offset: u16| -> Result<(), DecodeError> {
+            Err(DecodeError::MalformedOpcode {
+                cell: *cell.repr_hash(),
+                offset,
+                name: "PUSHINT {x}".to_owned(),
+            })
+        };
+
+        // Continuations stored in references
+        for opcode in [&[0xdb, 0x3c][..], &[0xdb, 0x3d], &[0xe3, 0x00], &[0x8a]] {
+            let code = with_ref(opcode, truncated.clone())?;
+            assert_eq!(
+                cp.validate(&OwnedCellSlice::new_allow_exotic(code), &NoLibraries),
+                malformed(&truncated, 8)
+            );
+        }
+
+        let child = Boc::decode(tvmasm!("INT 1 ADD"))?;
+        let code = with_ref(&[0xdb, 0x3c], child.clone())?;
+        let ops = cp.disassemble(&OwnedCellSlice::new_allow_exotic(code), &NoLibraries);
+        assert_eq!(ops[1..], [
+            (*child.repr_hash(), 0, "PUSHINT 1".to_owned()),
+            (*child.repr_hash(), 8, "ADD".to_owned())
+        ]);
+
+        // Data references are not decoded
+        let code = with_ref(&[0x88], truncated.clone())?;
+        cp.validate(&OwnedCellSlice::new_allow_exotic(code), &NoLibraries)?;
+
+        // Truncated opcode inside an inline continuation
+        for (opcode, offset) in [(&[0x93][..], 16), (&[0x8e, 0x03], 24)] {
+            let code = {
+                let mut b = CellBuilder::new();
+                b.store_raw(opcode, opcode.len() as u16 * 8)?;
+                b.store_raw(&[0x71, 0x81, 0x00], 24)?;
+                b.store_u8(0xa0)?;
+                b.build()?
+            };
+            assert_eq!(
+                cp.validate(
+                    &OwnedCellSlice::new_allow_exotic(code.clone()),
+                    &NoLibraries
+                ),
+                malformed(&code, offset)
+            );
+        }
+
+        // Inline continuation with an implicit jump to its reference
+        let code = with_ref(&[0x8e, 0x81, 0x71], truncated.clone())?;
+        assert_eq!(
+            cp.validate(&OwnedCellSlice::new_allow_exotic(code), &NoLibraries),
+            malformed(&truncated, 8)
+        );
+
+        // Library cells are resolved
+        let library = {
+            let mut b = CellBuilder::new();
+            b.set_exotic(true);
+            b.store_u8(CellType::LibraryReference.to_byte())?;
+            b.store_u256(truncated.repr_hash())?;
+            b.build()?
+        };
+        let libraries = HashMap::from([(*truncated.repr_hash(), SimpleLib {
+            public: true,
+            root: truncated.clone(),
+        })]);
+
+        for (opcode, offset) in [(&[0x71][..], 8), (&[0xdb, 0x3c], 0)] {
+            let code = with_ref(opcode, library.clone())?;
+            let hash = *code.repr_hash();
+            let code = OwnedCellSlice::new_allow_exotic(code);
+            assert_eq!(
+                cp.validate(&code, &NoLibraries),
+                Err(DecodeError::MissingLibrary {
+                    cell: hash,
+                    offset,
+                    hash: *truncated.repr_hash(),
+                })
+            );
+            assert_eq!(cp.validate(&code, &libraries), malformed(&truncated, 8));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn stack_underflow_context() -> anyhow::Result<()> {
+        let code = CellBuilder::build_from(0xa0u8)?;
+        let mut vm = VmState::builder()
+            .with_code(code)
+            .with_stack([SafeRc::new_dyn_value(num_bigint::BigInt::from(1))])
+            .with_gas(GasParams::getter())
+            .build();
+
+        // ADD on a single value
+        let err = vm.step().unwrap_err();
+        match *err {
+            VmError::StackUnderflow {
+                required,
+                op,
+                actual,
+            } => {
+                assert_eq!(op, Some("ADD"));
+                assert_eq!(required, 2);
+                assert_eq!(actual, Some(1));
+            }
+            ref e => panic!("unexpected error: {e:?}"),
+        }
+        assert_eq!(
+            err.to_string(),
+            "stack underflow: required depth 2 in ADD (initial depth 1)"
+        );
+        assert_eq!(
+            err.as_exception(),
+            crate::error::VmException::StackUnderflow
+        );
+
+        Ok(())
+    }
 }
@@ -1,3 +1,4 @@
+use everscale_types::cell::HashBytes;
 use everscale_types::error::Error;
 
 use crate::stack::StackValueType;
@@ -8,8 +9,16 @@ pub type VmResult<T> = Result<T, Box<VmError>>;
 /// VM execution error.
 #[derive(Debug, thiserror::Error)]
 pub enum VmError {
-    #[error("stack underflow at depth {0}")]
-    StackUnderflow(usize),
+    #[error("stack underflow: required depth {required}{}", OpContext(.op, .actual))]
+    StackUnderflow {
+        /// Required stack depth. Includes the values which were already
+        /// popped by the opcode (see [`VmError::with_op_context`]).
+        required: usize,
+        /// Opcode name (see [`VmError::with_op_context`]).
+        op: Option<&'static str>,
+        /// Stack depth before the opcode (see [`VmError::with_op_context`]).
+        actual: Option<usize>,
+    },
     #[error("too many arguments copied into a closure continuation: {0}")]
     TooManyArguments(usize),
     #[error("expected integer in range {min}..={max}, found {actual}")]
@@ -18,6 +27,12 @@ pub enum VmError {
         max: isize,
         actual: String,
     },
+    #[error("dictionary key {actual} is out of range {min}..={max}")]
+    DictKeyOutOfRange {
+        min: String,
+        max: String,
+        actual: String,
+    },
     #[error("control register index out of range: {0}")]
     ControlRegisterOutOfRange(usize),
     #[error("control register redefined")]
@@ -37,26 +52,110 @@ pub enum VmError {
     CellError(#[from] Error),
     #[error("dict error")]
     DictError,
+    #[error("step limit exceeded: {0}")]
+    StepLimitExceeded(u64),
     #[error("unknown error. {0}")]
     Unknown(String),
 }
 
+/// Optional opcode context of [`VmError::StackUnderflow`].
+struct OpContext<'a>(&'a Option<&'static str>, &'a Option<usize>);
+
+impl std::fmt::Display for OpContext<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.0, self.1) {
+            (Some(op), Some(actual)) => write!(f, " in {op} (initial depth {actual})"),
+            _ => Ok(()),
+        }
+    }
+}
+
 impl VmError {
     pub fn is_out_of_gas(&self) -> bool {
         matches!(self, Self::OutOfGas | Self::CellError(Error::Cancelled))
     }
 
+    /// Stack underflow without the opcode context.
+    ///
+    /// `required` is the number of values which must be on the current stack.
+    pub const fn stack_underflow(required: usize) -> Self {
+        Self::StackUnderflow {
+            required,
+            op: None,
+            actual: None,
+        }
+    }
+
+    /// Adds the opcode context to the stack underflow error.
+    ///
+    /// `actual` is the stack depth before the opcode was executed and `depth`
+    /// is the depth after the failure. The difference is the number of values
+    /// already popped by the opcode, so it is added to the required depth.
+    pub fn with_op_context(
+        mut self: Box<Self>,
+        op: &'static str,
+        actual: usize,
+        depth: usize,
+    ) -> Box<Self> {
+        if let Self::StackUnderflow {
+            required,
+            op: op_ctx,
+            actual: actual_ctx,
+        } = &mut *self
+        {
+            *required += actual.saturating_sub(depth);
+            *op_ctx = Some(op);
+            *actual_ctx = Some(actual);
+        }
+        self
+    }
+
+    /// Returns the TVM exception which corresponds to this error.
+    ///
+    /// | Error | Exception | Exception number |
+    /// |-|-|-|
+    /// | [`StackUnderflow`] | [`VmException::StackUnderflow`] | 2 |
+    /// | [`TooManyArguments`] | [`VmException::StackOverflow`] | 3 |
+    /// | [`IntegerOverflow`] | [`VmException::IntOverflow`] | 4 |
+    /// | [`IntegerOutOfRange`], [`DictKeyOutOfRange`], [`ControlRegisterOutOfRange`] | [`VmException::RangeCheck`] | 5 |
+    /// | [`InvalidOpcode`] | [`VmException::InvalidOpcode`] | 6 |
+    /// | [`InvalidType`], [`ControlRegisterRedefined`] | [`VmException::TypeCheck`] | 7 |
+    /// | [`CellError`] (cell overflow) | [`VmException::CellOverflow`] | 8 |
+    /// | [`CellError`] (cell underflow) | [`VmException::CellUnderflow`] | 9 |
+    /// | [`DictError`] | [`VmException::DictError`] | 10 |
+    /// | [`Unknown`] | [`VmException::Unknown`] | 11 |
+    /// | [`StepLimitExceeded`] | [`VmException::Fatal`] | 12 |
+    /// | [`OutOfGas`] | [`VmException::OutOfGas`] | 13 |
+    /// | [`CellError`] (unexpected cell type) | [`VmException::VirtError`] | 14 |
+    ///
+    /// [`StackUnderflow`]: Self::StackUnderflow
+    /// [`TooManyArguments`]: Self::TooManyArguments
+    /// [`IntegerOverflow`]: Self::IntegerOverflow
+    /// [`IntegerOutOfRange`]: Self::IntegerOutOfRange
+    /// [`DictKeyOutOfRange`]: Self::DictKeyOutOfRange
+    /// [`ControlRegisterOutOfRange`]: Self::ControlRegisterOutOfRange
+    /// [`InvalidOpcode`]: Self::InvalidOpcode
+    /// [`InvalidType`]: Self::InvalidType
+    /// [`ControlRegisterRedefined`]: Self::ControlRegisterRedefined
+    /// [`CellError`]: Self::CellError
+    /// [`DictError`]: Self::DictError
+    /// [`Unknown`]: Self::Unknown
+    /// [`StepLimitExceeded`]: Self::StepLimitExceeded
+    /// [`OutOfGas`]: Self::OutOfGas
     pub fn as_exception(&self) -> VmException {
         match self {
-            Self::StackUnderflow(_) => VmException::StackUnderflow,
+            Self::StackUnderflow { .. } => VmException::StackUnderflow,
             Self::TooManyArguments(_) => VmException::StackOverflow,
-            Self::IntegerOutOfRange { .. } => VmException::RangeCheck,
+            Self::IntegerOutOfRange { .. } | Self::DictKeyOutOfRange { .. } => {
+                VmException::RangeCheck
+            }
             Self::ControlRegisterOutOfRange(_) => VmException::RangeCheck,
             Self::ControlRegisterRedefined => VmException::TypeCheck,
             Self::IntegerOverflow => VmException::IntOverflow,
             Self::InvalidOpcode => VmException::InvalidOpcode,
             Self::InvalidType { .. } => VmException::TypeCheck,
             Self::OutOfGas => VmException::OutOfGas,
+            Self::StepLimitExceeded(_) => VmException::Fatal,
             Self::Unknown(_) => VmException::Unknown,
             Self::CellError(e) => match e {
                 Error::CellUnderflow => VmException::CellUnderflow,
@@ -73,6 +172,13 @@ impl VmError {
     }
 }
 
+impl From<&VmError> for VmException {
+    #[inline]
+    fn from(e: &VmError) -> Self {
+        e.as_exception()
+    }
+}
+
 impl From<Error> for Box<VmError> {
     #[inline]
     fn from(e: Error) -> Self {
@@ -80,6 +186,52 @@ impl From<Error> for Box<VmError> {
     }
 }
 
+/// Code decoding error.
+#[derive(Debug, Clone, Eq, PartialEq, thiserror::Error)]
+pub enum DecodeError {
+    #[error("unknown opcode at {cell}:{offset}")]
+    UnknownOpcode { cell: HashBytes, offset: u16 },
+    #[error("malformed opcode `{name}` at {cell}:{offset}")]
+    MalformedOpcode {
+        cell: HashBytes,
+        offset: u16,
+        name: String,
+    },
+    #[error("unexpected exotic cell at {cell}:{offset}")]
+    ExoticCell { cell: HashBytes, offset: u16 },
+    #[error("missing library {hash} at {cell}:{offset}")]
+    MissingLibrary {
+        cell: HashBytes,
+        offset: u16,
+        hash: HashBytes,
+    },
+}
+
+/// Typed stack decoding error (see [`FromStack`]).
+///
+/// [`FromStack`]: crate::stack::FromStack
+#[derive(Debug, thiserror::Error)]
+pub enum StackDecodeError {
+    #[error("expected {expected} stack values, found {actual}")]
+    DepthMismatch { expected: usize, actual: usize },
+    #[error("invalid stack value at index {index}")]
+    InvalidValue {
+        /// Value index from the bottom of the stack.
+        index: usize,
+        #[source]
+        source: Box<VmError>,
+    },
+}
+
+/// Invalid set of additional codepages.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, thiserror::Error)]
+pub enum CodepageError {
+    #[error("codepage 0 is reserved for the default one")]
+    ReservedId,
+    #[error("duplicate codepage {0}")]
+    DuplicateId(u16),
+}
+
 /// A code for an execution error.
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
 #[repr(u8)]
@@ -128,3 +280,67 @@ impl std::fmt::Display for VmException {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exception_mapping() {
+        let cases = [
+            (VmError::stack_underflow(0), 2),
+            (
+                VmError::StackUnderflow {
+                    required: 1,
+                    op: Some("ADD"),
+                    actual: Some(1),
+                },
+                2,
+            ),
+            (VmError::TooManyArguments(300), 3),
+            (VmError::IntegerOverflow, 4),
+            (VmError::CellError(Error::IntOverflow), 4),
+            (
+                VmError::IntegerOutOfRange {
+                    min: 0,
+                    max: 255,
+                    actual: "256".to_owned(),
+                },
+                5,
+            ),
+            (
+                VmError::DictKeyOutOfRange {
+                    min: "-128".to_owned(),
+                    max: "127".to_owned(),
+                    actual: "128".to_owned(),
+                },
+                5,
+            ),
+            (VmError::ControlRegisterOutOfRange(6), 5),
+            (VmError::InvalidOpcode, 6),
+            (
+                VmError::InvalidType {
+                    expected: StackValueType::Int,
+                    actual: StackValueType::Null,
+                },
+                7,
+            ),
+            (VmError::ControlRegisterRedefined, 7),
+            (VmError::CellError(Error::CellOverflow), 8),
+            (VmError::CellError(Error::CellUnderflow), 9),
+            (VmError::DictError, 10),
+            (VmError::Unknown("test".to_owned()), 11),
+            (VmError::StepLimitExceeded(10), 12),
+            (VmError::OutOfGas, 13),
+            (VmError::CellError(Error::Cancelled), 13),
+            (VmError::CellError(Error::UnexpectedExoticCell), 14),
+            (VmError::CellError(Error::UnexpectedOrdinaryCell), 14),
+        ];
+
+        for (error, code) in cases {
+            let exception = VmException::from(&error);
+            assert_eq!(exception as i32, code, "{error:?}");
+            assert_eq!(exception.as_exit_code(), !code, "{error:?}");
+        }
+    }
+}
@@ -55,6 +55,38 @@ impl Default for GasParams {
     }
 }
 
+/// Gas prices of basic operations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GasPrices {
+    /// Base price of each executed instruction.
+    pub instruction: u64,
+    /// Price of each bit of the instruction opcode.
+    pub instruction_bit: u64,
+    /// Price of building a new cell.
+    pub build_cell: u64,
+    /// Price of the first load of a cell.
+    pub new_cell: u64,
+    /// Price of all subsequent loads of the same cell.
+    pub old_cell: u64,
+}
+
+impl GasPrices {
+    pub const DEFAULT: Self = Self {
+        instruction: 10,
+        instruction_bit: 1,
+        build_cell: 500,
+        new_cell: 100,
+        old_cell: 25,
+    };
+}
+
+impl Default for GasPrices {
+    #[inline]
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
 /// Library cells resolver.
 pub trait LibraryProvider {
     fn find(&self, library_hash: &HashBytes) -> Result<Option<Cell>, Error>;
@@ -293,6 +325,8 @@ pub struct GasConsumer<'l> {
     gas_remaining: std::cell::Cell<u64>,
     /// Gas price (fixed point with 16 bits for fractional part).
     gas_price: NonZeroU64,
+    /// Gas prices of basic operations.
+    prices: GasPrices,
 
     /// A set of visited cells.
     loaded_cells: std::cell::UnsafeCell<HashSet<HashBytes>>,
@@ -304,13 +338,14 @@ pub struct GasConsumer<'l> {
 
     // Missing library in case of resolving error occured.
     missing_library: std::cell::Cell<Option<HashBytes>>,
+
+    /// Optional gas consumption callback.
+    gas_trace: Option<std::cell::RefCell<GasTrace<'l>>>,
+    /// Step and opcode for the next traced events.
+    trace_context: std::cell::Cell<(u64, Option<&'static str>)>,
 }
 
 impl<'l> GasConsumer<'l> {
-    pub const BUILD_CELL_GAS: u64 = 500;
-    pub const NEW_CELL_GAS: u64 = 100;
-    pub const OLD_CELL_GAS: u64 = 25;
-
     pub const FREE_STACK_DEPTH: usize = 32;
     pub const FREE_SIGNATURE_CHECKS: usize = 10;
     pub const FREE_NESTED_CONT_JUMP: usize = 8;
@@ -319,9 +354,11 @@ impl<'l> GasConsumer<'l> {
     pub const TUPLE_ENTRY_GAS_PRICE: u64 = 1;
     pub const HASH_EXT_ENTRY_GAS_PRICE: u64 = 1;
     pub const CHK_SGN_GAS_PRICE: u64 = 4000;
+    pub const ECRECOVER_GAS_PRICE: u64 = 1500;
     pub const IMPLICIT_JMPREF_GAS_PRICE: u64 = 10;
     pub const IMPLICIT_RET_GAS_PRICE: u64 = 5;
     pub const EXCEPTION_GAS_PRICE: u64 = 50;
+    pub const RUNVM_GAS_PRICE: u64 = 40;
 
     pub fn new(params: GasParams) -> Self {
         static NO_LIBRARIES: NoLibraries = NoLibraries;
@@ -339,13 +376,93 @@ impl<'l> GasConsumer<'l> {
             gas_base: std::cell::Cell::new(gas_remaining),
             gas_remaining: std::cell::Cell::new(gas_remaining),
             gas_price: NonZeroU64::new(params.price).unwrap_or(NonZeroU64::MIN),
+            prices: GasPrices::DEFAULT,
             loaded_cells: Default::default(),
             libraries,
             chksign_counter: std::cell::Cell::new(0),
             missing_library: std::cell::Cell::new(None),
+            gas_trace: None,
+            trace_context: std::cell::Cell::new((0, None)),
         }
     }
 
+    /// Sets a callback which is called on each gas consumption.
+    pub fn set_gas_trace<F>(&mut self, trace: F)
+    where
+        F: FnMut(GasEvent) + 'l,
+    {
+        self.gas_trace = Some(std::cell::RefCell::new(Box::new(trace)));
+    }
+
+    /// Removes the gas consumption callback.
+    pub fn clear_gas_trace(&mut self) {
+        self.gas_trace = None;
+    }
+
+    #[inline]
+    pub fn has_gas_trace(&self) -> bool {
+        self.gas_trace.is_some()
+    }
+
+    /// Sets the step and the opcode name for the next traced events.
+    #[inline]
+    pub fn set_trace_context(&self, step: u64, opcode_name: Option<&'static str>) {
+        self.trace_context.set((step, opcode_name));
+    }
+
+    /// Resets all counters to the new params and clears the set
+    /// of loaded cells, keeping its allocated memory.
+    ///
+    /// NOTE: The set of loaded cells is the only state which is kept
+    /// between runs. A consumer which is not reset keeps the loaded-cell
+    /// discount, e.g. for a sequence of executions within one transaction.
+    pub fn reset(&mut self, params: GasParams) {
+        let gas_remaining = params.limit.saturating_add(params.credit);
+
+        self.gas_max = params.max;
+        self.gas_limit.set(params.limit);
+        self.gas_credit.set(params.credit);
+        self.gas_base.set(gas_remaining);
+        self.gas_remaining.set(gas_remaining);
+        self.gas_price = NonZeroU64::new(params.price).unwrap_or(NonZeroU64::MIN);
+        self.loaded_cells.get_mut().clear();
+        self.chksign_counter.set(0);
+        self.missing_library.set(None);
+    }
+
+    /// Captures all gas counters and the set of loaded cells.
+    pub fn snapshot(&self) -> GasSnapshot {
+        GasSnapshot {
+            limit: self.gas_limit.get(),
+            credit: self.gas_credit.get(),
+            base: self.gas_base.get(),
+            remaining: self.gas_remaining.get(),
+            chksign_counter: self.chksign_counter.get(),
+            // SAFETY: `loaded_cells` is only borrowed as mut in `load_cell_impl`
+            // which is not active at this point.
+            loaded_cells: unsafe { (*self.loaded_cells.get()).clone() },
+        }
+    }
+
+    /// Rewinds gas counters and the set of loaded cells to the snapshot.
+    pub fn restore(&mut self, snapshot: &GasSnapshot) {
+        self.gas_limit.set(snapshot.limit);
+        self.gas_credit.set(snapshot.credit);
+        self.gas_base.set(snapshot.base);
+        self.gas_remaining.set(snapshot.remaining);
+        self.chksign_counter.set(snapshot.chksign_counter);
+        self.loaded_cells
+            .get_mut()
+            .clone_from(&snapshot.loaded_cells);
+    }
+
+    /// Exchanges the set of loaded cells and the signature checks
+    /// counter with another consumer (e.g. for a nested VM).
+    pub(crate) fn swap_shared(&mut self, other: &mut GasConsumer<'_>) {
+        std::mem::swap(self.loaded_cells.get_mut(), other.loaded_cells.get_mut());
+        self.chksign_counter.swap(&other.chksign_counter);
+    }
+
     pub fn libraries(&self) -> &'l dyn LibraryProvider {
         self.libraries
     }
@@ -362,6 +479,10 @@ impl<'l> GasConsumer<'l> {
         self.gas_base.get()
     }
 
+    pub fn remaining(&self) -> u64 {
+        self.gas_remaining.get()
+    }
+
     pub fn limit(&self) -> u64 {
         self.gas_limit.get()
     }
@@ -392,16 +513,34 @@ impl<'l> GasConsumer<'l> {
         self.gas_price.get()
     }
 
+    pub fn prices(&self) -> &GasPrices {
+        &self.prices
+    }
+
+    /// Sets gas prices of basic operations (kept by [`reset`]).
+    ///
+    /// [`reset`]: Self::reset
+    pub fn set_prices(&mut self, prices: GasPrices) {
+        self.prices = prices;
+    }
+
+    pub fn try_consume_instruction_gas(&self, bits: u16) -> Result<(), Error> {
+        self.try_consume_ext(
+            self.prices.instruction + bits as u64 * self.prices.instruction_bit,
+            GasReason::Instruction,
+        )
+    }
+
     pub fn try_consume_exception_gas(&self) -> Result<(), Error> {
         self.try_consume(Self::EXCEPTION_GAS_PRICE)
     }
 
     pub fn try_consume_implicit_jmpref_gas(&self) -> Result<(), Error> {
-        self.try_consume(Self::IMPLICIT_JMPREF_GAS_PRICE)
+        self.try_consume_ext(Self::IMPLICIT_JMPREF_GAS_PRICE, GasReason::Instruction)
     }
 
     pub fn try_consume_implicit_ret_gas(&self) -> Result<(), Error> {
-        self.try_consume(Self::IMPLICIT_RET_GAS_PRICE)
+        self.try_consume_ext(Self::IMPLICIT_RET_GAS_PRICE, GasReason::Instruction)
     }
 
     pub fn try_consume_check_signature_gas(&self) -> Result<(), Error> {
@@ -432,8 +571,23 @@ impl<'l> GasConsumer<'l> {
     }
 
     pub fn try_consume(&self, amount: u64) -> Result<(), Error> {
-        if let Some(remaining) = self.gas_remaining.get().checked_sub(amount) {
+        self.try_consume_ext(amount, GasReason::Other)
+    }
+
+    fn try_consume_ext(&self, amount: u64, reason: GasReason) -> Result<(), Error> {
+        let gas_before = self.gas_remaining.get();
+        if let Some(remaining) = gas_before.checked_sub(amount) {
             self.gas_remaining.set(remaining);
+            if let Some(trace) = &self.gas_trace {
+                let (step, opcode_name) = self.trace_context.get();
+                (trace.borrow_mut())(GasEvent {
+                    step,
+                    opcode_name,
+                    gas_before,
+                    gas_after: remaining,
+                    reason,
+                });
+            }
             Ok(())
         } else {
             Err(Error::Cancelled)
@@ -465,11 +619,14 @@ impl<'l> GasConsumer<'l> {
                 let is_new =
                     unsafe { (*self.loaded_cells.get()).insert(*cell.as_ref().repr_hash()) };
 
-                ok!(self.try_consume(if is_new {
-                    GasConsumer::NEW_CELL_GAS
-                } else {
-                    GasConsumer::OLD_CELL_GAS
-                }));
+                ok!(self.try_consume_ext(
+                    if is_new {
+                        self.prices.new_cell
+                    } else {
+                        self.prices.old_cell
+                    },
+                    GasReason::CellLoad,
+                ));
             }
 
             if !mode.resolve() {
@@ -503,9 +660,51 @@ impl<'l> GasConsumer<'l> {
     }
 }
 
+/// A callback for gas consumption events.
+pub type GasTrace<'l> = Box<dyn FnMut(GasEvent) + 'l>;
+
+/// Gas consumption event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GasEvent {
+    /// VM step at which the gas was consumed.
+    pub step: u64,
+    /// Name of the executed opcode (`None` for implicit jumps and returns).
+    pub opcode_name: Option<&'static str>,
+    /// Remaining gas before the consumption.
+    pub gas_before: u64,
+    /// Remaining gas after the consumption.
+    pub gas_after: u64,
+    /// What the gas was consumed for.
+    pub reason: GasReason,
+}
+
+/// Reason of a gas consumption.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GasReason {
+    /// Basic instruction price.
+    Instruction,
+    /// Loading a cell.
+    CellLoad,
+    /// Building a new cell.
+    CellBuild,
+    /// Everything else (stack, tuples, exceptions, etc.).
+    Other,
+}
+
+/// Gas counters captured by [`GasConsumer::snapshot`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GasSnapshot {
+    limit: u64,
+    credit: u64,
+    base: u64,
+    remaining: u64,
+    chksign_counter: usize,
+    loaded_cells: HashSet<HashBytes>,
+}
+
 impl CellContext for GasConsumer<'_> {
     fn finalize_cell(&self, cell: CellParts<'_>) -> Result<Cell, Error> {
-        ok!(self.try_consume(GasConsumer::BUILD_CELL_GAS));
+        ok!(self.try_consume_ext(self.prices.build_cell, GasReason::CellBuild));
         Cell::empty_context().finalize_cell(cell)
     }
 
@@ -522,6 +721,21 @@ impl CellContext for GasConsumer<'_> {
     }
 }
 
+/// Counts the number of unique cells (by representation hash) in the tree.
+///
+/// Each unique cell is charged as [`GasPrices::new_cell`] on the first load,
+/// all other loads of the same cell are charged as [`GasPrices::old_cell`].
+pub fn count_unique_cells(root: &DynCell) -> usize {
+    let mut visited = HashSet::<&HashBytes>::default();
+    let mut stack = vec![root];
+    while let Some(cell) = stack.pop() {
+        if visited.insert(cell.repr_hash()) {
+            stack.extend(cell.references());
+        }
+    }
+    visited.len()
+}
+
 trait LoadLibrary<'a>: AsRef<DynCell> + 'a {
     fn load_library(gas: &'a GasConsumer, library_hash: &HashBytes) -> Result<Option<Self>, Error>
     where
@@ -550,6 +764,89 @@ impl LoadLibrary<'_> for Cell {
 mod tests {
     use super::*;
 
+    #[test]
+    fn count_unique_cells_works() {
+        fn count_all_cells(root: &DynCell) -> usize {
+            1 + root.references().map(count_all_cells).sum::<usize>()
+        }
+
+        let leaf = CellBuilder::build_from(0xdeadbeefu32).unwrap();
+        assert_eq!(count_unique_cells(leaf.as_ref()), 1);
+
+        // Two different nodes with the same shared leaf
+        let left = CellBuilder::build_from((1u8, leaf.clone())).unwrap();
+        let right = CellBuilder::build_from((2u8, leaf.clone(), leaf.clone())).unwrap();
+        // Same subtree is referenced twice
+        let root = CellBuilder::build_from((left.clone(), right, left)).unwrap();
+
+        assert_eq!(count_all_cells(root.as_ref()), 8);
+        assert_eq!(count_unique_cells(root.as_ref()), 4);
+    }
+
+    #[test]
+    fn reset_clears_loaded_cells() {
+        let cell = CellBuilder::build_from(0xdeadbeefu32).unwrap();
+
+        let mut gas = GasConsumer::new(GasParams::getter());
+        gas.load_cell_as_slice(cell.clone(), LoadMode::Full)
+            .unwrap();
+        assert_eq!(gas.consumed(), GasPrices::DEFAULT.new_cell);
+
+        // Same cell within one run
+        gas.load_cell_as_slice(cell.clone(), LoadMode::Full)
+            .unwrap();
+        assert_eq!(
+            gas.consumed(),
+            GasPrices::DEFAULT.new_cell + GasPrices::DEFAULT.old_cell
+        );
+
+        // Same cell after reset
+        gas.reset(GasParams::getter());
+        assert_eq!(gas.consumed(), 0);
+        assert_eq!(gas.limit(), GasParams::getter().limit);
+
+        gas.load_cell_as_slice(cell, LoadMode::Full).unwrap();
+        assert_eq!(gas.consumed(), GasPrices::DEFAULT.new_cell);
+    }
+
+    #[test]
+    fn load_library_cell() {
+        let code = Boc::decode(tvmasm!("NOP")).unwrap();
+        let library = {
+            let mut b = CellBuilder::new();
+            b.set_exotic(true);
+            b.store_u8(CellType::LibraryReference.to_byte()).unwrap();
+            b.store_u256(code.repr_hash()).unwrap();
+            b.build().unwrap()
+        };
+
+        // Unknown library
+        let gas = GasConsumer::new(GasParams::getter());
+        let res = gas.load_cell_as_slice(library.clone(), LoadMode::Full);
+        assert!(matches!(res, Err(Error::CellUnderflow)));
+        assert_eq!(gas.missing_library(), Some(*code.repr_hash()));
+
+        // Both the reference and the library root are charged
+        let libs = std::collections::HashMap::from([(*code.repr_hash(), SimpleLib {
+            public: true,
+            root: code.clone(),
+        })]);
+        let gas = GasConsumer::with_libraries(GasParams::getter(), &libs);
+        let cs = gas
+            .load_cell_as_slice(library.clone(), LoadMode::Full)
+            .unwrap();
+        assert_eq!(cs.cell().repr_hash(), code.repr_hash());
+        assert_eq!(gas.consumed(), 2 * GasPrices::DEFAULT.new_cell);
+        assert_eq!(gas.missing_library(), None);
+
+        // Both cells are now marked as loaded
+        gas.load_cell_as_slice(library, LoadMode::Full).unwrap();
+        assert_eq!(
+            gas.consumed(),
+            2 * GasPrices::DEFAULT.new_cell + 2 * GasPrices::DEFAULT.old_cell
+        );
+    }
+
     #[test]
     fn find_lib_dict_ref() {
         let lib1 = Boc::decode(tvmasm!("NOP")).unwrap();
@@ -622,4 +919,21 @@ mod tests {
             libraries.find_ref(lib2.repr_hash()).unwrap().unwrap()
         );
     }
+
+    #[test]
+    fn instruction_gas_vectors() {
+        let vectors = include!("../benches/common/gas_vectors.rs");
+        for (_, code, cell, stack, gas, exit_code) in vectors {
+            let (actual_exit_code, vm) = crate::tests::run_code(cell, stack);
+            assert_eq!(
+                actual_exit_code, exit_code,
+                "exit code mismatch for `{code}`: expected {exit_code}, actual {actual_exit_code}"
+            );
+            let actual_gas = vm.gas.consumed();
+            assert_eq!(
+                actual_gas, gas,
+                "gas mismatch for `{code}`: expected {gas}, actual {actual_gas}"
+            );
+        }
+    }
 }
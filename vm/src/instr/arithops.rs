@@ -1,6 +1,7 @@
 use std::cmp::Ordering;
 
 use anyhow::Result;
+use everscale_types::cell::CellSlice;
 use num_bigint::{BigInt, Sign};
 use num_integer::Integer;
 use num_traits::Zero;
@@ -8,9 +9,11 @@ use tycho_vm_proc::vm_module;
 
 use crate::dispatch::Opcodes;
 use crate::error::VmResult;
+use crate::int257::Int257;
 use crate::saferc::SafeRc;
+use crate::stack::{InlineInt, Stack};
 use crate::state::VmState;
-use crate::util::load_int_from_slice;
+use crate::util::{load_ext_operand, load_int_from_slice};
 
 pub struct ArithOps;
 
@@ -20,7 +23,14 @@ impl ArithOps {
 
     #[init]
     fn init_int_const_ext(&self, t: &mut Opcodes) -> Result<()> {
-        t.add_ext_range(0x82 << 5, (0x82 << 5) + 31, 13, exec_push_int)?;
+        t.add_ext_range(
+            "PUSHINT",
+            0x82 << 5,
+            (0x82 << 5) + 31,
+            13,
+            exec_push_int,
+            dump_push_int,
+        )?;
         Ok(())
     }
 
@@ -32,6 +42,8 @@ impl ArithOps {
         Ok(0)
     }
 
+    // NOTE: Only the 13-bit prefix is charged by the dispatcher,
+    // the immediate value itself is free (same as in the reference node).
     fn exec_push_int(st: &mut VmState, args: u32, bits: u16) -> VmResult<i32> {
         let l = (args as u16 & 0b11111) + 2;
         let value_len = 3 + l * 8;
@@ -45,12 +57,20 @@ impl ArithOps {
         let int = load_int_from_slice(&mut cs, value_len, true)?;
         st.code.set_range(cs.range());
 
-        vm_log_op!("PUSHINT {int}");
+        vm_log_op!(st, "PUSHINT {int}");
 
         ok!(SafeRc::make_mut(&mut st.stack).push_int(int));
         Ok(0)
     }
 
+    fn dump_push_int(code: &mut CellSlice<'_>, args: u32, bits: u16) -> Option<String> {
+        let l = (args as u16 & 0b11111) + 2;
+        let value_len = 3 + l * 8;
+        let mut value = load_ext_operand(code, bits, value_len, 0)?;
+        let int = load_int_from_slice(&mut value, value_len, true).ok()?;
+        Some(format!("PUSHINT {int}"))
+    }
+
     #[op(code = "83xx @ ..83ff", fmt = "PUSHPOW2 {x}", args(x = (args & 0xff) + 1))]
     pub fn exec_push_pow2(st: &mut VmState, x: u32) -> VmResult<i32> {
         let stack = SafeRc::make_mut(&mut st.stack);
@@ -84,6 +104,10 @@ impl ArithOps {
     #[op(code = "b7a0", fmt = "QADD", args(quiet = true))]
     fn exec_add(st: &mut VmState, quiet: bool) -> VmResult<i32> {
         let stack = SafeRc::make_mut(&mut st.stack);
+        if try_inline_op::<2>(stack, |[x, y]| x.checked_add(y)) {
+            return Ok(0);
+        }
+
         let y = ok!(stack.pop_int_or_nan());
         let x = ok!(stack.pop_int_or_nan());
         match (x, y) {
@@ -101,6 +125,10 @@ impl ArithOps {
     #[op(code = "b7a1", fmt = "QSUB", args(quiet = true))]
     fn exec_sub(st: &mut VmState, quiet: bool) -> VmResult<i32> {
         let stack = SafeRc::make_mut(&mut st.stack);
+        if try_inline_op::<2>(stack, |[x, y]| x.checked_sub(y)) {
+            return Ok(0);
+        }
+
         let y = ok!(stack.pop_int_or_nan());
         let x = ok!(stack.pop_int_or_nan());
         match (x, y) {
@@ -153,6 +181,10 @@ impl ArithOps {
     #[op(code = "b7a4", fmt = "QINC", args(quiet = true))]
     fn exec_inc(st: &mut VmState, quiet: bool) -> VmResult<i32> {
         let stack = SafeRc::make_mut(&mut st.stack);
+        if try_inline_op::<1>(stack, |[x]| x.checked_add(Int257::ONE)) {
+            return Ok(0);
+        }
+
         match ok!(stack.pop_int_or_nan()) {
             Some(mut x) => {
                 *SafeRc::make_mut(&mut x) += 1;
@@ -168,6 +200,10 @@ impl ArithOps {
     #[op(code = "b7a5", fmt = "QDEC", args(quiet = true))]
     fn exec_dec(st: &mut VmState, quiet: bool) -> VmResult<i32> {
         let stack = SafeRc::make_mut(&mut st.stack);
+        if try_inline_op::<1>(stack, |[x]| x.checked_sub(Int257::ONE)) {
+            return Ok(0);
+        }
+
         match ok!(stack.pop_int_or_nan()) {
             Some(mut x) => {
                 *SafeRc::make_mut(&mut x) -= 1;
@@ -227,8 +263,8 @@ impl ArithOps {
     }
 
     // === Division instructions ===
-    #[op(code = "a90m", fmt = DumpDivmod(m), args(quiet = false))]
-    #[op(code = "b7a90m", fmt = DumpDivmod(m), args(quiet = true))]
+    #[op(code = "a90m", name = "DIV", fmt = DumpDivmod(m), args(quiet = false))]
+    #[op(code = "b7a90m", name = "QDIV", fmt = DumpDivmod(m), args(quiet = true))]
     fn exec_divmod(st: &mut VmState, m: u32, quiet: bool) -> VmResult<i32> {
         enum Operation {
             Div,
@@ -298,9 +334,19 @@ impl ArithOps {
         Ok(0)
     }
 
-    #[op(code = "a92m", fmt = DumpShr(m, false), args(imm = false, quiet = false))]
-    #[op(code = "a93mmm", fmt = DumpShr(m, true), args(imm = true, quiet = false))]
-    #[op(code = "b7a92m", fmt = ("Q{}", DumpShr(m, false)), args(imm = false, quiet = true))]
+    #[op(code = "a92m", name = "RSHIFT", fmt = DumpShr(m, false), args(imm = false, quiet = false))]
+    #[op(
+        code = "a93mmm",
+        name = "RSHIFT#",
+        fmt = DumpShr(m, true),
+        args(imm = true, quiet = false)
+    )]
+    #[op(
+        code = "b7a92m",
+        name = "QRSHIFT",
+        fmt = ("Q{}", DumpShr(m, false)),
+        args(imm = false, quiet = true)
+    )]
     fn exec_shrmod(st: &mut VmState, mut m: u32, imm: bool, quiet: bool) -> VmResult<i32> {
         enum Operation {
             RShift,
@@ -384,8 +430,8 @@ impl ArithOps {
         Ok(0)
     }
 
-    #[op(code = "a98m", fmt = ("MUL{}", DumpDivmod(m)), args(quiet = false))]
-    #[op(code = "b7a98m", fmt = ("QMUL{}", DumpDivmod(m)), args(quiet = true))]
+    #[op(code = "a98m", name = "MULDIV", fmt = ("MUL{}", DumpDivmod(m)), args(quiet = false))]
+    #[op(code = "b7a98m", name = "QMULDIV", fmt = ("QMUL{}", DumpDivmod(m)), args(quiet = true))]
     fn exec_muldivmod(st: &mut VmState, m: u32, quiet: bool) -> VmResult<i32> {
         #[allow(clippy::enum_variant_names)]
         enum Operation {
@@ -461,9 +507,24 @@ impl ArithOps {
         Ok(0)
     }
 
-    #[op(code = "a9am", fmt = ("MUL{}", DumpShr(m, false)), args(imm = false, quiet = false))]
-    #[op(code = "a9bmmm", fmt = ("MUL{}", DumpShr(m, true)), args(imm = true, quiet = false))]
-    #[op(code = "b7a9am", fmt = ("QMUL{}", DumpShr(m, false)), args(imm = false, quiet = true))]
+    #[op(
+        code = "a9am",
+        name = "MULRSHIFT",
+        fmt = ("MUL{}", DumpShr(m, false)),
+        args(imm = false, quiet = false)
+    )]
+    #[op(
+        code = "a9bmmm",
+        name = "MULRSHIFT#",
+        fmt = ("MUL{}", DumpShr(m, true)),
+        args(imm = true, quiet = false)
+    )]
+    #[op(
+        code = "b7a9am",
+        name = "QMULRSHIFT",
+        fmt = ("QMUL{}", DumpShr(m, false)),
+        args(imm = false, quiet = true)
+    )]
     fn exec_mulshrmod(st: &mut VmState, mut m: u32, imm: bool, quiet: bool) -> VmResult<i32> {
         enum Operation {
             MulRShift,
@@ -553,9 +614,24 @@ impl ArithOps {
         Ok(0)
     }
 
-    #[op(code = "a9cm", fmt = DumpShl(m, false), args(imm = false, quiet = false))]
-    #[op(code = "a9dmmm", fmt = DumpShl(m, true), args(imm = true, quiet = false))]
-    #[op(code = "b7a9cm", fmt = ("Q{}", DumpShl(m, false)), args(imm = false, quiet = true))]
+    #[op(
+        code = "a9cm",
+        name = "LSHIFTDIV",
+        fmt = DumpShl(m, false),
+        args(imm = false, quiet = false)
+    )]
+    #[op(
+        code = "a9dmmm",
+        name = "LSHIFT#DIV",
+        fmt = DumpShl(m, true),
+        args(imm = true, quiet = false)
+    )]
+    #[op(
+        code = "b7a9cm",
+        name = "QLSHIFTDIV",
+        fmt = ("Q{}", DumpShl(m, false)),
+        args(imm = false, quiet = true)
+    )]
     fn exec_shldivmod(st: &mut VmState, mut m: u32, imm: bool, quiet: bool) -> VmResult<i32> {
         enum Operation {
             Div,
@@ -697,6 +773,34 @@ impl ArithOps {
     }
 }
 
+/// Applies `f` to the top `N` integers if all of them are stored inline.
+///
+/// Returns `false` if the generic path must be used instead
+/// (e.g. on stack underflow, non-inline values or overflow).
+fn try_inline_op<const N: usize>(
+    stack: &mut Stack,
+    f: impl FnOnce([Int257; N]) -> Option<Int257>,
+) -> bool {
+    let Some(offset) = stack.depth().checked_sub(N) else {
+        return false;
+    };
+
+    let mut args = [Int257::ZERO; N];
+    for (arg, item) in args.iter_mut().zip(&stack.items[offset..]) {
+        match item.as_int257() {
+            Some(value) => *arg = value,
+            None => return false,
+        }
+    }
+
+    let Some(res) = f(args) else {
+        return false;
+    };
+    stack.items.truncate(offset);
+    stack.items.push(SafeRc::new_dyn_value(InlineInt::new(res)));
+    true
+}
+
 struct DumpDivmod(u32);
 
 impl std::fmt::Display for DumpDivmod {
@@ -951,9 +1055,12 @@ fn update_or_new_rc(mut rc: SafeRc<BigInt>, value: BigInt) -> SafeRc<BigInt> {
 
 #[cfg(test)]
 mod tests {
+    use everscale_types::boc::Boc;
     use tracing_test::traced_test;
 
     use super::*;
+    use crate::stack::{RcStackValue, StackValueType};
+    use crate::tests::run_code;
 
     #[test]
     #[traced_test]
@@ -961,6 +1068,18 @@ mod tests {
         assert_run_vm!("PUSHINT 1", [] => [int 1]);
         assert_run_vm!("PUSHINT 127", [] => [int 127]);
         assert_run_vm!("PUSHINT 32767", [] => [int 32767]);
+        assert_run_vm!("PUSHINT -32768", [] => [int -32768]);
+        assert_run_vm!("PUSHINT -129", [] => [int -129]);
+        assert_run_vm!("@inline x{818000}", [] => [int -32768]);
+        assert_run_vm!("@inline x{817fff}", [] => [int 32767]);
+        assert_run_vm!("@inline x{81ffff}", [] => [int -1]);
+        assert_run_vm!("@inline x{810080}", [] => [int 128]);
+        assert_run_vm!("@inline x{8080}", [] => [int -128]);
+        assert_run_vm!("@inline x{807f}", [] => [int 127]);
+        assert_run_vm!("@inline x{80ff}", [] => [int -1]);
+        // Next opcode must be decoded right after the 16-bit constant
+        assert_run_vm!("@inline x{81800071}", [] => [int -32768, int 1]);
+        assert_run_vm!("@inline x{81ffff7f}", [] => [int -1, int -1]);
         assert_run_vm!("PUSHINT 0x7FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF", [] => [int 0x7FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFi128]);
         assert_run_vm!("PUSHPOW2 1", [] => [int 2]);
         assert_run_vm!("PUSHPOW2 10", [] => [int (1 << 10)]);
@@ -974,6 +1093,90 @@ mod tests {
         assert_run_vm!("PUSHNEGPOW2 255", [] => [int (BigInt::from(-1) << 255)]);
     }
 
+    #[test]
+    #[traced_test]
+    fn op_pushint_long_gas() {
+        use everscale_types::boc::Boc;
+
+        use crate::error::VmError;
+        use crate::gas::GasParams;
+
+        fn make_vm(code: &[u8]) -> VmState<'static> {
+            VmState::builder()
+                .with_code(Boc::decode(code).unwrap())
+                .with_gas(GasParams::getter())
+                .build()
+        }
+
+        // 13-bit prefix + implicit RET, regardless of the value size
+        let expected_gas = 10 + 13 + 5;
+        let codes: [&[u8]; 2] = [
+            tvmasm!("PUSHINT 0x100000000"),
+            tvmasm!("PUSHINT 0x1000000000000000000000000000000000000000000000000000000000000"),
+        ];
+        for code in codes {
+            let mut vm = make_vm(code);
+            assert_eq!(vm.run(), -1);
+            assert_eq!(vm.gas.consumed(), expected_gas);
+        }
+
+        // Truncated immediate value (35 bits expected, 3 bits left)
+        let mut vm = make_vm(tvmasm!("@inline x{8210}"));
+        let e = vm.step().unwrap_err();
+        assert!(matches!(*e, VmError::InvalidOpcode));
+        // Code is not advanced
+        assert_eq!(vm.code.range().size_bits(), 16);
+    }
+
+    #[test]
+    #[traced_test]
+    fn op_pushint_long_signed() {
+        // Minimal form (19-bit value)
+        assert_run_vm!("@inline x{8207ffff}", [] => [int -1]);
+        assert_run_vm!("@inline x{82040000}", [] => [int -(1 << 18)]);
+        assert_run_vm!("@inline x{8203ffff}", [] => [int (1 << 18) - 1]);
+        // Non-minimal forms of small negative values
+        assert_run_vm!("@inline x{8207fffb}", [] => [int -5]);
+        assert_run_vm!("@inline x{820ffffffb}", [] => [int -5]);
+        assert_run_vm!("@inline x{8217fffffffb}", [] => [int -5]);
+
+        // Maximal form (259-bit value)
+        assert_run_vm!(
+            "@inline x{82f7ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff}",
+            [] => [int -1],
+        );
+        assert_run_vm!(
+            "@inline x{82f70000000000000000000000000000000000000000000000000000000000000000}",
+            [] => [int -(BigInt::from(1) << 256)],
+        );
+        assert_run_vm!(
+            "@inline x{82f78000000000000000000000000000000000000000000000000000000000000000}",
+            [] => [int -(BigInt::from(1) << 255)],
+        );
+        assert_run_vm!(
+            "@inline x{82f0ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff}",
+            [] => [int (BigInt::from(1) << 256) - 1],
+        );
+        assert_run_vm!(
+            "PUSHINT -115792089237316195423570985008687907853269984665640564039457584007913129639936",
+            [] => [int -(BigInt::from(1) << 256)],
+        );
+    }
+
+    #[test]
+    #[traced_test]
+    fn op_pushpow2_boundaries() {
+        // The last PUSHPOW2 opcode is 2^255 (2^256 doesn't fit into int257)
+        assert_run_vm!("@inline x{83fe}", [] => [int (BigInt::from(1) << 255)]);
+        // The next opcode is reserved for NaN
+        assert_run_vm!("@inline x{83ff}", [] => [nan]);
+        assert_run_vm!("@inline x{83ff} INC", [] => [int 0], exit_code: 4);
+        assert_run_vm!("@inline x{83ff} QINC", [] => [nan]);
+        // Other variants use the full range
+        assert_run_vm!("@inline x{84ff}", [] => [int (BigInt::from(1) << 256) - 1]);
+        assert_run_vm!("@inline x{85ff}", [] => [int (BigInt::from(-1) << 256)]);
+    }
+
     #[test]
     #[traced_test]
     fn op_simple_math() {
@@ -992,6 +1195,7 @@ mod tests {
         assert_run_vm!("ADDINT 5", [nan] => [int 0], exit_code: 4);
         assert_run_vm!("ADD", [int int257_max(), int 1] => [int 0], exit_code: 4);
         assert_run_vm!("ADDINT 1", [int int257_max()] => [int 0], exit_code: 4);
+        assert_run_vm!("ADDINT -128", [int int257_min() + 127] => [int 0], exit_code: 4);
 
         // pos
         assert_run_vm!("QADD", [int 2, int 5] => [int 7]);
@@ -1004,6 +1208,9 @@ mod tests {
         assert_run_vm!("QADDINT -5", [int 5] => [int 0]);
         assert_run_vm!("QADDINT 2", [nan] => [nan]);
         assert_run_vm!("QADDINT -5", [int 5] => [int 0]);
+        assert_run_vm!("QADDINT 1", [int int257_max()] => [nan]);
+        assert_run_vm!("QADDINT -1", [int int257_min()] => [nan]);
+        assert_run_vm!("QADDINT 127", [int int257_max() - 127] => [int int257_max()]);
         // neg
         assert_run_vm!("QADD", [] => [int 0], exit_code: 2);
         assert_run_vm!("QADD", [int 123] => [int 0], exit_code: 2);
@@ -1144,6 +1351,10 @@ mod tests {
         assert_run_vm!("MUL", [int int257_min(), int -1] => [int 0], exit_code: 4);
         assert_run_vm!("MUL", [int int257_min() / 2, int -2] => [int 0], exit_code: 4);
         assert_run_vm!("MUL", [int int257_max(), int 2] => [int 0], exit_code: 4);
+        assert_run_vm!("MULINT 127", [int int257_max() / 100] => [int 0], exit_code: 4);
+        assert_run_vm!("MULINT -2", [int int257_min() / 2] => [int 0], exit_code: 4);
+        assert_run_vm!("MULINT -1", [int int257_min()] => [int 0], exit_code: 4);
+        assert_run_vm!("MULINT 2", [nan] => [int 0], exit_code: 4);
 
         // pos
         assert_run_vm!("QMUL", [int 123, int 0] => [int 0]);
@@ -1167,6 +1378,11 @@ mod tests {
         assert_run_vm!("QMUL", [int int257_min(), int -1] => [nan]);
         assert_run_vm!("QMUL", [int int257_min() / 2, int -2] => [nan]);
         assert_run_vm!("QMUL", [int int257_max(), int 2] => [nan]);
+        assert_run_vm!("QMULINT 127", [int int257_max() / 127] => [int int257_max() / 127 * 127]);
+        assert_run_vm!("QMULINT 127", [int int257_max() / 100] => [nan]);
+        assert_run_vm!("QMULINT -2", [int int257_min() / 2] => [nan]);
+        assert_run_vm!("QMULINT -1", [int int257_min()] => [nan]);
+        assert_run_vm!("QMULINT 2", [nan] => [nan]);
         // neg
         assert_run_vm!("QMUL", [] => [int 0], exit_code: 2);
         assert_run_vm!("QMUL", [int 2] => [int 0], exit_code: 2);
@@ -1440,6 +1656,50 @@ mod tests {
         assert_run_vm!("QUIET ABS", [null] => [int 0], exit_code: 7);
     }
 
+    #[test]
+    #[traced_test]
+    fn inline_int_ops() {
+        let code = |code: &[u8]| Boc::decode(code).unwrap();
+        let inline = |value: Int257| SafeRc::new_dyn_value(InlineInt::new(value));
+
+        // Results of inline operands are kept inline
+        let (exit_code, vm) = run_code(
+            code(tvmasm!("INT 1 INC INT 2 ADD DEC INT 5 SUB")),
+            Vec::new(),
+        );
+        assert_eq!(exit_code, 0);
+        assert_eq!(vm.stack.items[0].as_int257(), Some(Int257::from(-2)));
+        assert_eq!(vm.stack.items[0].as_int(), Some(&BigInt::from(-2)));
+
+        // Generic path is used for other integers
+        let (exit_code, vm) = run_code(code(tvmasm!("ADD")), [
+            inline(Int257::ONE),
+            SafeRc::new_dyn_value(BigInt::from(2)),
+        ]);
+        assert_eq!(exit_code, 0);
+        assert_eq!(vm.stack.items[0].as_int257(), None);
+        assert_eq!(vm.stack.items[0].as_int(), Some(&BigInt::from(3)));
+
+        // Overflow is handled by the generic path
+        let cases: [(&[u8], Vec<RcStackValue>); 3] = [
+            (tvmasm!("INC"), vec![inline(Int257::MAX)]),
+            (tvmasm!("DEC"), vec![inline(Int257::MIN)]),
+            (tvmasm!("ADD"), vec![
+                inline(Int257::MAX),
+                inline(Int257::ONE),
+            ]),
+        ];
+        for (code, stack) in cases {
+            let (exit_code, _) = run_code(Boc::decode(code).unwrap(), stack);
+            assert_eq!(exit_code, 4);
+        }
+
+        let (exit_code, vm) = run_code(code(tvmasm!("QUIET INC")), [inline(Int257::MAX)]);
+        assert_eq!(exit_code, 0);
+        assert_eq!(vm.stack.items[0].ty(), StackValueType::Int);
+        assert_eq!(vm.stack.items[0].as_int(), None);
+    }
+
     fn int257_min() -> BigInt {
         BigInt::from(-1) << 256
     }
@@ -15,7 +15,10 @@ use crate::error::{VmError, VmResult};
 use crate::saferc::SafeRc;
 use crate::stack::{Stack, StackValue};
 use crate::state::VmState;
-use crate::util::{bitsize, load_int_from_slice, remove_trailing, OwnedCellSlice};
+use crate::util::{
+    bitsize, dump_ref_operand, dump_slice_operand, load_ext_operand, load_int_from_slice,
+    remove_trailing, OwnedCellSlice,
+};
 
 pub struct CellOps;
 
@@ -25,14 +28,48 @@ impl CellOps {
 
     #[init]
     fn init_cell_const(&self, t: &mut Opcodes) -> Result<()> {
-        t.add_ext(0x88, 8, 0, exec_push_ref)?;
-        t.add_ext(0x89, 8, 0, exec_push_ref_slice)?;
-        t.add_ext(0x8a, 8, 0, exec_push_ref_cont)?;
-        t.add_ext(0x8b, 8, 4, exec_push_slice)?;
-        t.add_ext(0x8c, 8, 7, exec_push_slice_r)?;
-        t.add_ext_range(0x8d << 10, ((0x8d << 3) + 5) << 7, 18, exec_push_slice_r2)?;
-        t.add_ext(0x8e >> 1, 7, 9, exec_push_cont)?;
-        t.add_ext(0x9, 4, 4, exec_push_cont_simple)
+        t.add_ext("PUSHREF", 0x88, 8, 0, exec_push_ref, dump_push_ref)?;
+        t.add_ext(
+            "PUSHREFSLICE",
+            0x89,
+            8,
+            0,
+            exec_push_ref_slice,
+            dump_push_ref_slice,
+        )?;
+        t.add_ext_with_ref_conts(
+            "PUSHREFCONT",
+            0x8a,
+            8,
+            exec_push_ref_cont,
+            dump_push_ref_cont,
+        )?;
+        t.add_ext("PUSHSLICE", 0x8b, 8, 4, exec_push_slice, dump_push_slice)?;
+        t.add_ext(
+            "PUSHSLICE",
+            0x8c,
+            8,
+            7,
+            exec_push_slice_r,
+            dump_push_slice_r,
+        )?;
+        t.add_ext_range(
+            "PUSHSLICE",
+            0x8d << 10,
+            ((0x8d << 3) + 5) << 7,
+            18,
+            exec_push_slice_r2,
+            dump_push_slice_r2,
+        )?;
+        t.add_ext_with_inline_cont("PUSHCONT", 0x8e >> 1, 7, 9, exec_push_cont, dump_push_cont)?;
+        t.add_ext_with_inline_cont(
+            "PUSHCONT",
+            0x9,
+            4,
+            4,
+            exec_push_cont_simple,
+            dump_push_cont_simple,
+        )
     }
 
     fn exec_push_ref(st: &mut VmState, _: u32, bits: u16) -> VmResult<i32> {
@@ -47,6 +84,18 @@ impl CellOps {
         exec_push_ref_common(st, bits, "PUSHREFCONT", PushRefMode::Cont)
     }
 
+    fn dump_push_ref(code: &mut CellSlice<'_>, _: u32, bits: u16) -> Option<String> {
+        dump_ref_operand(code, bits, 1, "PUSHREF")
+    }
+
+    fn dump_push_ref_slice(code: &mut CellSlice<'_>, _: u32, bits: u16) -> Option<String> {
+        dump_ref_operand(code, bits, 1, "PUSHREFSLICE")
+    }
+
+    fn dump_push_ref_cont(code: &mut CellSlice<'_>, _: u32, bits: u16) -> Option<String> {
+        dump_ref_operand(code, bits, 1, "PUSHREFCONT")
+    }
+
     fn exec_push_slice(st: &mut VmState, args: u32, bits: u16) -> VmResult<i32> {
         let data_bits = ((args & 0xf) * 8 + 4) as u16;
         exec_push_slice_common(st, bits, data_bits, 0)
@@ -64,6 +113,23 @@ impl CellOps {
         exec_push_slice_common(st, bits, data_bits, refs)
     }
 
+    fn dump_push_slice(code: &mut CellSlice<'_>, args: u32, bits: u16) -> Option<String> {
+        let data_bits = ((args & 0xf) * 8 + 4) as u16;
+        dump_push_slice_common(code, bits, data_bits, 0)
+    }
+
+    fn dump_push_slice_r(code: &mut CellSlice<'_>, args: u32, bits: u16) -> Option<String> {
+        let data_bits = ((args & 0x1f) * 8 + 1) as u16;
+        let refs = (((args >> 5) & 0b11) + 1) as u8;
+        dump_push_slice_common(code, bits, data_bits, refs)
+    }
+
+    fn dump_push_slice_r2(code: &mut CellSlice<'_>, args: u32, bits: u16) -> Option<String> {
+        let data_bits = ((args & 0x7f) * 8 + 6) as u16;
+        let refs = ((args >> 7) & 0b111) as u8;
+        dump_push_slice_common(code, bits, data_bits, refs)
+    }
+
     fn exec_push_cont(st: &mut VmState, args: u32, bits: u16) -> VmResult<i32> {
         let data_bits = ((args & 0x7f) * 8) as u16;
         let refs = ((args >> 7) & 0b11) as u8;
@@ -81,7 +147,7 @@ impl CellOps {
         code_range.skip_first(data_bits, refs).ok();
 
         let code = OwnedCellSlice::from((st.code.cell().clone(), slice_range));
-        vm_log_op!("PUSHCONT {}", code);
+        vm_log_op!(st, "PUSHCONT {}", code);
 
         let cont = SafeRc::new(OrdCont::simple(code, st.cp.id()));
         ok!(SafeRc::make_mut(&mut st.stack).push_raw(cont));
@@ -101,13 +167,26 @@ impl CellOps {
         code_range.skip_first(data_bits, 0).ok();
 
         let code = OwnedCellSlice::from((st.code.cell().clone(), slice_range));
-        vm_log_op!("PUSHCONT {}", code);
+        vm_log_op!(st, "PUSHCONT {}", code);
 
         let cont = SafeRc::new(OrdCont::simple(code, st.cp.id()));
         ok!(SafeRc::make_mut(&mut st.stack).push_raw(cont));
         Ok(0)
     }
 
+    fn dump_push_cont(code: &mut CellSlice<'_>, args: u32, bits: u16) -> Option<String> {
+        let data_bits = ((args & 0x7f) * 8) as u16;
+        let refs = ((args >> 7) & 0b11) as u8;
+        let cont = load_ext_operand(code, bits, data_bits, refs)?;
+        Some(format!("PUSHCONT {}", dump_slice_operand(&cont)))
+    }
+
+    fn dump_push_cont_simple(code: &mut CellSlice<'_>, args: u32, bits: u16) -> Option<String> {
+        let data_bits = ((args & 0xf) * 8) as u16;
+        let cont = load_ext_operand(code, bits, data_bits, 0)?;
+        Some(format!("PUSHCONT {}", dump_slice_operand(&cont)))
+    }
+
     // === Slice comparison ops ===
 
     #[op(code = "c700", fmt = "SEMPTY", args(op = SliceBoolUnaryOp::IsEmpty))]
@@ -211,8 +290,22 @@ impl CellOps {
 
     #[init]
     fn init_serializer_ops(&self, t: &mut Opcodes) -> Result<()> {
-        t.add_ext_range(0xcf20, 0xcf22, 16, exec_store_const_ref)?;
-        t.add_ext(0xcf80 >> 7, 9, 5, exec_store_const_slice)
+        t.add_ext_range(
+            "STREFCONST",
+            0xcf20,
+            0xcf22,
+            16,
+            exec_store_const_ref,
+            dump_store_const_ref,
+        )?;
+        t.add_ext(
+            "STSLICECONST",
+            0xcf80 >> 7,
+            9,
+            5,
+            exec_store_const_slice,
+            dump_store_const_slice,
+        )
     }
 
     #[op(code = "c8", fmt = "NEWC")]
@@ -290,7 +383,7 @@ impl CellOps {
         finish_store_ok(stack, builder, quiet)
     }
 
-    #[op(code = "cf0$0sss", fmt = s.display_x(), args(s = StoreIntArgs(args)))]
+    #[op(code = "cf0$0sss", name = "STIX", fmt = s.display_x(), args(s = StoreIntArgs(args)))]
     fn exec_store_int_var(st: &mut VmState, s: StoreIntArgs) -> VmResult<i32> {
         let stack = SafeRc::make_mut(&mut st.stack);
         let bits = ok!(stack.pop_smallint_range(0, 256 + s.is_signed() as u32));
@@ -299,6 +392,7 @@ impl CellOps {
 
     #[op(
         code = "cf0$1sss#nn",
+        name = "STI",
         fmt = ("{} {n}", s.display()),
         args(s = StoreIntArgs(args >> 8), n = (args & 0xff) + 1),
     )]
@@ -394,7 +488,7 @@ impl CellOps {
         vm_ensure!(code_range.has_remaining(bits, refs), InvalidOpcode);
         code_range.skip_first(bits, 0).ok();
 
-        vm_log_op!("STREF{refs}CONST");
+        vm_log_op!(st, "STREF{refs}CONST");
 
         let stack = SafeRc::make_mut(&mut st.stack);
         let mut builder = ok!(stack.pop_builder());
@@ -418,6 +512,16 @@ impl CellOps {
         Ok(0)
     }
 
+    fn dump_store_const_ref(code: &mut CellSlice<'_>, args: u32, bits: u16) -> Option<String> {
+        let refs = ((args & 1) + 1) as u8;
+        let name = if refs == 1 {
+            "STREFCONST"
+        } else {
+            "STREF2CONST"
+        };
+        dump_ref_operand(code, bits, refs, name)
+    }
+
     #[op(code = "cf22$ss", fmt = "{s}", args(s = StoreLeIntArgs(args)))]
     fn exec_store_le_int(st: &mut VmState, s: StoreLeIntArgs) -> VmResult<i32> {
         let stack = SafeRc::make_mut(&mut st.stack);
@@ -634,6 +738,7 @@ impl CellOps {
         remove_trailing(&mut slice)?;
 
         vm_log_op!(
+            st,
             "STSLICECONST {}",
             OwnedCellSlice::from((st.code.cell().clone(), slice_range))
         );
@@ -645,23 +750,35 @@ impl CellOps {
         Ok(0)
     }
 
+    fn dump_store_const_slice(code: &mut CellSlice<'_>, args: u32, bits: u16) -> Option<String> {
+        let data_bits = ((args & 0b111) * 8 + 2) as u16;
+        let refs = ((args >> 3) & 0b11) as u8;
+        let mut slice = load_ext_operand(code, bits, data_bits, refs)?;
+        remove_trailing(&mut slice).ok()?;
+        Some(format!("STSLICECONST {}", dump_slice_operand(&slice)))
+    }
+
     // === Deserializer ops ===
 
     #[init]
     fn init_deserializer_ops(&self, t: &mut Opcodes) -> Result<()> {
-        t.add_ext(0xd728 >> 3, 13, 8, exec_slice_begins_with_const)
+        t.add_ext(
+            "SDBEGINS",
+            0xd728 >> 3,
+            13,
+            8,
+            exec_slice_begins_with_const,
+            dump_slice_begins_with_const,
+        )
     }
 
     #[op(code = "d0", fmt = "CTOS")]
     fn exec_cell_to_slice(st: &mut VmState) -> VmResult<i32> {
-        let stack = SafeRc::make_mut(&mut st.stack);
-        let cell = ok!(stack.pop_cell());
+        let cell = ok!(SafeRc::make_mut(&mut st.stack).pop_cell());
 
-        let cs = st
-            .gas
-            .load_cell_as_slice(SafeRc::unwrap_or_clone(cell), LoadMode::Full)?;
+        let cs = ok!(st.load_cell_as_slice(SafeRc::unwrap_or_clone(cell), LoadMode::Full));
 
-        ok!(stack.push(cs));
+        ok!(SafeRc::make_mut(&mut st.stack).push(cs));
         Ok(0)
     }
 
@@ -722,7 +839,7 @@ impl CellOps {
         exec_load_slice_common(stack, x as _, LoadSliceArgs(0))
     }
 
-    #[op(code = "d70$0sss", fmt = s.display_x(), args(s = LoadIntArgs(args)))]
+    #[op(code = "d70$0sss", name = "LDIX", fmt = s.display_x(), args(s = LoadIntArgs(args)))]
     fn exec_load_int_var(st: &mut VmState, s: LoadIntArgs) -> VmResult<i32> {
         let stack = SafeRc::make_mut(&mut st.stack);
         let bits = ok!(stack.pop_smallint_range(0, 256 + s.is_signed() as u32));
@@ -731,6 +848,7 @@ impl CellOps {
 
     #[op(
         code = "d70$1sss#nn",
+        name = "LDI",
         fmt = ("{} {n}", s.display()),
         args(s = LoadIntArgs(args >> 8), n = (args & 0xff) + 1)
     )]
@@ -795,7 +913,7 @@ impl CellOps {
         Ok(0)
     }
 
-    #[op(code = "d71$10ss", fmt = s.display_x(), args(s = LoadSliceArgs(args)))]
+    #[op(code = "d71$10ss", name = "LDSLICEX", fmt = s.display_x(), args(s = LoadSliceArgs(args)))]
     fn exec_load_slice(st: &mut VmState, s: LoadSliceArgs) -> VmResult<i32> {
         let stack = SafeRc::make_mut(&mut st.stack);
         let bits = ok!(stack.pop_smallint_range(0, 1023));
@@ -804,6 +922,7 @@ impl CellOps {
 
     #[op(
         code = "d71$11ss#nn",
+        name = "LDSLICE",
         fmt = ("{} {n}", s.display()),
         args(s = LoadSliceArgs(args >> 8), n = (args & 0xff) + 1)
     )]
@@ -890,6 +1009,7 @@ impl CellOps {
         remove_trailing(&mut slice)?;
 
         vm_log_op!(
+            st,
             "SDBEGINS{} {}",
             if quiet { "Q" } else { "" },
             OwnedCellSlice::from((st.code.cell().clone(), slice_range))
@@ -899,6 +1019,22 @@ impl CellOps {
         exec_slice_begins_with_common(stack, &slice, quiet)
     }
 
+    fn dump_slice_begins_with_const(
+        code: &mut CellSlice<'_>,
+        args: u32,
+        bits: u16,
+    ) -> Option<String> {
+        let quiet = (args & 0x80) != 0;
+        let data_bits = ((args & 0x7f) * 8 + 3) as u16;
+        let mut slice = load_ext_operand(code, bits, data_bits, 0)?;
+        remove_trailing(&mut slice).ok()?;
+        Some(format!(
+            "SDBEGINS{} {}",
+            if quiet { "Q" } else { "" },
+            dump_slice_operand(&slice)
+        ))
+    }
+
     #[op(code = "d730", fmt = "SCUTFIRST", args(op = SliceRangeOp::CutFirst))]
     #[op(code = "d731", fmt = "SSKIPFIRST", args(op = SliceRangeOp::SkipFirst))]
     #[op(code = "d732", fmt = "SCUTLAST", args(op = SliceRangeOp::CutLast))]
@@ -1300,7 +1436,7 @@ fn exec_push_ref_common(
     let ok = st.code.range_mut().skip_first(0, 1).is_ok();
     debug_assert!(ok);
 
-    vm_log_op!("{name} ({})", cell.repr_hash());
+    vm_log_op!(st, "{name} ({})", cell.repr_hash());
 
     let stack = SafeRc::make_mut(&mut st.stack);
     ok!(match mode {
@@ -1339,12 +1475,23 @@ fn exec_push_slice_common(st: &mut VmState, bits: u16, data_bits: u16, refs: u8)
     }
 
     let slice = SafeRc::new_dyn_value(OwnedCellSlice::from((st.code.cell().clone(), slice_range)));
-    vm_log_op!("PUSHSLICE {}", slice.display_list());
+    vm_log_op!(st, "PUSHSLICE {}", slice.display_list());
 
     ok!(SafeRc::make_mut(&mut st.stack).push_raw(slice));
     Ok(0)
 }
 
+fn dump_push_slice_common(
+    code: &mut CellSlice<'_>,
+    bits: u16,
+    data_bits: u16,
+    refs: u8,
+) -> Option<String> {
+    let mut slice = load_ext_operand(code, bits, data_bits, refs)?;
+    remove_trailing(&mut slice).ok()?;
+    Some(format!("PUSHSLICE {}", dump_slice_operand(&slice)))
+}
+
 #[derive(Clone, Copy)]
 enum SliceBoolUnaryOp {
     IsEmpty,
@@ -1850,6 +1997,9 @@ mod tests {
     use tracing_test::traced_test;
 
     use super::*;
+    use crate::error::VmException;
+    use crate::gas::{GasConsumer, GasParams, GasPrices};
+    use crate::state::BehaviourModifiers;
     use crate::util::store_int_to_builder;
 
     #[test]
@@ -1876,6 +2026,36 @@ mod tests {
         );
     }
 
+    #[test]
+    #[traced_test]
+    fn store_reversed() {
+        let mut unsigned = CellBuilder::new();
+        unsigned.store_u8(5).unwrap();
+        let mut signed = CellBuilder::new();
+        signed.store_u8(0xfd).unwrap();
+
+        assert_run_vm!("STU 8", [int 5, builder CellBuilder::new()] => [builder unsigned.clone()]);
+        assert_run_vm!("STUR 8", [builder CellBuilder::new(), int 5] => [builder unsigned.clone()]);
+        assert_run_vm!("STI 8", [int -3, builder CellBuilder::new()] => [builder signed.clone()]);
+        assert_run_vm!("STIR 8", [builder CellBuilder::new(), int -3] => [builder signed.clone()]);
+
+        // Both orders fail the same way
+        assert_run_vm!("STU 8", [int 256, builder CellBuilder::new()] => [int 0], exit_code: 5);
+        assert_run_vm!("STUR 8", [builder CellBuilder::new(), int 256] => [int 0], exit_code: 5);
+        assert_run_vm!("STUR 8", [int 5, builder CellBuilder::new()] => [int 0], exit_code: 7);
+
+        let cell = Cell::default();
+        let with_ref = init_builder_with_refs(cell.clone(), 1);
+        assert_run_vm!("STREF", [cell cell.clone(), builder CellBuilder::new()] => [builder with_ref.clone()]);
+        assert_run_vm!("STREFR", [builder CellBuilder::new(), cell cell.clone()] => [builder with_ref.clone()]);
+
+        let slice = make_uint_cell_slice(0b1011, 4);
+        let mut with_slice = CellBuilder::new();
+        with_slice.store_small_uint(0b1011, 4).unwrap();
+        assert_run_vm!("STSLICE", [slice slice.clone(), builder CellBuilder::new()] => [builder with_slice.clone()]);
+        assert_run_vm!("STSLICER", [builder CellBuilder::new(), slice slice.clone()] => [builder with_slice.clone()]);
+    }
+
     #[test]
     #[traced_test]
     fn lexcmp_tests() {
@@ -1887,6 +2067,43 @@ mod tests {
         assert_run_vm!("SDLEXCMP", [slice slice2, slice slice1] => [int 1]);
     }
 
+    #[test]
+    #[traced_test]
+    fn sdeq_tests() {
+        // Same bits as a suffix of a different cell
+        let plain = make_uint_cell_slice(0xabcd, 16);
+        let mut shifted = make_uint_cell_slice(0x5abcd, 20);
+        shifted.range_mut().skip_first(4, 0).unwrap();
+
+        // Same bits with an extra reference
+        let with_ref = {
+            let mut b = CellBuilder::new();
+            b.store_u16(0xabcd).unwrap();
+            b.store_reference(Cell::empty_cell()).unwrap();
+            OwnedCellSlice::new_allow_exotic(b.build().unwrap())
+        };
+
+        // Differs by the last bit
+        let other = make_uint_cell_slice(0xabcc, 16);
+
+        assert_run_vm!("SDEQ", [slice plain.clone(), slice plain.clone()] => [int -1]);
+        assert_run_vm!("SDEQ", [slice plain.clone(), slice shifted.clone()] => [int -1]);
+        assert_run_vm!("SDEQ", [slice shifted.clone(), slice with_ref.clone()] => [int -1]);
+        assert_run_vm!("SDEQ", [slice plain.clone(), slice other.clone()] => [int 0]);
+        assert_run_vm!("SDEQ", [slice shifted, slice other] => [int 0]);
+
+        // Prefix is not equal
+        let prefix = make_uint_cell_slice(0xab, 8);
+        assert_run_vm!("SDEQ", [slice plain.clone(), slice prefix] => [int 0]);
+
+        // Empty slices
+        let empty = OwnedCellSlice::new_allow_exotic(Cell::empty_cell());
+        let mut consumed = plain.clone();
+        consumed.range_mut().skip_first(16, 0).unwrap();
+        assert_run_vm!("SDEQ", [slice empty.clone(), slice consumed] => [int -1]);
+        assert_run_vm!("SDEQ", [slice empty, slice plain] => [int 0]);
+    }
+
     #[test]
     #[traced_test]
     fn store_tests() {
@@ -1992,6 +2209,200 @@ mod tests {
         cb
     }
 
+    #[test]
+    #[traced_test]
+    fn push_slice_tests() -> anyhow::Result<()> {
+        assert_run_vm!(
+            "PUSHSLICE x{abcdef} DUP SBITS SWAP PLDU 24",
+            [] => [int 24, int 0xabcdef],
+        );
+        assert_run_vm!(
+            "PUSHSLICE x{0123456789abcdef0123456789abcdef} DUP SBITS SWAP PLDU 128",
+            [] => [int 128, int 0x0123456789abcdef0123456789abcdef_u128],
+        );
+
+        // PUSHSLICE x{ab} (short form, with a completion tag)
+        let code = {
+            let mut b = CellBuilder::new();
+            b.store_uint(0x8b1ab8, 24)?;
+            b.build()?
+        };
+        let vm = run_raw_code(code);
+        let cs = vm.stack.items[0].as_cell_slice().unwrap().apply();
+        assert_eq!((cs.size_bits(), cs.size_refs()), (8, 0));
+        assert_eq!(cs.get_u8(0)?, 0xab);
+
+        // PUSHSLICE with one reference
+        let code = {
+            let mut b = CellBuilder::new();
+            b.store_uint(0x8c0357, 24)?;
+            b.store_reference(Cell::empty_cell())?;
+            b.build()?
+        };
+        let vm = run_raw_code(code);
+        let cs = vm.stack.items[0].as_cell_slice().unwrap().apply();
+        assert_eq!((cs.size_bits(), cs.size_refs()), (8, 1));
+        assert_eq!(cs.get_u8(0)?, 0xab);
+
+        // PUSHSLICE with two references and an unaligned length
+        let code = {
+            let mut b = CellBuilder::new();
+            b.store_u32(0x8d40735c)?;
+            b.store_reference(Cell::empty_cell())?;
+            b.store_reference(Cell::empty_cell())?;
+            b.build()?
+        };
+        let vm = run_raw_code(code);
+        let cs = vm.stack.items[0].as_cell_slice().unwrap().apply();
+        assert_eq!((cs.size_bits(), cs.size_refs()), (11, 2));
+        assert_eq!(cs.get_uint(0, 11)?, 0b11001101011);
+
+        // Truncated literal
+        let code = CellBuilder::build_from(0x8b1au16)?;
+        let mut vm = VmState::builder()
+            .with_code(code)
+            .with_gas(GasParams::getter())
+            .build();
+        assert_eq!(!vm.run(), VmException::InvalidOpcode as i32);
+
+        Ok(())
+    }
+
+    #[test]
+    #[traced_test]
+    fn push_cont_tests() {
+        assert_run_vm!("PUSHCONT { INT 1 INT 2 ADD } EXECUTE", [] => [int 3]);
+
+        // Long form
+        assert_run_vm!(
+            r#"
+            PUSHCONT {
+                INT 1000 INT 1000 INT 1000 INT 1000 INT 1000 INT 1000
+                ADD ADD ADD ADD ADD
+            }
+            DUP EXECUTE SWAP EXECUTE ADD
+            "#,
+            [] => [int 12000],
+        );
+    }
+
+    #[test]
+    #[traced_test]
+    fn push_ref_tests() -> anyhow::Result<()> {
+        let child = CellBuilder::build_from(0xdeadbeefu32)?;
+        let code_with_ref = |opcode: u8| {
+            let mut b = CellBuilder::new();
+            b.store_u8(opcode)?;
+            b.store_reference(child.clone())?;
+            b.build()
+        };
+
+        // PUSHREF doesn't load the cell
+        let vm = run_raw_code(code_with_ref(0x88)?);
+        let cell = vm.stack.items[0].as_cell().unwrap();
+        assert_eq!(cell.repr_hash(), child.repr_hash());
+        assert_eq!(vm.gas.consumed(), 18 + 5);
+
+        // PUSHREFSLICE pays for the cell load
+        let vm = run_raw_code(code_with_ref(0x89)?);
+        let cs = vm.stack.items[0].as_cell_slice().unwrap();
+        assert_eq!(cs.apply().get_u32(0)?, 0xdeadbeef);
+        assert_eq!(vm.gas.consumed(), 18 + GasPrices::DEFAULT.new_cell + 5);
+
+        // No reference left in the code cell
+        for opcode in [0x88, 0x89, 0x8a] {
+            let code = CellBuilder::build_from(opcode as u8)?;
+            let mut vm = VmState::builder()
+                .with_code(code)
+                .with_gas(GasParams::getter())
+                .build();
+            assert_eq!(!vm.run(), VmException::InvalidOpcode as i32);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    #[traced_test]
+    fn store_const_tests() -> anyhow::Result<()> {
+        assert_run_vm!(
+            "NEWC STSLICECONST x{abcd} ENDC CTOS LDSLICE 16 ENDS PLDU 16",
+            [] => [int 0xabcd],
+        );
+        assert_run_vm!(
+            "NEWC STSLICECONST x{7_} STSLICECONST x{abcd} ENDC CTOS LDU 3 PLDU 16",
+            [] => [int 0b011, int 0xabcd],
+        );
+
+        // NEWC STREFCONST STREF2CONST
+        let code = {
+            let mut b = CellBuilder::new();
+            b.store_u8(0xc8)?;
+            b.store_u16(0xcf20)?;
+            b.store_u16(0xcf21)?;
+            for i in 0..3u8 {
+                b.store_reference(CellBuilder::build_from(i)?)?;
+            }
+            b.build()?
+        };
+        let vm = run_raw_code(code);
+        let builder = vm.stack.items[0].as_cell_builder().unwrap();
+        assert_eq!(builder.size_refs(), 3);
+        for (i, child) in builder.references().iter().enumerate() {
+            assert_eq!(child.as_slice()?.get_u8(0)?, i as u8);
+        }
+
+        Ok(())
+    }
+
+    fn run_raw_code(code: Cell) -> VmState<'static> {
+        let mut vm = VmState::builder()
+            .with_code(code)
+            .with_gas(GasParams::getter())
+            .build();
+        assert_eq!(vm.run(), -1);
+        vm
+    }
+
+    #[test]
+    #[traced_test]
+    fn ctos_depth_limit() -> anyhow::Result<()> {
+        fn make_chain(depth: u16) -> Cell {
+            let mut cell = Cell::empty_cell();
+            for _ in 0..depth {
+                cell = CellBuilder::build_from(cell).unwrap();
+            }
+            cell
+        }
+
+        let code = Boc::decode(tvmasm!("CTOS SREFS"))?;
+        let run = |cell: Cell, limit_load_depth: bool| {
+            let mut vm = VmState::builder()
+                .with_code(code.clone())
+                .with_stack(tuple![cell cell])
+                .with_gas(GasParams::getter())
+                .with_modifiers(BehaviourModifiers {
+                    limit_load_depth,
+                    ..Default::default()
+                })
+                .build();
+            !vm.run()
+        };
+
+        let max_depth = VmState::MAX_DATA_DEPTH;
+
+        // No limit by default
+        assert_eq!(run(make_chain(max_depth + 1), false), 0);
+
+        assert_eq!(run(make_chain(max_depth), true), 0);
+        assert_eq!(
+            run(make_chain(max_depth + 1), true),
+            VmException::CellOverflow as i32
+        );
+
+        Ok(())
+    }
+
     #[test]
     #[traced_test]
     fn ctos_tests() {
@@ -2307,6 +2718,14 @@ mod tests {
         assert_run_vm!("SDSKIPLAST", [slice slice.clone(), int 512] => [slice prefix.clone()]);
 
         assert_run_vm!("SDSKIPLAST", [slice slice.clone(), int 513] => [int 0], exit_code: 9);
+
+        let slice = make_uint_cell_slice(0xabcdef, 24);
+        let suffix = cut_slice_suffix(&slice, 16, 0);
+        assert_run_vm!("SDSKIPFIRST", [slice slice.clone(), int 8] => [slice suffix]);
+        assert_run_vm!("SDSKIPFIRST SBITS", [slice slice.clone(), int 8] => [int 16]);
+        assert_run_vm!("SDCUTFIRST SBITS", [slice slice.clone(), int 8] => [int 8]);
+        assert_run_vm!("SDSKIPFIRST", [slice slice.clone(), int 25] => [int 0], exit_code: 9);
+        assert_run_vm!("SDCUTLAST", [slice slice.clone(), int 25] => [int 0], exit_code: 9);
     }
 
     #[test]
@@ -2406,9 +2825,47 @@ mod tests {
         assert_run_vm!("PLDREFIDX 3", [slice slice.clone()] => [int 0], exit_code: 9);
         assert_run_vm!("PLDREFVAR", [slice slice.clone(), int 10] => [int 0], exit_code: 5);
 
+        // Preloading doesn't advance the slice
+        let cell0 = cs.get_reference_cloned(0).unwrap();
+        let cell1 = cs.get_reference_cloned(1).unwrap();
+        assert_run_vm!(
+            "DUP PLDREFIDX 0 OVER PLDREFIDX 1 ROT SBITREFS",
+            [slice slice.clone()] => [cell cell0.clone(), cell cell1.clone(), int 512, int 2],
+        );
+        assert_run_vm!(
+            "DUP INT 1 PLDREFVAR SWAP INT 0 PLDREFVAR SWAP",
+            [slice slice.clone()] => [cell cell0, cell cell1],
+        );
+
         assert_run_vm!("SBITS", [slice slice.clone()] => [int 512]);
         assert_run_vm!("SREFS", [slice slice.clone()] => [int 2]);
         assert_run_vm!("SBITREFS", [slice slice.clone()] => [int 512, int 2]);
+
+        // 40 bits and 2 refs left after skipping a prefix
+        let slice = cut_slice_suffix(&make_cell_slice_with_refs(1000, 48, 2), 40, 2);
+        assert_run_vm!("SBITS", [slice slice.clone()] => [int 40]);
+        assert_run_vm!("SREFS", [slice slice.clone()] => [int 2]);
+        assert_run_vm!("SBITREFS", [slice slice.clone()] => [int 40, int 2]);
+        assert_run_vm!("SDEPTH", [slice slice.clone()] => [int 1]);
+        assert_run_vm!("SEMPTY", [slice slice.clone()] => [int 0]);
+        assert_run_vm!("SDEMPTY", [slice slice.clone()] => [int 0]);
+        assert_run_vm!("SREMPTY", [slice slice.clone()] => [int 0]);
+
+        // Metadata ops must not consume the slice
+        assert_run_vm!("DUP SBITS SWAP SREFS", [slice slice.clone()] => [int 40, int 2]);
+
+        let no_refs = cut_slice_prefix(&slice, 40, 0);
+        assert_run_vm!("SDEPTH", [slice no_refs.clone()] => [int 0]);
+        assert_run_vm!("SDEMPTY", [slice no_refs.clone()] => [int 0]);
+        assert_run_vm!("SREMPTY", [slice no_refs] => [int -1]);
+
+        let no_bits = cut_slice_suffix(&slice, 0, 2);
+        assert_run_vm!("SDEMPTY", [slice no_bits.clone()] => [int -1]);
+        assert_run_vm!("SEMPTY", [slice no_bits] => [int 0]);
+
+        let empty = cut_slice_suffix(&slice, 0, 0);
+        assert_run_vm!("SEMPTY", [slice empty.clone()] => [int -1]);
+        assert_run_vm!("SBITREFS", [slice empty] => [int 0, int 0]);
     }
 
     #[test]
@@ -2424,6 +2881,18 @@ mod tests {
         let prefix = make_uint_cell_slice(0b11, 9);
         assert_run_vm!("SDBEGINSX", [slice slice.clone(), slice prefix.clone()] => [int 0], exit_code: 9);
         assert_run_vm!("SDBEGINSXQ", [slice slice.clone(), slice prefix.clone()] => [slice slice.clone(), int 0]);
+
+        let slice = make_uint_cell_slice(0b1010_1100, 8);
+        let prefix = make_uint_cell_slice(0b1010, 4);
+        let result = cut_slice_suffix(&slice, 4, 0);
+        assert_run_vm!("SDBEGINSX", [slice slice.clone(), slice prefix.clone()] => [slice result.clone()]);
+        assert_run_vm!("SDBEGINS b{1010}", [slice slice.clone()] => [slice result.clone()]);
+        assert_run_vm!("SDBEGINSQ b{1010}", [slice slice.clone()] => [slice result, int -1]);
+
+        let slice = make_uint_cell_slice(0b0110_1100, 8);
+        assert_run_vm!("SDBEGINSX", [slice slice.clone(), slice prefix.clone()] => [int 0], exit_code: 9);
+        assert_run_vm!("SDBEGINS b{1010}", [slice slice.clone()] => [int 0], exit_code: 9);
+        assert_run_vm!("SDBEGINSQ b{1010}", [slice slice.clone()] => [slice slice.clone(), int 0]);
     }
 
     #[test]
@@ -2535,6 +3004,56 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    #[traced_test]
+    fn xload_library() -> anyhow::Result<()> {
+        let library_code = Boc::decode(tvmasm!("NOP"))?;
+        let library = {
+            let mut b = CellBuilder::new();
+            b.set_exotic(true);
+            b.store_u8(CellType::LibraryReference.to_byte())?;
+            b.store_u256(library_code.repr_hash())?;
+            b.build()?
+        };
+        let code = Boc::decode(tvmasm!("XLOADQ"))?;
+
+        let run = |libs: &HashMap<HashBytes, SimpleLib>| {
+            let mut vm = VmState::builder()
+                .with_code(code.clone())
+                .with_stack(tuple![cell library.clone()])
+                .with_libraries(libs)
+                .with_gas(GasParams::getter())
+                .build();
+            assert_eq!(vm.run(), -1);
+            (
+                vm.stack.clone(),
+                vm.gas.consumed(),
+                vm.gas.missing_library(),
+            )
+        };
+
+        // Only the library cell itself is charged
+        let expected_gas = 26 + GasPrices::DEFAULT.new_cell + 5;
+
+        // Unresolved library is reported
+        let (stack, gas, missing) = run(&HashMap::new());
+        crate::tests::compare_stack(&stack.items, &tuple![int 0]);
+        assert_eq!(gas, expected_gas);
+        assert_eq!(missing, Some(*library_code.repr_hash()));
+
+        // Resolved library
+        let libs = HashMap::from([(*library_code.repr_hash(), SimpleLib {
+            public: true,
+            root: library_code.clone(),
+        })]);
+        let (stack, gas, missing) = run(&libs);
+        crate::tests::compare_stack(&stack.items, &tuple![cell library_code.clone(), int -1]);
+        assert_eq!(gas, expected_gas);
+        assert_eq!(missing, None);
+
+        Ok(())
+    }
+
     fn skip_common(slice: &OwnedCellSlice, prefix: &OwnedCellSlice) -> OwnedCellSlice {
         let common = get_common_prefix(slice, prefix);
         let mut slice = slice.clone();
@@ -0,0 +1,114 @@
+use tycho_vm_proc::vm_module;
+
+use crate::error::VmResult;
+use crate::saferc::SafeRc;
+use crate::state::VmState;
+
+pub struct CodepageOps;
+
+#[vm_module]
+impl CodepageOps {
+    #[op(code = "ff00", fmt = "SETCP0", args(x = 0i16))]
+    #[op(code = "ffxx @ ff01..fff0", fmt = "SETCP {x}", args(x = (args & 0xff) as i16))]
+    #[op(code = "fffx @ fff1..", fmt = "SETCP {x}", args(x = (args & 0xf) as i16 - 16))]
+    fn exec_set_cp(st: &mut VmState, x: i16) -> VmResult<i32> {
+        ok!(st.force_cp(x as u16));
+        Ok(0)
+    }
+
+    #[op(code = "fff0", fmt = "SETCPX")]
+    fn exec_set_cp_any(st: &mut VmState) -> VmResult<i32> {
+        let cp = ok!(SafeRc::make_mut(&mut st.stack).pop_smallint_signed_range(-0x8000, 0x7fff));
+        ok!(st.force_cp(cp as i16 as u16));
+        Ok(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use everscale_types::prelude::CellBuilder;
+    use tracing_test::traced_test;
+
+    use crate::dispatch::DispatchTable;
+    use crate::error::CodepageError;
+    use crate::gas::GasParams;
+    use crate::saferc::SafeRc;
+    use crate::state::VmState;
+
+    #[test]
+    #[traced_test]
+    fn set_cp0() {
+        assert_run_vm!("SETCP0 INT 1", [] => [int 1]);
+        assert_run_vm!("INT 0 SETCPX INT 1", [] => [int 1]);
+    }
+
+    #[test]
+    #[traced_test]
+    fn set_unknown_cp() {
+        assert_run_vm!("INT 123 SETCPX", [] => [int 0], exit_code: 6);
+        assert_run_vm!("INT -1 SETCPX", [] => [int 0], exit_code: 6);
+        assert_run_vm!("INT 32768 SETCPX", [] => [int 0], exit_code: 5);
+    }
+
+    #[test]
+    #[traced_test]
+    fn custom_codepage() -> anyhow::Result<()> {
+        let mut cp = DispatchTable::builder(0x42);
+        cp.add_simple(
+            "MAGIC",
+            0xaa,
+            8,
+            |st| {
+                ok!(SafeRc::make_mut(&mut st.stack).push_int(42));
+                Ok(0)
+            },
+            || "MAGIC".to_owned(),
+        )?;
+        let codepages = [cp.build()];
+
+        // SETCP 0x42; MAGIC
+        let code = {
+            let mut b = CellBuilder::new();
+            b.store_u16(0xff42)?;
+            b.store_u8(0xaa)?;
+            b.build()?
+        };
+
+        let mut vm = VmState::builder()
+            .with_code(code.clone())
+            .with_gas(GasParams::getter())
+            .with_codepages(&codepages)?
+            .build();
+        assert_eq!(vm.run(), -1);
+        assert_eq!(vm.cp.id(), 0x42);
+        crate::tests::compare_stack(&vm.stack.items, &tuple![int 42]);
+
+        // Codepages are not shared between states
+        let mut vm = VmState::builder()
+            .with_code(code)
+            .with_gas(GasParams::getter())
+            .build();
+        assert_eq!(!vm.run(), 6); // invalid opcode
+
+        Ok(())
+    }
+
+    #[test]
+    #[traced_test]
+    fn invalid_codepages() {
+        let res = VmState::builder().with_codepages(&[]);
+        assert!(res.is_ok());
+
+        let codepages = [DispatchTable::builder(0).build()];
+        let res = VmState::builder().with_codepages(&codepages);
+        assert!(matches!(res, Err(CodepageError::ReservedId)));
+
+        let codepages = [
+            DispatchTable::builder(0x42).build(),
+            DispatchTable::builder(0x43).build(),
+            DispatchTable::builder(0x42).build(),
+        ];
+        let res = VmState::builder().with_codepages(&codepages);
+        assert!(matches!(res, Err(CodepageError::DuplicateId(0x42))));
+    }
+}
@@ -20,7 +20,7 @@ pub struct ConfigOps;
 
 #[vm_module]
 impl ConfigOps {
-    #[op(code = "f82s", fmt = DisplayConfigOpsArgs(s))]
+    #[op(code = "f82s", name = "GETPARAM", fmt = DisplayConfigOpsArgs(s))]
     fn exec_get_param(st: &mut VmState, s: u32) -> VmResult<i32> {
         let stack = SafeRc::make_mut(&mut st.stack);
         ok!(get_and_push_param(&mut st.cr, stack, s as usize));
@@ -377,3 +377,160 @@ fn get_parsed_config(regs: &ControlRegs) -> VmResult<&[RcStackValue]> {
 }
 
 const CONFIG_KEY_BITS: u16 = 32;
+
+#[cfg(test)]
+mod tests {
+    use tracing_test::traced_test;
+
+    use super::*;
+
+    #[test]
+    #[traced_test]
+    fn smc_info_params() {
+        let addr = OwnedCellSlice::new_allow_exotic(CellBuilder::build_from(0xabcdu16).unwrap());
+        let config_root = CellBuilder::build_from(0x1234u16).unwrap();
+        let c7 = tuple![[
+            int 0x076ef1ea,           // 0
+            int 0,                    // 1
+            int 0,                    // 2
+            int 1700000000,           // UNIX_TIME_IDX
+            int 123,                  // BLOCK_LT_IDX
+            int 456,                  // TX_LT_IDX
+            int 789,                  // RANDSEED_IDX
+            [int 1000, null],         // BALANCE_IDX
+            slice addr.clone(),       // MYADDR_IDX
+            cell config_root.clone(), // CONFIG_IDX
+        ]];
+
+        assert_run_vm!("NOW", c7: c7.clone(), [] => [int 1700000000]);
+        assert_run_vm!("BLOCKLT", c7: c7.clone(), [] => [int 123]);
+        assert_run_vm!("LTIME", c7: c7.clone(), [] => [int 456]);
+        assert_run_vm!("RANDSEED", c7: c7.clone(), [] => [int 789]);
+        assert_run_vm!("BALANCE", c7: c7.clone(), [] => [[int 1000, null]]);
+        assert_run_vm!("MYADDR", c7: c7.clone(), [] => [slice addr]);
+        assert_run_vm!("CONFIGROOT", c7: c7.clone(), [] => [cell config_root]);
+        assert_run_vm!("GETPARAM 3", c7: c7.clone(), [] => [int 1700000000]);
+
+        // Missing params
+        assert_run_vm!("MYCODE", c7: c7, [] => [int 0], exit_code: 5);
+        assert_run_vm!("NOW", c7: tuple![], [] => [int 0], exit_code: 5);
+
+        // Mistyped params tuple
+        assert_run_vm!("NOW", c7: tuple![int 1], [] => [int 0], exit_code: 7);
+    }
+
+    #[test]
+    #[traced_test]
+    fn smc_info_builder() -> anyhow::Result<()> {
+        use everscale_types::models::{IntAddr, StdAddr, VarAddr};
+        use everscale_types::num::Uint9;
+
+        use crate::smc_info::SmcInfo;
+
+        let addr = StdAddr::new(-1, HashBytes([0x11; 32]));
+        let c7 = SmcInfoBase::new()
+            .with_now(1700000000)
+            .with_block_lt(123)
+            .with_tx_lt(456)
+            .try_with_account_addr(IntAddr::Std(addr.clone()))?
+            .build_c7();
+        let c7 = SafeRc::unwrap_or_clone(c7);
+
+        let addr = OwnedCellSlice::new_allow_exotic(CellBuilder::build_from(&addr)?);
+        assert_run_vm!("NOW", c7: c7.clone(), [] => [int 1700000000]);
+        assert_run_vm!("BLOCKLT", c7: c7.clone(), [] => [int 123]);
+        assert_run_vm!("LTIME", c7: c7.clone(), [] => [int 456]);
+        assert_run_vm!("MYADDR", c7: c7, [] => [slice addr]);
+
+        // Short workchain with a full account id must use `addr_std`
+        let var_addr = |workchain: i32| {
+            IntAddr::Var(VarAddr {
+                anycast: None,
+                address_len: Uint9::new(256),
+                workchain,
+                address: vec![0x11; 32],
+            })
+        };
+        assert!(SmcInfoBase::new()
+            .try_with_account_addr(var_addr(0))
+            .is_err());
+        assert!(SmcInfoBase::new()
+            .try_with_account_addr(var_addr(1000))
+            .is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    #[traced_test]
+    fn config_params() -> anyhow::Result<()> {
+        let param1 = CellBuilder::build_from(0x11u8)?;
+        let param7 = CellBuilder::build_from(0x77u8)?;
+
+        let mut config = dict::Dict::<u32, Cell>::new();
+        config.set(1, param1.clone())?;
+        config.set(7, param7.clone())?;
+        let config_root = config.into_root().unwrap();
+
+        let c7 = tuple![[
+            null,                     // 0
+            null,                     // 1
+            null,                     // 2
+            null,                     // 3
+            null,                     // 4
+            null,                     // 5
+            null,                     // 6
+            null,                     // 7
+            null,                     // 8
+            cell config_root.clone(), // CONFIG_IDX
+        ]];
+
+        assert_run_vm!("CONFIGDICT", c7: c7.clone(), [] => [cell config_root, int 32]);
+
+        assert_run_vm!("INT 1 CONFIGPARAM", c7: c7.clone(), [] => [cell param1.clone(), int -1]);
+        assert_run_vm!("INT 7 CONFIGPARAM", c7: c7.clone(), [] => [cell param7, int -1]);
+        assert_run_vm!("INT 5 CONFIGPARAM", c7: c7.clone(), [] => [int 0]);
+        assert_run_vm!("INT -1 CONFIGPARAM", c7: c7.clone(), [] => [int 0]);
+
+        assert_run_vm!("INT 1 CONFIGOPTPARAM", c7: c7.clone(), [] => [cell param1]);
+        assert_run_vm!("INT 5 CONFIGOPTPARAM", c7: c7, [] => [null]);
+
+        // Empty config
+        let c7 = tuple![[null, null, null, null, null, null, null, null, null, null]];
+        assert_run_vm!("INT 1 CONFIGPARAM", c7: c7.clone(), [] => [int 0]);
+        assert_run_vm!("INT 1 CONFIGOPTPARAM", c7: c7, [] => [null]);
+
+        Ok(())
+    }
+
+    #[test]
+    #[traced_test]
+    fn globals() {
+        let c7 = tuple![[int 0x076ef1ea]];
+
+        assert_run_vm!("INT 10 SETGLOB 1 GETGLOB 1", c7: c7.clone(), [] => [int 10]);
+        assert_run_vm!("INT 10 INT 1 SETGLOBVAR INT 1 GETGLOBVAR", c7: c7.clone(), [] => [int 10]);
+        assert_run_vm!("GETGLOB 1", c7: c7.clone(), [] => [null]);
+        assert_run_vm!("INT 200 GETGLOBVAR", c7: c7.clone(), [] => [null]);
+
+        // SmcInfo stays in place
+        assert_run_vm!("INT 10 SETGLOB 1 GETPARAM 0", c7: c7.clone(), [] => [int 0x076ef1ea]);
+
+        // Index 0 is the SmcInfo tuple itself, and it is writable as in TON
+        assert_run_vm!("INT 0 GETGLOBVAR", c7: c7.clone(), [] => [[int 0x076ef1ea]]);
+        assert_run_vm!("INT 10 INT 0 SETGLOBVAR INT 0 GETGLOBVAR", c7: c7.clone(), [] => [int 10]);
+
+        // Tuple is padded with nulls
+        assert_run_vm!(
+            "INT 10 SETGLOB 5 PUSH c7 TLEN GETGLOB 3 GETGLOB 5",
+            c7: c7.clone(),
+            [] => [int 6, null, int 10]
+        );
+        assert_run_vm!("PUSHNULL SETGLOB 5 PUSH c7 TLEN", c7: c7.clone(), [] => [int 1]);
+        assert_run_vm!("INT 10 INT 254 SETGLOBVAR PUSH c7 TLEN", c7: c7.clone(), [] => [int 255]);
+
+        // Tuple size is limited to 255 items
+        assert_run_vm!("INT 10 INT 255 SETGLOBVAR", c7: c7.clone(), [] => [int 0], exit_code: 5);
+        assert_run_vm!("INT 255 GETGLOBVAR", c7: c7, [] => [int 0], exit_code: 5);
+    }
+}
@@ -1,13 +1,16 @@
 use anyhow::Result;
-use everscale_types::prelude::Cell;
+use bitflags::bitflags;
+use everscale_types::prelude::{Cell, CellSlice};
 use tycho_vm_proc::vm_module;
 
 use crate::cont::{ArgContExt, ControlData, ControlRegs, OrdCont, PushIntCont, RcCont};
 use crate::dispatch::Opcodes;
-use crate::error::VmResult;
+use crate::error::{VmException, VmResult};
+use crate::gas::{GasConsumer, GasParams};
 use crate::saferc::SafeRc;
 use crate::stack::{Stack, StackValueType};
-use crate::state::{SaveCr, VmState};
+use crate::state::{BehaviourModifiers, SaveCr, VmState};
+use crate::util::dump_ref_operand;
 
 pub struct ContOps;
 
@@ -133,9 +136,9 @@ impl ContOps {
 
     #[init]
     fn init_jumps_with_ref(&self, t: &mut Opcodes) -> Result<()> {
-        ok!(t.add_ext(0xdb3c, 16, 0, exec_callref));
-        ok!(t.add_ext(0xdb3d, 16, 0, exec_jmpref));
-        t.add_ext(0xdb3e, 16, 0, exec_jmpref_data)
+        ok!(t.add_ext_with_ref_conts("CALLREF", 0xdb3c, 16, exec_callref, dump_callref));
+        ok!(t.add_ext_with_ref_conts("JMPREF", 0xdb3d, 16, exec_jmpref, dump_jmpref));
+        t.add_ext_with_ref_conts("JMPREFDATA", 0xdb3e, 16, exec_jmpref_data, dump_jmpref_data)
     }
 
     fn exec_callref(st: &mut VmState, _: u32, bits: u16) -> VmResult<i32> {
@@ -154,6 +157,18 @@ impl ContOps {
         st.jump(cont)
     }
 
+    fn dump_callref(code: &mut CellSlice<'_>, _: u32, bits: u16) -> Option<String> {
+        dump_ref_operand(code, bits, 1, "CALLREF")
+    }
+
+    fn dump_jmpref(code: &mut CellSlice<'_>, _: u32, bits: u16) -> Option<String> {
+        dump_ref_operand(code, bits, 1, "JMPREF")
+    }
+
+    fn dump_jmpref_data(code: &mut CellSlice<'_>, _: u32, bits: u16) -> Option<String> {
+        dump_ref_operand(code, bits, 1, "JMPREFDATA")
+    }
+
     #[op(code = "db3f", fmt = "RETDATA")]
     fn exec_ret_data(st: &mut VmState) -> VmResult<i32> {
         ok!(SafeRc::make_mut(&mut st.stack).push(st.code.clone()));
@@ -240,10 +255,32 @@ impl ContOps {
 
     #[init]
     fn init_if_with_ref(&self, t: &mut Opcodes) -> Result<()> {
-        ok!(t.add_ext(0xe300, 16, 0, exec_ifref));
-        ok!(t.add_ext(0xe301, 16, 0, exec_ifnotref));
-        ok!(t.add_ext(0xe302, 16, 0, exec_ifjmpref));
-        t.add_ext(0xe303, 16, 0, exec_ifnotjmpref)
+        ok!(t.add_ext_with_ref_conts("IFREF", 0xe300, 16, exec_ifref, dump_ifref));
+        ok!(t.add_ext_with_ref_conts("IFNOTREF", 0xe301, 16, exec_ifnotref, dump_ifnotref));
+        ok!(t.add_ext_with_ref_conts("IFJMPREF", 0xe302, 16, exec_ifjmpref, dump_ifjmpref));
+        t.add_ext_with_ref_conts(
+            "IFNOTJMPREF",
+            0xe303,
+            16,
+            exec_ifnotjmpref,
+            dump_ifnotjmpref,
+        )
+    }
+
+    fn dump_ifref(code: &mut CellSlice<'_>, _: u32, bits: u16) -> Option<String> {
+        dump_ref_operand(code, bits, 1, "IFREF")
+    }
+
+    fn dump_ifnotref(code: &mut CellSlice<'_>, _: u32, bits: u16) -> Option<String> {
+        dump_ref_operand(code, bits, 1, "IFNOTREF")
+    }
+
+    fn dump_ifjmpref(code: &mut CellSlice<'_>, _: u32, bits: u16) -> Option<String> {
+        dump_ref_operand(code, bits, 1, "IFJMPREF")
+    }
+
+    fn dump_ifnotjmpref(code: &mut CellSlice<'_>, _: u32, bits: u16) -> Option<String> {
+        dump_ref_operand(code, bits, 1, "IFNOTJMPREF")
     }
 
     fn exec_ifref(st: &mut VmState, _: u32, bits: u16) -> VmResult<i32> {
@@ -336,10 +373,41 @@ impl ContOps {
 
     #[init]
     fn init_ifelse_with_ref(&self, t: &mut Opcodes) -> Result<()> {
-        ok!(t.add_ext(0xe30d, 16, 0, exec_ifrefelse));
-        ok!(t.add_ext(0xe30e, 16, 0, exec_ifelseref));
-        ok!(t.add_ext(0xe30f, 16, 0, exec_ifref_elseref));
-        t.add_ext(0xe3c0 >> 6, 10, 0, exec_if_bit_jmpref)
+        ok!(t.add_ext_with_ref_conts("IFREFELSE", 0xe30d, 16, exec_ifrefelse, dump_ifrefelse));
+        ok!(t.add_ext_with_ref_conts("IFELSEREF", 0xe30e, 16, exec_ifelseref, dump_ifelseref));
+        ok!(t.add_ext_with_ref_conts(
+            "IFREFELSEREF",
+            0xe30f,
+            16,
+            exec_ifref_elseref,
+            dump_ifref_elseref
+        ));
+        t.add_ext_with_ref_conts(
+            "IFBITJMPREF",
+            0xe3c0 >> 6,
+            10,
+            exec_if_bit_jmpref,
+            dump_if_bit_jmpref,
+        )
+    }
+
+    fn dump_ifrefelse(code: &mut CellSlice<'_>, _: u32, bits: u16) -> Option<String> {
+        dump_ref_operand(code, bits, 1, "IFREFELSE")
+    }
+
+    fn dump_ifelseref(code: &mut CellSlice<'_>, _: u32, bits: u16) -> Option<String> {
+        dump_ref_operand(code, bits, 1, "IFELSEREF")
+    }
+
+    fn dump_ifref_elseref(code: &mut CellSlice<'_>, _: u32, bits: u16) -> Option<String> {
+        dump_ref_operand(code, bits, 2, "IFREFELSEREF")
+    }
+
+    fn dump_if_bit_jmpref(code: &mut CellSlice<'_>, args: u32, bits: u16) -> Option<String> {
+        let negate = (args & 0x20) != 0;
+        let bit = args & 0x1f;
+        let name = format!("{}BITJMPREF {bit}", if negate { "N" } else { "" });
+        dump_ref_operand(code, bits, 1, &name)
     }
 
     fn exec_ifrefelse(st: &mut VmState, _: u32, bits: u16) -> VmResult<i32> {
@@ -370,6 +438,7 @@ impl ContOps {
             debug_assert!(ok);
 
             vm_log_op!(
+                st,
                 "IFREFELSEREF ({}) ({})",
                 cell1.repr_hash(),
                 cell0.repr_hash()
@@ -384,7 +453,7 @@ impl ContOps {
         st.call(cont)
     }
 
-    #[op(code = "e3$10nx#x", fmt = ("IF{}BITJMP {x}", if n { "N" } else { "" }))]
+    #[op(code = "e3$10nx#x", name = "IFBITJMP", fmt = ("IF{}BITJMP {x}", if n { "N" } else { "" }))]
     fn exec_if_bit_jmp(st: &mut VmState, n: bool, x: u32) -> VmResult<i32> {
         let (cont, bit) = {
             let stack = SafeRc::make_mut(&mut st.stack);
@@ -417,6 +486,7 @@ impl ContOps {
         let negate = (args & 0x20) != 0;
         let bit = args & 0x1f;
         vm_log_op!(
+            st,
             "{}BITJMPREF {bit} ({})",
             if negate { "N" } else { "" },
             cell.repr_hash()
@@ -758,7 +828,12 @@ impl ContOps {
         Ok(0)
     }
 
-    #[op(code = "ede3xx", fmt = ("SETCONTCTRMANY {}", args & 0xff), args(x = Some(args & 0xff)))]
+    #[op(
+        code = "ede3xx",
+        name = "SETCONTCTRMANY",
+        fmt = ("SETCONTCTRMANY {}", args & 0xff),
+        args(x = Some(args & 0xff))
+    )]
     #[op(code = "ede4", fmt = "SETCONTCTRMANYX", args(x = None))]
     fn exec_setcont_ctr_many(st: &mut VmState, x: Option<u32>) -> VmResult<i32> {
         ok!(st.version.require_ton(9..));
@@ -970,7 +1045,7 @@ impl ContOps {
         st.throw_exception_with_arg(n as i32, arg)
     }
 
-    #[op(code = "f2fx @ ..f2f6", fmt = ThrowAnyArgs(x))]
+    #[op(code = "f2fx @ ..f2f6", name = "THROWANY", fmt = ThrowAnyArgs(x))]
     fn exec_throw_any(st: &mut VmState, x: u32) -> VmResult<i32> {
         let args = ThrowAnyArgs(x);
 
@@ -1005,23 +1080,197 @@ impl ContOps {
         exec_try_common(st, Some((p as u16, r as u16)))
     }
 
-    // === Codepage ops ===
-    #[op(code = "fff0", fmt = "SETCPX")]
-    fn exec_set_cp_any(st: &mut VmState) -> VmResult<i32> {
-        let cp = ok!(SafeRc::make_mut(&mut st.stack).pop_smallint_signed_range(-0x8000, 0x7fff));
-        ok!(st.force_cp(cp as i16 as u16));
-        Ok(0)
+    // === Nested VM ops ===
+
+    #[op(code = "db4xxx", fmt = "RUNVM {x}")]
+    fn exec_runvm(st: &mut VmState, x: u32) -> VmResult<i32> {
+        ok!(st.version.require_ton(4..));
+        exec_runvm_common(st, x)
     }
 
-    #[op(code = "ff00", fmt = "SETCP0", args(x = 0i16))]
-    #[op(code = "ffxx @ ff01..fff0", fmt = "SETCP", args(x = (args & 0xff) as i16),)]
-    #[op(code = "fffx @ fff1..", fmt = "SETCP {x}", args(x = (args & 0xf) as i16 - 16))]
-    fn exec_set_cp(st: &mut VmState, x: i16) -> VmResult<i32> {
-        ok!(st.force_cp(x as u16));
-        Ok(0)
+    #[op(code = "db50", fmt = "RUNVMX")]
+    fn exec_runvmx(st: &mut VmState) -> VmResult<i32> {
+        ok!(st.version.require_ton(4..));
+        let flags = ok!(SafeRc::make_mut(&mut st.stack).pop_smallint_range(0, 4095));
+        exec_runvm_common(st, flags)
     }
 }
 
+bitflags! {
+    /// `RUNVM` flags.
+    struct RunVmFlags: u32 {
+        /// Set `c3` to the child code.
+        const SAME_C3 = 1;
+        /// Push an implicit `0` before running the code.
+        const PUSH_ZERO = 1 << 1;
+        /// Load `c4` from the stack and return its committed value.
+        const WITH_DATA = 1 << 2;
+        /// Load the gas limit from the stack and return the consumed gas.
+        const WITH_GAS_LIMIT = 1 << 3;
+        /// Load `c7` from the stack.
+        const WITH_C7 = 1 << 4;
+        /// Return the committed `c5`.
+        const RETURN_ACTIONS = 1 << 5;
+        /// Load the hard gas limit (enabled by `ACCEPT`) from the stack.
+        const WITH_HARD_GAS_LIMIT = 1 << 6;
+        /// Don't share the set of loaded cells and signature checks with the parent.
+        const ISOLATE_GAS = 1 << 7;
+        /// Load the number of return values from the stack.
+        const WITH_RETURN_VALUES = 1 << 8;
+    }
+}
+
+fn exec_runvm_common(st: &mut VmState, flags: u32) -> VmResult<i32> {
+    let Some(flags) = RunVmFlags::from_bits(flags) else {
+        vm_bail!(IntegerOutOfRange {
+            min: 0,
+            max: RunVmFlags::all().bits() as isize,
+            actual: flags.to_string(),
+        });
+    };
+    ok!(st.gas.try_consume(GasConsumer::RUNVM_GAS_PRICE));
+
+    let stack = SafeRc::make_mut(&mut st.stack);
+
+    let mut gas_max = u64::MAX;
+    if flags.contains(RunVmFlags::WITH_HARD_GAS_LIMIT) {
+        gas_max = ok!(stack.pop_long_range(0, i64::MAX as u64));
+    }
+    let mut gas_limit = u64::MAX;
+    if flags.contains(RunVmFlags::WITH_GAS_LIMIT) {
+        gas_limit = ok!(stack.pop_long_range(0, i64::MAX as u64));
+    }
+    if flags.contains(RunVmFlags::WITH_HARD_GAS_LIMIT) {
+        gas_max = std::cmp::max(gas_max, gas_limit);
+    } else {
+        gas_max = gas_limit;
+    }
+
+    let c7 = if flags.contains(RunVmFlags::WITH_C7) {
+        Some(ok!(stack.pop_tuple()))
+    } else {
+        None
+    };
+    let data = if flags.contains(RunVmFlags::WITH_DATA) {
+        Some(SafeRc::unwrap_or_clone(ok!(stack.pop_cell())))
+    } else {
+        None
+    };
+    let ret_vals = if flags.contains(RunVmFlags::WITH_RETURN_VALUES) {
+        Some(ok!(stack.pop_smallint_range(0, 1 << 30)) as usize)
+    } else {
+        None
+    };
+
+    let code = SafeRc::unwrap_or_clone(ok!(stack.pop_cs()));
+    let max_args = stack.depth().saturating_sub(1) as u32;
+    let n = ok!(stack.pop_smallint_range(0, max_args));
+    let mut child_stack = ok!(stack.split_top(n as usize));
+    if flags.contains(RunVmFlags::PUSH_ZERO) {
+        SafeRc::make_mut(&mut child_stack)
+            .items
+            .push(Stack::make_zero());
+    }
+    ok!(st.gas.try_consume_stack_gas(Some(&child_stack)));
+
+    // NOTE: Child gas is paid by the parent, so it can't exceed the remaining gas
+    // (which is checked after paying for the child stack).
+    let remaining = st.gas.remaining();
+    let gas_max = std::cmp::min(gas_max, remaining);
+    let gas_limit = std::cmp::min(gas_limit, remaining);
+
+    let mut builder = VmState::builder()
+        .with_code(code)
+        .with_raw_stack(child_stack)
+        .with_gas(GasParams {
+            max: gas_max,
+            limit: gas_limit,
+            credit: 0,
+            price: st.gas.price(),
+        })
+        .with_gas_prices(*st.gas.prices())
+        .with_version(st.version)
+        .with_modifiers(BehaviourModifiers {
+            // NOTE: Committed state is always returned from the child.
+            auto_commit: true,
+            ..st.modifiers
+        });
+    if flags.contains(RunVmFlags::SAME_C3) {
+        builder = builder.with_init_selector(false);
+    }
+    if let Some(limit) = st.step_limit {
+        // NOTE: Child steps are counted against the parent limit.
+        builder = builder.with_step_limit(limit.saturating_sub(st.steps));
+    }
+    builder.c7 = c7;
+    builder.data = data;
+    // NOTE: Parent codepages are already checked.
+    builder.codepages = st.codepages;
+    builder.libraries = Some(st.gas.libraries());
+    builder.debug = st
+        .debug
+        .as_deref_mut()
+        .map(|debug| debug as &mut dyn std::fmt::Write);
+
+    let mut child = builder.build();
+
+    let isolate_gas = flags.contains(RunVmFlags::ISOLATE_GAS);
+    if !isolate_gas {
+        st.gas.swap_shared(&mut child.gas);
+    }
+    let mut exit_code = !child.run();
+    if !isolate_gas {
+        st.gas.swap_shared(&mut child.gas);
+    }
+
+    let gas_used = child.gas.consumed();
+    st.steps += child.steps;
+    let committed = child.commited_state.take();
+    let mut child_stack = child.take_stack();
+    drop(child);
+
+    let child_stack = SafeRc::make_mut(&mut child_stack);
+    let stack = SafeRc::make_mut(&mut st.stack);
+    if exit_code == 0 || exit_code == 1 {
+        match ret_vals {
+            Some(n) if child_stack.depth() < n => {
+                exit_code = VmException::StackUnderflow as i32;
+                ok!(stack.push_zero());
+            }
+            n => {
+                let n = n.unwrap_or(child_stack.depth());
+                ok!(stack.move_from_stack(child_stack, n));
+            }
+        }
+    } else {
+        // Exception argument
+        match child_stack.items.pop() {
+            Some(arg) => ok!(stack.push_raw(arg)),
+            None => ok!(stack.push_zero()),
+        }
+    }
+    ok!(stack.push_int(exit_code));
+
+    if flags.contains(RunVmFlags::WITH_DATA) {
+        match &committed {
+            Some(committed) => ok!(stack.push(committed.c4.clone())),
+            None => ok!(stack.push_null()),
+        }
+    }
+    if flags.contains(RunVmFlags::RETURN_ACTIONS) {
+        match &committed {
+            Some(committed) => ok!(stack.push(committed.c5.clone())),
+            None => ok!(stack.push_null()),
+        }
+    }
+    if flags.contains(RunVmFlags::WITH_GAS_LIMIT) {
+        ok!(stack.push_int(gas_used));
+    }
+
+    ok!(st.gas.try_consume(gas_used));
+    Ok(0)
+}
+
 fn exec_ref_prefix(st: &mut VmState, bits: u16, name: &str) -> VmResult<RcCont> {
     let code_range = st.code.range();
     vm_ensure!(code_range.has_remaining(bits, 1), InvalidOpcode);
@@ -1034,7 +1283,7 @@ fn exec_ref_prefix(st: &mut VmState, bits: u16, name: &str) -> VmResult<RcCont>
     let ok = st.code.range_mut().skip_first(0, 1).is_ok();
     debug_assert!(ok);
 
-    vm_log_op!("{name} ({})", code.repr_hash());
+    vm_log_op!(st, "{name} ({})", code.repr_hash());
     st.ref_to_cont(code)
 }
 
@@ -1050,7 +1299,7 @@ fn exec_cell_prefix(st: &mut VmState, bits: u16, name: &str) -> VmResult<Cell> {
     let ok = st.code.range_mut().skip_first(0, 1).is_ok();
     debug_assert!(ok);
 
-    vm_log_op!("{name} ({})", cell.repr_hash());
+    vm_log_op!(st, "{name} ({})", cell.repr_hash());
     Ok(cell)
 }
 
@@ -1072,7 +1321,7 @@ fn exec_ifelse_ref_impl(st: &mut VmState, bits: u16, ref_first: bool) -> VmResul
             false => "IFELSEREF",
         };
 
-        vm_log_op!("{name} ({})", cell.repr_hash());
+        vm_log_op!(st, "{name} ({})", cell.repr_hash());
 
         let stack = SafeRc::make_mut(&mut st.stack);
         let cont = ok!(stack.pop_cont());
@@ -1097,7 +1346,7 @@ fn exec_setcontargs_common(st: &mut VmState, copy: u32, more: i32) -> VmResult<(
             if let Some(cdata_stack) = &mut cdata.stack {
                 ok!(SafeRc::make_mut(cdata_stack).move_from_stack(stack, copy as _));
             } else {
-                cdata.stack = Some(ok!(stack.split_top(copy as _)));
+                cdata.stack = Some(ok!(stack.take_top(copy as _)));
             }
 
             st.gas.try_consume_stack_gas(cdata.stack.as_ref())?;
@@ -1131,7 +1380,7 @@ fn exec_return_args_common(st: &mut VmState, count: u32) -> VmResult<()> {
         let copy = match (count as usize).cmp(&depth) {
             std::cmp::Ordering::Less => depth - count as usize,
             std::cmp::Ordering::Equal => return Ok(()),
-            std::cmp::Ordering::Greater => vm_bail!(StackUnderflow(count as _)),
+            std::cmp::Ordering::Greater => vm_bail!(stack_underflow(count as _)),
         };
         let new_stack = ok!(stack.split_top(count as _));
 
@@ -1263,15 +1512,199 @@ impl std::fmt::Display for ThrowAnyArgs {
 #[cfg(test)]
 mod tests {
     use everscale_types::boc::Boc;
+    use everscale_types::prelude::{CellBuilder, CellFamily};
     use num_bigint::BigInt;
     use tracing_test::traced_test;
 
     use super::*;
     use crate::cont::QuitCont;
+    use crate::gas::GasPrices;
     use crate::instr::codepage0;
+    use crate::stack::Tuple;
     use crate::state::IntoCode;
+    use crate::tests::run_code;
     use crate::OwnedCellSlice;
 
+    #[test]
+    #[traced_test]
+    fn bool_compose() {
+        // Predicates return with RET (true) or RETALT (false)
+        assert_run_vm!("PUSHCONT { } BOOLEVAL INT 3", [] => [int -1, int 3]);
+        assert_run_vm!("PUSHCONT { RETALT } BOOLEVAL INT 3", [] => [int 0, int 3]);
+        assert_run_vm!(
+            "PUSHCONT { GTINT 0 IFRET RETALT } BOOLEVAL",
+            [int 5] => [int -1],
+        );
+        assert_run_vm!(
+            "PUSHCONT { GTINT 0 IFRET RETALT } BOOLEVAL",
+            [int -5] => [int 0],
+        );
+
+        // BOOLAND
+        assert_run_vm!("PUSHCONT { } PUSHCONT { } BOOLAND BOOLEVAL", [] => [int -1]);
+        assert_run_vm!("PUSHCONT { } PUSHCONT { RETALT } BOOLAND BOOLEVAL", [] => [int 0]);
+        // The second predicate is skipped
+        assert_run_vm!(
+            "PUSHCONT { RETALT } PUSHCONT { INT 7 } BOOLAND BOOLEVAL",
+            [] => [int 0],
+        );
+
+        // BOOLOR
+        assert_run_vm!("PUSHCONT { RETALT } PUSHCONT { } BOOLOR BOOLEVAL", [] => [int -1]);
+        assert_run_vm!(
+            "PUSHCONT { RETALT } PUSHCONT { RETALT } BOOLOR BOOLEVAL",
+            [] => [int 0],
+        );
+        // The second predicate is skipped
+        assert_run_vm!("PUSHCONT { } PUSHCONT { INT 7 } BOOLOR BOOLEVAL", [] => [int -1]);
+    }
+
+    #[test]
+    #[traced_test]
+    fn ret_primitives() {
+        // PUSHCONT { RETTRUE } and PUSHCONT { RETFALSE },
+        // which are assembler aliases for RET and RETALT
+        assert_run_vm!("@inline x{92db30} BOOLEVAL", [] => [int -1]);
+        assert_run_vm!("@inline x{92db31} BOOLEVAL", [] => [int 0]);
+
+        // BRANCH is an assembler alias for RETBOOL
+        assert_run_vm!("PUSHCONT { RETBOOL } BOOLEVAL", [int 5] => [int -1]);
+        assert_run_vm!("PUSHCONT { RETBOOL } BOOLEVAL", [int 0] => [int 0]);
+        assert_run_vm!(
+            "PUSHCONT { INT 1 RETBOOL INT 2 } BOOLEVAL",
+            [] => [int -1],
+        );
+        assert_run_vm!("PUSHCONT { RETBOOL } BOOLEVAL", [null] => [int 0], exit_code: 7);
+
+        // JMPXDATA pushes the rest of the current code
+        assert_run_vm!("PUSHCONT { SBITS } JMPXDATA INT 1", [] => [int 8]);
+        assert_run_vm!("PUSHCONT { DROP INT 7 } JMPXDATA INT 1", [] => [int 7]);
+    }
+
+    #[test]
+    #[traced_test]
+    fn condsel() {
+        assert_run_vm!("CONDSEL", [int -1, int 10, int 20] => [int 10]);
+        assert_run_vm!("CONDSEL", [int 0, int 10, int 20] => [int 20]);
+        assert_run_vm!("CONDSEL", [int 5, int 10, null] => [int 10]);
+        assert_run_vm!("CONDSEL", [int 0, int 10, null] => [null]);
+        assert_run_vm!("CONDSEL", [null, int 10, int 20] => [int 0], exit_code: 7);
+        assert_run_vm!("CONDSEL", [int 10, int 20] => [int 0], exit_code: 2);
+
+        assert_run_vm!("CONDSELCHK", [int -1, int 10, int 20] => [int 10]);
+        assert_run_vm!("CONDSELCHK", [int 0, int 10, int 20] => [int 20]);
+        assert_run_vm!("CONDSELCHK", [int 0, null, null] => [null]);
+        assert_run_vm!("CONDSELCHK", [int -1, int 10, null] => [int 0], exit_code: 7);
+        assert_run_vm!("CONDSELCHK", [int 0, cell Cell::empty_cell(), int 1] => [int 0], exit_code: 7);
+    }
+
+    #[test]
+    #[traced_test]
+    fn setcontargs() {
+        // SETCONTARGS 2,-1
+        assert_run_vm!(
+            "INT 1 INT 2 PUSHCONT { ADD } @inline x{ec2f} EXECUTE",
+            [] => [int 3],
+        );
+        // SETCONTARGS 2,0 (preloaded args only, the rest of the stack is kept)
+        assert_run_vm!(
+            "INT 1 INT 2 PUSHCONT { ADD } @inline x{ec20} EXECUTE",
+            [int 5] => [int 5, int 3],
+        );
+        // SETNUMARGS 1
+        assert_run_vm!(
+            "INT 1 INT 2 PUSHCONT { ADD } @inline x{ec01} EXECUTE",
+            [] => [int 0],
+            exit_code: 2
+        );
+        // Not enough values to preload
+        assert_run_vm!("PUSHCONT { } @inline x{ec2f}", [int 1] => [int 0], exit_code: 2);
+
+        assert_run_vm!(
+            "INT 1 INT 2 PUSHCONT { ADD } INT 2 INT -1 SETCONTVARARGS EXECUTE",
+            [] => [int 3],
+        );
+        assert_run_vm!(
+            "INT 1 INT 2 PUSHCONT { ADD } INT 2 SETNUMVARARGS EXECUTE",
+            [int 5] => [int 5, int 3],
+        );
+        assert_run_vm!(
+            "INT 1 INT 2 PUSHCONT { ADD } INT 1 SETNUMVARARGS EXECUTE",
+            [] => [int 0],
+            exit_code: 2
+        );
+    }
+
+    #[test]
+    #[traced_test]
+    fn bless() {
+        let code = make_code(tvmasm!("INT 5 INT 6 ADD"));
+        assert_run_vm!("INT 5 INT 6 ADD", [] => [int 11]);
+        assert_run_vm!("BLESS EXECUTE", [slice code.clone()] => [int 11]);
+        assert_run_vm!("BLESS JMPX", [slice code] => [int 11]);
+        assert_run_vm!("BLESS", [int 1] => [int 0], exit_code: 7);
+
+        let add = make_code(tvmasm!("ADD"));
+        // BLESSARGS 2,-1
+        assert_run_vm!(
+            "@inline x{ee2f} EXECUTE",
+            [int 1, int 2, slice add.clone()] => [int 3],
+        );
+        // BLESSARGS 1,0
+        assert_run_vm!(
+            "@inline x{ee10} EXECUTE",
+            [int 1, int 2, slice add.clone()] => [int 0],
+            exit_code: 2
+        );
+        assert_run_vm!(
+            "BLESSVARARGS EXECUTE",
+            [int 1, int 2, slice add, int 2, int -1] => [int 3],
+        );
+    }
+
+    #[test]
+    fn deep_recursion() {
+        // Nested calls are chained through `c0` instead of the native stack
+        assert_run_vm!(
+            r#"
+            PUSHCONT {
+                OVER
+                IFNOTRET
+                SWAP
+                DEC
+                SWAP
+                DUP
+                EXECUTE
+            }
+            DUP
+            EXECUTE
+            DROP
+            "#,
+            gas: 100_000_000,
+            [int 20000] => [int 0],
+        );
+
+        // The whole chain of return continuations is dropped at once
+        assert_run_vm!(
+            r#"
+            PUSHCONT {
+                OVER
+                THROWIFNOT 42
+                SWAP
+                DEC
+                SWAP
+                DUP
+                EXECUTE
+            }
+            DUP
+            EXECUTE
+            "#,
+            gas: 100_000_000,
+            [int 20000] => [int 0],
+            exit_code: 42
+        );
+    }
+
     #[test]
     #[traced_test]
     fn argument_contops() {
@@ -1398,6 +1831,195 @@ mod tests {
         );
     }
 
+    #[test]
+    #[traced_test]
+    fn call_and_jump_contops() {
+        // Calls return to the rest of the code
+        assert_run_vm!(
+            "PUSHCONT { PUSHINT 3 PUSHINT 4 } EXECUTE PUSHINT 5",
+            [int 1] => [int 1, int 3, int 4, int 5]
+        );
+        assert_run_vm!(
+            "PUSHCONT { PUSHINT 3 PUSHINT 4 } CALLX PUSHINT 5",
+            [int 1] => [int 1, int 3, int 4, int 5]
+        );
+
+        // Jumps don't
+        assert_run_vm!(
+            "PUSHCONT { PUSHINT 3 PUSHINT 4 } JMPX PUSHINT 5",
+            [int 1] => [int 1, int 3, int 4]
+        );
+
+        // Only the requested number of values is passed and returned,
+        // the rest of the caller stack is preserved
+        assert_run_vm!(
+            "PUSHCONT { ADD PUSHINT 7 PUSHINT 8 } CALLXARGS 2, 1 PUSHINT 5",
+            [int 10, int 1, int 2] => [int 10, int 8, int 5]
+        );
+        assert_run_vm!(
+            "PUSHCONT { DEPTH } CALLXARGS 2, 2",
+            [int 10, int 1, int 2] => [int 10, int 2, int 2]
+        );
+        assert_run_vm!(
+            "PUSHCONT { DEPTH } JMPXARGS 1",
+            [int 10, int 1, int 2] => [int 2, int 1]
+        );
+
+        // Not a continuation
+        assert_run_vm!("EXECUTE", [int 1] => [int 0], exit_code: 7);
+        assert_run_vm!("JMPX", [null] => [int 0], exit_code: 7);
+        assert_run_vm!("CALLXARGS 0, 1", [int 1] => [int 0], exit_code: 7);
+    }
+
+    #[test]
+    #[traced_test]
+    fn loop_iterations_counter() {
+        // REPEAT
+        for (n, iterations) in [(5, 5), (1, 1), (0, 0), (-3, 0)] {
+            assert_run_vm!(
+                r#"
+                INT 0 SETGLOB 1
+                PUSHCONT { GETGLOB 1 INC SETGLOB 1 }
+                REPEAT
+                GETGLOB 1
+                "#,
+                [int n] => [int iterations]
+            );
+        }
+
+        // REPEATEND
+        assert_run_vm!(
+            r#"
+            INT 0 SETGLOB 1
+            PUSHCONT {
+                REPEATEND
+                GETGLOB 1 INC SETGLOB 1
+            }
+            CALLX
+            GETGLOB 1
+            "#,
+            [int 4] => [int 4]
+        );
+
+        // UNTIL, the body is executed at least once
+        assert_run_vm!(
+            r#"
+            SETGLOB 1
+            PUSHCONT { GETGLOB 1 INC DUP SETGLOB 1 GTINT 3 }
+            UNTIL
+            GETGLOB 1
+            "#,
+            [int 0] => [int 4]
+        );
+        assert_run_vm!(
+            r#"
+            SETGLOB 1
+            PUSHCONT { GETGLOB 1 INC DUP SETGLOB 1 GTINT 3 }
+            UNTIL
+            GETGLOB 1
+            "#,
+            [int 10] => [int 11]
+        );
+
+        // WHILE, the body may be skipped
+        assert_run_vm!(
+            r#"
+            SETGLOB 1
+            PUSHCONT { GETGLOB 1 LESSINT 3 }
+            PUSHCONT { GETGLOB 1 INC SETGLOB 1 }
+            WHILE
+            GETGLOB 1
+            "#,
+            [int 0] => [int 3]
+        );
+        assert_run_vm!(
+            r#"
+            SETGLOB 1
+            PUSHCONT { GETGLOB 1 LESSINT 3 }
+            PUSHCONT { GETGLOB 1 INC SETGLOB 1 }
+            WHILE
+            GETGLOB 1
+            "#,
+            [int 5] => [int 5]
+        );
+
+        // AGAINBRK, exited with RETALT
+        assert_run_vm!(
+            r#"
+            INT 0 SETGLOB 1
+            PUSHCONT { GETGLOB 1 INC DUP SETGLOB 1 EQINT 3 IFRETALT }
+            AGAINBRK
+            GETGLOB 1
+            "#,
+            [] => [int 3]
+        );
+    }
+
+    #[test]
+    #[traced_test]
+    fn control_register_contops() {
+        assert_run_vm!(
+            "INT 123 NEWC STU 32 ENDC POP c4 PUSH c4 CTOS LDU 32 ENDS",
+            [] => [int 123]
+        );
+
+        // Swap the data and read both values back
+        assert_run_vm!(
+            r#"
+            INT 1 NEWC STU 8 ENDC POP c4
+            INT 2 NEWC STU 8 ENDC
+            PUSH c4 SWAP POP c4
+            CTOS PLDU 8
+            PUSH c4 CTOS PLDU 8
+            "#,
+            [] => [int 1, int 2]
+        );
+        assert_run_vm!(
+            r#"
+            INT 1 NEWC STU 8 ENDC INT 4 POPCTRX
+            INT 4 PUSHCTRX CTOS PLDU 8
+            "#,
+            [] => [int 1]
+        );
+        assert_run_vm!("INT 5 INT 6 PAIR POP c7 PUSH c7 SECOND", [] => [int 6]);
+
+        // Invalid value types
+        assert_run_vm!("INT 1 POP c4", [] => [int 0], exit_code: 7);
+        assert_run_vm!("PUSHCONT { } POP c5", [] => [int 0], exit_code: 7);
+        assert_run_vm!("NEWC ENDC POP c0", [] => [int 0], exit_code: 7);
+        assert_run_vm!("INT 1 POP c7", [] => [int 0], exit_code: 7);
+        assert_run_vm!("INT 1 INT 4 POPCTRX", [] => [int 0], exit_code: 7);
+
+        // Invalid register index
+        assert_run_vm!("NEWC ENDC INT 6 POPCTRX", [] => [int 0], exit_code: 5);
+        assert_run_vm!("INT 6 PUSHCTRX", [] => [int 0], exit_code: 5);
+
+        // Saved registers are restored when the continuation is entered
+        assert_run_vm!(
+            r#"
+            INT 7 NEWC STU 8 ENDC
+            PUSHCONT { PUSH c4 CTOS PLDU 8 }
+            SETCONTCTR c4
+            EXECUTE
+            "#,
+            [] => [int 7]
+        );
+        assert_run_vm!(
+            r#"
+            INT 7 NEWC STU 8 ENDC
+            PUSHCONT { PUSH c4 CTOS PLDU 8 }
+            INT 4 SETCONTCTRX
+            EXECUTE
+            "#,
+            [] => [int 7]
+        );
+        assert_run_vm!(
+            "INT 1 PUSHCONT { } SETCONTCTR c4",
+            [] => [int 0],
+            exit_code: 7
+        );
+    }
+
     #[test]
     #[traced_test]
     fn basic_contops() -> anyhow::Result<()> {
@@ -1581,6 +2203,40 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    #[traced_test]
+    fn conditional_jmp_contops() -> anyhow::Result<()> {
+        let code = make_code(tvmasm! {
+            r#"
+            PUSHINT 1
+            PUSHINT 2
+            "#
+        });
+        let cont = SafeRc::new_dyn_value(OrdCont::simple(code, codepage0().id()));
+
+        // Jumps never return to the rest of the code
+        assert_run_vm!("IFJMP PUSHINT 5", [int -1, raw cont.clone()] => [int 1, int 2]);
+        assert_run_vm!("IFJMP PUSHINT 5", [int 0, raw cont.clone()] => [int 5]);
+        assert_run_vm!("IFNOTJMP PUSHINT 5", [int 0, raw cont.clone()] => [int 1, int 2]);
+        assert_run_vm!("IFNOTJMP PUSHINT 5", [int 3, raw cont.clone()] => [int 5]);
+
+        // Calls do
+        assert_run_vm!("IF PUSHINT 5", [int -1, raw cont.clone()] => [int 1, int 2, int 5]);
+        assert_run_vm!("IFNOT PUSHINT 5", [int 0, raw cont.clone()] => [int 1, int 2, int 5]);
+
+        // Non-integer condition
+        assert_run_vm!("IF", [null, raw cont.clone()] => [int 0], exit_code: 7);
+        assert_run_vm!("IFNOT", [null, raw cont.clone()] => [int 0], exit_code: 7);
+        assert_run_vm!("IFJMP", [null, raw cont.clone()] => [int 0], exit_code: 7);
+        assert_run_vm!("IFNOTJMP", [null, raw cont.clone()] => [int 0], exit_code: 7);
+
+        // Non-continuation argument
+        assert_run_vm!("IF", [int -1, int 1] => [int 0], exit_code: 7);
+        assert_run_vm!("IFJMP", [int -1, int 1] => [int 0], exit_code: 7);
+
+        Ok(())
+    }
+
     #[test]
     #[traced_test]
     fn conditional_refcontops() -> anyhow::Result<()> {
@@ -1592,11 +2248,52 @@ mod tests {
         });
         let cont = SafeRc::new_dyn_value(OrdCont::simple(code, codepage0().id()));
 
-        // assert_run_vm!(
-        //     "IFREF",
-        //     code.as,
-        //     [int 0, raw cont.clone()] => [],
-        // );
+        // `<opcode>` with a `{ PUSHINT 1 PUSHINT 2 }` reference, followed by `PUSHINT 5`
+        let make_ref_code = |opcode: u16, with_ref: bool| -> anyhow::Result<Cell> {
+            let mut b = CellBuilder::new();
+            b.store_u16(opcode)?;
+            if with_ref {
+                b.store_reference(Boc::decode(tvmasm!("PUSHINT 1 PUSHINT 2"))?)?;
+            }
+            b.store_u8(0x75)?;
+            Ok(b.build()?)
+        };
+
+        let cases = [
+            // IFREF
+            (0xe300, -1, tuple![int 1, int 2, int 5]),
+            (0xe300, 0, tuple![int 5]),
+            // IFNOTREF
+            (0xe301, 0, tuple![int 1, int 2, int 5]),
+            (0xe301, 1, tuple![int 5]),
+            // IFJMPREF
+            (0xe302, -1, tuple![int 1, int 2]),
+            (0xe302, 0, tuple![int 5]),
+            // IFNOTJMPREF
+            (0xe303, 0, tuple![int 1, int 2]),
+            (0xe303, 1, tuple![int 5]),
+        ];
+        for (opcode, flag, expected) in cases {
+            let (exit_code, vm) = run_code(make_ref_code(opcode, true)?, tuple![int flag]);
+            assert_eq!(exit_code, 0);
+            crate::tests::compare_stack(&vm.stack.items, &expected);
+        }
+
+        // The reference is loaded only when the branch is taken
+        let gas_cases = [
+            (0, 26 + 18 + 5),
+            (-1, 26 + GasPrices::DEFAULT.new_cell + 18 + 18 + 5 + 18 + 5),
+        ];
+        for (flag, expected_gas) in gas_cases {
+            let (_, vm) = run_code(make_ref_code(0xe300, true)?, tuple![int flag]);
+            assert_eq!(vm.gas.consumed(), expected_gas);
+        }
+
+        // The reference is required even if the branch is not taken
+        for opcode in [0xe300, 0xe301, 0xe302, 0xe303] {
+            let (exit_code, _) = run_code(make_ref_code(opcode, false)?, tuple![int 0]);
+            assert_eq!(exit_code, VmException::InvalidOpcode as i32);
+        }
 
         assert_run_vm!(
             "IF",
@@ -1702,6 +2399,15 @@ mod tests {
             "REPEATEND PUSHINT 2",
             [int 3] => [int 2, int 2, int 2]
         );
+        assert_run_vm!("REPEATEND INC", [int 0, int 5] => [int 5]);
+        assert_run_vm!("REPEATEND INC", [int 0, int 0] => [int 0]);
+        assert_run_vm!("REPEATEND INC", [int 0, int -1] => [int 0]);
+
+        // The rest of the called continuation is the body
+        assert_run_vm!(
+            "PUSHCONT { REPEATEND INC } CALLX PUSHINT 7",
+            [int 0, int 3] => [int 3, int 7]
+        );
 
         // UNTIL
         assert_run_vm!(
@@ -1715,6 +2421,11 @@ mod tests {
             "UNTILEND PUSHINT 0 PUSHINT 1",
             [int 3] => [int 3, int 0]
         );
+        assert_run_vm!("UNTILEND INC DUP GTINT 2", [int 0] => [int 3]);
+        assert_run_vm!(
+            "PUSHCONT { UNTILEND INC DUP GTINT 2 } CALLX PUSHINT 7",
+            [int 0] => [int 3, int 7]
+        );
 
         // WHILE
         let code0 = make_code(tvmasm! {
@@ -1742,6 +2453,12 @@ mod tests {
             "WHILEEND PUSHINT 1",
             [int 2, raw c1.clone()] => [int 2]
         );
+        assert_run_vm!("PUSHCONT { DUP LESSINT 3 } WHILEEND INC", [int 0] => [int 3]);
+        assert_run_vm!("PUSHCONT { DUP LESSINT 3 } WHILEEND INC", [int 5] => [int 5]);
+        assert_run_vm!(
+            "PUSHCONT { PUSHCONT { DUP LESSINT 3 } WHILEEND INC } CALLX PUSHINT 7",
+            [int 0] => [int 3, int 7]
+        );
 
         // AGAIN
         // TODO: TEST MORE CASES
@@ -1875,6 +2592,199 @@ mod tests {
         );
     }
 
+    #[test]
+    #[traced_test]
+    fn atexit_contops() {
+        // Finalizer runs on the implicit RET
+        assert_run_vm!("PUSHCONT { INT 9 } ATEXIT INT 1", [] => [int 1, int 9]);
+        assert_run_vm!(
+            "PUSHCONT { PUSHCONT { INT 9 } ATEXIT INT 1 } CALLX INT 2",
+            [] => [int 1, int 9, int 2]
+        );
+
+        // ... and on an explicit one
+        assert_run_vm!(
+            "PUSHCONT { PUSHCONT { INT 9 } ATEXIT INT 1 RET INT 5 } CALLX INT 2",
+            [] => [int 1, int 9, int 2]
+        );
+
+        // Alternative finalizers run on RETALT
+        assert_run_vm!(
+            "PUSHCONT { INT 9 } ATEXITALT INT 1 RETALT INT 5",
+            [] => [int 1, int 9]
+        );
+        assert_run_vm!(
+            "PUSHCONT { INT 9 } SETEXITALT INT 1 RETALT INT 5",
+            [] => [int 1, int 9]
+        );
+
+        // Composition
+        assert_run_vm!(
+            "PUSHCONT { INT 1 } PUSHCONT { INT 2 } COMPOS EXECUTE INT 3",
+            [] => [int 1, int 2]
+        );
+        assert_run_vm!(
+            "PUSHCONT { INT 1 RETALT } PUSHCONT { INT 2 } COMPOSALT EXECUTE INT 3",
+            [] => [int 1, int 2, int 3]
+        );
+        assert_run_vm!(
+            "PUSHCONT { INT 1 } PUSHCONT { INT 2 } COMPOSBOTH EXECUTE INT 3",
+            [] => [int 1, int 2]
+        );
+
+        // SAVECTR makes RET restore the register
+        assert_run_vm!(
+            r#"
+            PUSHCONT {
+                INT 1 NEWC STU 8 ENDC POP c4
+                SAVECTR c4
+                INT 2 NEWC STU 8 ENDC POP c4
+            }
+            CALLX
+            PUSH c4 CTOS PLDU 8
+            "#,
+            [] => [int 1]
+        );
+    }
+
+    #[test]
+    fn throw_contops() {
+        // Unhandled exceptions terminate with the thrown code
+        assert_run_vm!("INT 1 THROW 42", [] => [int 0], exit_code: 42);
+        assert_run_vm!("INT 1 THROW 1000", [] => [int 0], exit_code: 1000);
+
+        // Conditional forms pop the flag first
+        assert_run_vm!(
+            "PUSHCONT { INT 1 } POP c2 INT 5 TRUE THROWIF 42 INT 7",
+            [] => [int 0, int 42, int 1]
+        );
+        assert_run_vm!(
+            "PUSHCONT { INT 1 } POP c2 INT 5 FALSE THROWIF 42 INT 7",
+            [] => [int 5, int 7]
+        );
+        assert_run_vm!(
+            "PUSHCONT { INT 1 } POP c2 INT 5 TRUE THROWIFNOT 42 INT 7",
+            [] => [int 5, int 7]
+        );
+        assert_run_vm!(
+            "PUSHCONT { INT 1 } POP c2 INT 5 FALSE THROWIFNOT 42 INT 7",
+            [] => [int 0, int 42, int 1]
+        );
+
+        // Argument is passed to the handler instead of zero
+        assert_run_vm!(
+            "PUSHCONT { INT 1 } POP c2 INT 5 INT 123 THROWARG 300",
+            [] => [int 123, int 300, int 1]
+        );
+        assert_run_vm!(
+            "PUSHCONT { INT 1 } POP c2 INT 123 FALSE THROWARGIF 300 INT 7",
+            [] => [int 7]
+        );
+
+        // Dynamic exception codes
+        assert_run_vm!(
+            "PUSHCONT { INT 1 } POP c2 INT 5 INT 77 THROWANY",
+            [] => [int 0, int 77, int 1]
+        );
+        assert_run_vm!(
+            "PUSHCONT { INT 1 } POP c2 INT 123 INT 77 THROWARGANY",
+            [] => [int 123, int 77, int 1]
+        );
+        assert_run_vm!(
+            "PUSHCONT { INT 1 } POP c2 INT 5 INT 77 FALSE THROWANYIF INT 7",
+            [] => [int 5, int 7]
+        );
+        assert_run_vm!("INT 65536 THROWANY", [] => [int 0], exit_code: 5);
+    }
+
+    #[test]
+    fn throw_arg_preserved() {
+        let cell = CellBuilder::build_from(0xdeafbeafu32).unwrap();
+
+        // Any value can be used as an argument
+        assert_run_vm!(
+            "PUSHCONT { INT 1 } POP c2 THROWARG 300",
+            [cell cell.clone()] => [cell cell.clone(), int 300, int 1]
+        );
+        assert_run_vm!(
+            "PUSHCONT { INT 1 } POP c2 INT 300 THROWARGANY",
+            [cell cell.clone()] => [cell cell.clone(), int 300, int 1]
+        );
+        assert_run_vm!(
+            "PUSHCONT { INT 1 } POP c2 INT 300 TRUE THROWARGANYIF",
+            [cell cell.clone()] => [cell cell, int 300, int 1]
+        );
+        assert_run_vm!(
+            "PUSHCONT { THROWARG 300 } PUSHCONT { DROP CTOS LDU 32 ENDS } TRY",
+            [cell CellBuilder::build_from(123u32).unwrap()] => [int 123]
+        );
+
+        // Implicit exceptions always pass a zero argument
+        assert_run_vm!(
+            "PUSHCONT { INT 1 } POP c2 INT 1 INT 0 DIV",
+            [] => [int 0, int 4, int 1]
+        );
+        assert_run_vm!(
+            "PUSHCONT { INT 1 } POP c2 NEWC ENDC CTOS LDREF",
+            [] => [int 0, int 9, int 1]
+        );
+    }
+
+    #[test]
+    fn try_contops() {
+        // Handler receives the argument and the exception code
+        assert_run_vm!(
+            "INT 9 PUSHCONT { INT 1 THROW 42 } PUSHCONT { INT 2 } TRY INT 3",
+            [] => [int 0, int 42, int 2, int 3]
+        );
+        assert_run_vm!(
+            "PUSHCONT { INT 5 THROWARG 42 } PUSHCONT { } TRY INT 3",
+            [] => [int 5, int 42, int 3]
+        );
+
+        // Handler is not invoked when the body succeeds
+        assert_run_vm!(
+            "INT 9 PUSHCONT { INT 1 } PUSHCONT { INT 2 } TRY INT 3",
+            [] => [int 9, int 1, int 3]
+        );
+
+        // `c2` is restored after both normal and exceptional completion
+        assert_run_vm!(
+            "PUSHCONT { INT 1 } PUSHCONT { INT 2 } TRY THROW 50",
+            [] => [int 0],
+            exit_code: 50,
+        );
+        assert_run_vm!(
+            "PUSHCONT { THROW 42 } PUSHCONT { INT 2 } TRY THROW 50",
+            [] => [int 0],
+            exit_code: 50,
+        );
+
+        // Rethrow from a nested handler reaches the outer one
+        assert_run_vm!(
+            r#"
+            PUSHCONT {
+                PUSHCONT { THROW 42 }
+                PUSHCONT { INC THROWANY }
+                TRY
+            }
+            PUSHCONT { INT 1 }
+            TRY
+            "#,
+            [] => [int 0, int 43, int 1]
+        );
+
+        // TRYARGS passes `p` values and returns `r` values
+        assert_run_vm!(
+            "INT 7 INT 8 INT 9 PUSHCONT { ADD } PUSHCONT { DROP2 INT 2 } TRYARGS 2,1",
+            [] => [int 7, int 17]
+        );
+        assert_run_vm!(
+            "INT 7 INT 8 INT 9 PUSHCONT { THROW 42 } PUSHCONT { NIP } TRYARGS 2,1",
+            [] => [int 7, int 42]
+        );
+    }
+
     #[test]
     // #[traced_test]
     fn infinite_recursion() {
@@ -2015,4 +2925,144 @@ mod tests {
     fn make_code(code: &[u8]) -> OwnedCellSlice {
         Boc::decode(code).unwrap().into_code().unwrap()
     }
+
+    #[test]
+    #[traced_test]
+    fn runvm_works() -> anyhow::Result<()> {
+        let child = |code: &[u8]| Boc::decode(code).map(OwnedCellSlice::new_allow_exotic);
+
+        // Plain call with all results returned
+        let stack = tuple![int 1, int 2, int 3, int 2, slice child(tvmasm!("ADD"))?];
+        let (exit_code, vm) = run_runvm(0, stack)?;
+        assert_eq!(exit_code, 0);
+        crate::tests::compare_stack(&vm.stack.items, &tuple![int 1, int 5, int 0]);
+
+        // Data is loaded from the stack and committed on exit
+        let stack = tuple![
+            int 7,
+            int 1,
+            slice child(tvmasm!("DROP INT 1 INT 2 NEWC ENDC POP c4"))?,
+            int 1,
+            cell CellBuilder::build_from(0xdeadu16)?,
+        ];
+        let (exit_code, vm) = run_runvm(0x104, stack)?;
+        assert_eq!(exit_code, 0);
+        let expected = tuple![int 2, int 0, cell Cell::empty_cell()];
+        crate::tests::compare_stack(&vm.stack.items, &expected);
+
+        // Not enough return values
+        let stack = tuple![int 0, slice child(tvmasm!("INT 1"))?, int 2];
+        let (exit_code, vm) = run_runvm(0x100, stack)?;
+        assert_eq!(exit_code, 0);
+        crate::tests::compare_stack(&vm.stack.items, &tuple![int 0, int 2]);
+
+        // Child exceptions are returned to the parent
+        let stack = tuple![int 0, slice child(tvmasm!("ADD"))?];
+        let (exit_code, vm) = run_runvm(0, stack)?;
+        assert_eq!(exit_code, 0);
+        crate::tests::compare_stack(&vm.stack.items, &tuple![int 0, int 2]);
+
+        // Consumed gas (PUSHINT + implicit RET)
+        let stack = tuple![int 0, slice child(tvmasm!("INT 1"))?, int 1000];
+        let (exit_code, vm) = run_runvm(0x8, stack)?;
+        assert_eq!(exit_code, 0);
+        crate::tests::compare_stack(&vm.stack.items, &tuple![int 1, int 0, int 23]);
+
+        // Gas limit without a hard limit can't be raised by ACCEPT
+        let stack = tuple![int 0, slice child(tvmasm!("ACCEPT NEWC ENDC"))?, int 100];
+        let (exit_code, vm) = run_runvm(0x8, stack)?;
+        assert_eq!(exit_code, 0);
+        assert_eq!(vm.stack.depth(), 3);
+        assert_eq!(vm.stack.items[1].as_int(), Some(&BigInt::from(-14)));
+
+        // Hard gas limit (ACCEPT + NEWC + ENDC + cell build + implicit RET)
+        let stack = tuple![
+            int 0,
+            slice child(tvmasm!("ACCEPT NEWC ENDC"))?,
+            int 100,
+            int 1000,
+        ];
+        let (exit_code, vm) = run_runvm(0x48, stack)?;
+        assert_eq!(exit_code, 0);
+        let expected = tuple![cell Cell::empty_cell(), int 0, int 567];
+        crate::tests::compare_stack(&vm.stack.items, &expected);
+
+        // Unknown flags
+        let stack = tuple![int 0, slice child(tvmasm!("INT 1"))?];
+        let (exit_code, _) = run_runvm(0x200, stack)?;
+        assert_eq!(exit_code, 5);
+
+        Ok(())
+    }
+
+    #[test]
+    #[traced_test]
+    fn runvm_step_limit() -> anyhow::Result<()> {
+        let code = {
+            let mut b = CellBuilder::new();
+            b.store_u8(0xdb)?;
+            b.store_u16(0x4000)?;
+            b.build()?
+        };
+        let stack = tuple![int 0, slice make_code(tvmasm!("PUSHCONT { } AGAIN"))];
+        let mut vm = VmState::builder()
+            .with_code(code)
+            .with_stack(stack)
+            .with_gas(GasParams::unlimited())
+            .with_step_limit(1000)
+            .build();
+
+        // Child steps are counted against the parent limit
+        assert_eq!(vm.run(), VmException::Fatal as i32);
+        assert_eq!(vm.steps, 1000);
+
+        Ok(())
+    }
+
+    #[test]
+    #[traced_test]
+    fn runvm_gas_limit_after_stack_gas() -> anyhow::Result<()> {
+        let code = {
+            let mut b = CellBuilder::new();
+            b.store_u8(0xdb)?;
+            b.store_u16(0x4008)?;
+            b.build()?
+        };
+
+        // 50 extra child stack values (50 gas), PUSHINT + implicit RET (23 gas)
+        let stack = crate::tests::int_stack(82).chain(tuple![
+            int 82,
+            slice make_code(tvmasm!("INT 1")),
+            int 1000,
+        ]);
+
+        // RUNVM (34 + 40 gas) leaves 60 gas before the child stack is paid,
+        // so the child limit is 10 instead of 60.
+        let mut vm = VmState::builder()
+            .with_code(code)
+            .with_stack(stack)
+            .with_gas(GasParams {
+                max: 134,
+                limit: 134,
+                ..GasParams::getter()
+            })
+            .build();
+        assert_eq!(!vm.run(), 0);
+
+        let stack = &vm.stack.items;
+        assert_eq!(stack.len(), 3);
+        assert_eq!(stack[1].as_int(), Some(&BigInt::from(-14)));
+        assert_eq!(stack[2].as_int(), Some(&BigInt::from(0)));
+        Ok(())
+    }
+
+    fn run_runvm(flags: u16, stack: Tuple) -> anyhow::Result<(i32, VmState<'static>)> {
+        let code = {
+            let mut b = CellBuilder::new();
+            b.store_u8(0xdb)?;
+            b.store_u16(0x4000 | flags)?;
+            b.build()?
+        };
+        Ok(run_code(code, stack))
+    }
 }
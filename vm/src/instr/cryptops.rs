@@ -1,5 +1,6 @@
 use std::ops::Range;
 
+use anyhow::Result;
 use everscale_crypto::ed25519;
 use everscale_types::cell::{CellBuilder, CellSlice};
 use everscale_types::error::Error;
@@ -7,6 +8,7 @@ use num_bigint::{BigInt, Sign};
 use sha2::Digest;
 use tycho_vm_proc::vm_module;
 
+use crate::dispatch::Opcodes;
 use crate::error::VmResult;
 use crate::gas::GasConsumer;
 use crate::saferc::SafeRc;
@@ -52,7 +54,7 @@ impl CryptOps {
         Ok(0)
     }
 
-    #[op(code = "f90$01pr#ii", fmt = DisplayHashArgsExt { p, r, i })]
+    #[op(code = "f90$01pr#ii", name = "HASHEXT", fmt = DisplayHashArgsExt { p, r, i })]
     fn exec_hash_ext(st: &mut VmState, p: bool, r: bool, mut i: u32) -> VmResult<i32> {
         ok!(st.version.require_ton(4..));
 
@@ -177,6 +179,78 @@ impl CryptOps {
         ok!(stack.push_bool(is_valid || st.modifiers.chksig_always_succeed));
         Ok(0)
     }
+
+    #[init]
+    fn init_ecrecover(&self, t: &mut Opcodes) -> Result<()> {
+        #[cfg(feature = "secp256k1")]
+        t.add_simple("ECRECOVER", 0xf912, 16, exec_ecrecover, || {
+            "ECRECOVER".to_owned()
+        })?;
+        #[cfg(not(feature = "secp256k1"))]
+        let _ = t;
+        Ok(())
+    }
+
+    #[cfg(feature = "secp256k1")]
+    fn exec_ecrecover(st: &mut VmState) -> VmResult<i32> {
+        use secp256k1::ecdsa::{RecoverableSignature, RecoveryId};
+
+        ok!(st.version.require_ton(4..));
+
+        let stack = SafeRc::make_mut(&mut st.stack);
+        let s = ok!(stack.pop_int());
+        let r = ok!(stack.pop_int());
+        let v = ok!(stack.pop_smallint_range(0, 255));
+        let hash = ok!(stack.pop_int());
+
+        let hash = ok!(int_to_u256_bytes(&hash));
+        let mut signature = [0; 64];
+        signature[..32].copy_from_slice(&ok!(int_to_u256_bytes(&r)));
+        signature[32..].copy_from_slice(&ok!(int_to_u256_bytes(&s)));
+
+        st.gas.try_consume(GasConsumer::ECRECOVER_GAS_PRICE)?;
+
+        if v > 3 {
+            ok!(stack.push_bool(false));
+            return Ok(0);
+        }
+
+        let public_key = RecoveryId::from_i32(v as i32)
+            .and_then(|id| RecoverableSignature::from_compact(&signature, id))
+            .and_then(|signature| {
+                let message = secp256k1::Message::from_digest(hash);
+                secp256k1::SECP256K1.recover_ecdsa(&message, &signature)
+            });
+
+        let Ok(public_key) = public_key else {
+            ok!(stack.push_bool(false));
+            return Ok(0);
+        };
+
+        let public_key = public_key.serialize_uncompressed();
+        ok!(stack.push_int(public_key[0]));
+        ok!(stack.push_int(BigInt::from_bytes_be(Sign::Plus, &public_key[1..33])));
+        ok!(stack.push_int(BigInt::from_bytes_be(Sign::Plus, &public_key[33..65])));
+        ok!(stack.push_bool(true));
+        Ok(0)
+    }
+}
+
+#[cfg(feature = "secp256k1")]
+fn int_to_u256_bytes(int: &BigInt) -> VmResult<[u8; 32]> {
+    vm_ensure!(
+        int.sign() != Sign::Minus && int.bits() <= 256,
+        IntegerOutOfRange {
+            min: 0,
+            max: isize::MAX,
+            actual: int.to_string(),
+        }
+    );
+
+    let mut result = [0; 32];
+    let bytes = int.magnitude().to_bytes_be();
+    result[32 - bytes.len()..].copy_from_slice(&bytes);
+    Ok(result)
 }
 
 struct ToSign<'a> {
@@ -253,6 +327,8 @@ define_compute_hash_ext! {
     0 => compute_hash_ext_sha256(33, sha2::Sha256) -> [u8; 32],
     1 => compute_hash_ext_sha512(16, sha2::Sha512) -> [u8; 64],
     2 => compute_hash_ext_blake2b512(19, blake2::Blake2b512) -> [u8; 64],
+    3 => compute_hash_ext_keccak256(11, sha3::Keccak256) -> [u8; 32],
+    4 => compute_hash_ext_keccak512(6, sha3::Keccak512) -> [u8; 64],
 }
 
 struct HashInputReader<'a, 'l> {
@@ -408,7 +484,32 @@ mod tests {
 
     use crate::saferc::SafeRc;
     use crate::stack::RcStackValue;
+    use crate::tests::run_vm_with_stack;
     use crate::util::OwnedCellSlice;
+    use crate::NoLibraries;
+
+    #[test]
+    #[traced_test]
+    fn hash_empty_cell() -> anyhow::Result<()> {
+        let empty_cell_hash = "96a296d224f285c67bee93c30f8a309157f0daa35dc5b87e410b78630a09cfc7"
+            .parse::<HashBytes>()?;
+        let empty_sha256 = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+            .parse::<HashBytes>()?;
+
+        assert_run_vm!("NEWC ENDC HASHCU", [] => [raw build_int(empty_cell_hash.0)]);
+        assert_run_vm!("NEWC ENDC CTOS HASHSU", [] => [raw build_int(empty_cell_hash.0)]);
+        assert_run_vm!("NEWC ENDC CTOS SHA256U", [] => [raw build_int(empty_sha256.0)]);
+
+        // Slice hash is the hash of the cell built from it
+        assert_run_vm!(
+            "PUSHSLICE x{abcd_} DUP HASHSU SWAP NEWC STSLICE ENDC HASHCU EQUAL",
+            [] => [int -1]
+        );
+
+        assert_run_vm!("HASHCU", [int 0] => [int 0], exit_code: 7);
+        assert_run_vm!("PUSHSLICE b{101} SHA256U", [] => [int 0], exit_code: 9);
+        Ok(())
+    }
 
     #[test]
     #[traced_test]
@@ -508,6 +609,41 @@ mod tests {
         );
     }
 
+    #[test]
+    #[traced_test]
+    fn hashext_keccak() {
+        let data = b"look ma, raw bytes";
+        let keccak256_hash = build_int(sha3::Keccak256::digest(data));
+        let keccak512_hash = sha3::Keccak512::digest(data);
+
+        assert_run_vm!(
+            r#"INT 1 HASHEXT_KECCAK256"#,
+            [raw build_slice(data)] => [raw keccak256_hash.clone()]
+        );
+        assert_run_vm!(
+            r#"NEWC STSLICE INT 1 HASHEXT_KECCAK256"#,
+            [raw build_slice(data)] => [raw keccak256_hash]
+        );
+
+        let (keccak512_low, keccak512_high) = {
+            let (low, high) = keccak512_hash.split_at(32);
+            (build_int(low), build_int(high))
+        };
+        assert_run_vm!(
+            r#"INT 1 HASHEXT_KECCAK512 UNPAIR"#,
+            [raw build_slice(data)] => [raw keccak512_low, raw keccak512_high]
+        );
+
+        // Empty input
+        let empty_keccak256 = "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+            .parse::<HashBytes>()
+            .unwrap();
+        assert_run_vm!(
+            r#"INT 0 HASHEXT_KECCAK256"#,
+            [] => [raw build_int(empty_keccak256.0)]
+        );
+    }
+
     #[test]
     #[traced_test]
     fn chksign() -> anyhow::Result<()> {
@@ -592,6 +728,115 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    #[traced_test]
+    fn chksign_tampered() -> anyhow::Result<()> {
+        let secret = "403cbda795d10f129d81ac9963840f6100f8025e9341d486b247602e4b11f404"
+            .parse::<HashBytes>()?;
+        let keypair = ed25519::KeyPair::from(&ed25519::SecretKey::from_bytes(secret.0));
+
+        let data = [0xda_u8; 40];
+        let signature = keypair.sign_raw(&data);
+
+        let mut tampered = signature;
+        tampered[10] ^= 0x01;
+
+        let run = |signature: [u8; 64]| {
+            let mut output = String::new();
+            let (exit_code, vm) = run_vm_with_stack(
+                tvmasm!("CHKSIGNS"),
+                tuple![],
+                tuple![
+                    raw build_slice(data),
+                    raw build_slice(signature),
+                    raw build_int(keypair.public_key.as_bytes()),
+                ],
+                1000000,
+                &NoLibraries,
+                &mut output,
+            );
+            assert_eq!(exit_code, 0);
+            let is_valid =
+                vm.stack.items.last().unwrap().try_as_int().unwrap().sign() != Sign::NoSign;
+            (is_valid, vm.gas.consumed())
+        };
+
+        let (valid, valid_gas) = run(signature);
+        assert!(valid);
+
+        // Tampered and malformed signatures are rejected without an exception
+        let (valid, tampered_gas) = run(tampered);
+        assert!(!valid);
+        let (valid, malformed_gas) = run([0xff; 64]);
+        assert!(!valid);
+
+        // Verification cost does not depend on the signature
+        assert_eq!(valid_gas, tampered_gas);
+        assert_eq!(valid_gas, malformed_gas);
+
+        // Signatures must be at least 512 bits long
+        assert_run_vm!(
+            "CHKSIGNS",
+            [
+                raw build_slice(data),
+                raw build_slice(&signature[..63]),
+                raw build_int(keypair.public_key.as_bytes()),
+            ] => [int 0],
+            exit_code: 9
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    #[traced_test]
+    #[cfg(feature = "secp256k1")]
+    fn ecrecover() -> anyhow::Result<()> {
+        let secret = secp256k1::SecretKey::from_slice(&[0x11; 32])?;
+        let public_key = secret
+            .public_key(secp256k1::SECP256K1)
+            .serialize_uncompressed();
+
+        let hash = sha2::Sha256::digest(b"hello");
+        let message = secp256k1::Message::from_digest(hash.into());
+        let (recovery_id, signature) = secp256k1::SECP256K1
+            .sign_ecdsa_recoverable(&message, &secret)
+            .serialize_compact();
+        let v = recovery_id.to_i32();
+
+        let (r, s) = signature.split_at(32);
+        assert_run_vm!(
+            "ECRECOVER",
+            [raw build_int(hash), int v, raw build_int(r), raw build_int(s)] => [
+                int 4,
+                raw build_int(&public_key[1..33]),
+                raw build_int(&public_key[33..65]),
+                int -1,
+            ]
+        );
+
+        // Invalid recovery id (still charged)
+        assert_run_vm!(
+            "ECRECOVER GASCONSUMED",
+            [raw build_int(hash), int 4, raw build_int(r), raw build_int(s)] => [int 0, int 1552]
+        );
+
+        // Invalid signature
+        assert_run_vm!(
+            "ECRECOVER",
+            [raw build_int(hash), int v, int 0, raw build_int(s)] => [int 0]
+        );
+
+        // Hash must fit into 256 bits
+        assert_run_vm!(
+            "ECRECOVER",
+            [int -1, int v, raw build_int(r), raw build_int(s)] => [int 0],
+            exit_code: 5
+        );
+
+        Ok(())
+    }
+
     fn build_slice<T: AsRef<[u8]>>(data: T) -> RcStackValue {
         let data = data.as_ref();
         let b = CellBuilder::from_raw_data(data, data.len() as u16 * 8).unwrap();
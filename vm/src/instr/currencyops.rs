@@ -80,7 +80,7 @@ impl CurrencyOps {
                 ok!(stack.push_raw(csr));
                 ok!(stack.push_bool(false));
             }
-            Err(e) => vm_bail!(CellError(e)),
+            Err(_) => vm_bail!(CellError(Error::CellUnderflow)),
         }
         Ok(0)
     }
@@ -100,7 +100,7 @@ impl CurrencyOps {
                 }
             }
             Err(_) if quiet => ok!(stack.push_bool(false)),
-            Err(e) => vm_bail!(CellError(e)),
+            Err(_) => vm_bail!(CellError(Error::CellUnderflow)),
         }
         Ok(0)
     }
@@ -110,12 +110,13 @@ impl CurrencyOps {
     #[op(code = "fa46", fmt = "REWRITEVARADDR", args(var = true, q = false))]
     #[op(code = "fa47", fmt = "REWRITEVARADDR", args(var = true, q = true))]
     fn exec_rewrite_message_addr(st: &mut VmState, var: bool, q: bool) -> VmResult<i32> {
-        let handle_error = |stack: &mut Stack, e: Error| {
+        // NOTE: All parsing errors are reported as cell underflow.
+        let handle_error = |stack: &mut Stack, _: Error| {
             if q {
                 ok!(stack.push_bool(false));
                 Ok(0)
             } else {
-                vm_bail!(CellError(e));
+                vm_bail!(CellError(Error::CellUnderflow));
             }
         };
 
@@ -212,8 +213,18 @@ fn rewrite_addr_std(addr: OwnedCellSlice, pfx: Option<OwnedCellSlice>) -> Result
     Ok(BigInt::from_bytes_be(Sign::Plus, &addr))
 }
 
+/// Parses the whole slice as a `MsgAddress`, fails if some data remains.
 fn parse_message_addr(cell: &Cell, range: &mut CellSliceRange) -> Result<AddrParts, Error> {
     let mut cs = range.apply(cell)?;
+    let parts = ok!(load_message_addr(cell, &mut cs));
+    if !cs.is_data_empty() || !cs.is_refs_empty() {
+        return Err(Error::CellUnderflow);
+    }
+    *range = cs.range();
+    Ok(parts)
+}
+
+fn load_message_addr(cell: &Cell, cs: &mut CellSlice<'_>) -> Result<AddrParts, Error> {
     match cs.load_small_uint(2)? {
         // addr_none$00 = MsgAddressExt;
         0b00 => Ok(AddrParts::None),
@@ -231,7 +242,7 @@ fn parse_message_addr(cell: &Cell, range: &mut CellSliceRange) -> Result<AddrPar
         // addr_std$10
         0b10 => {
             // anycast:(Maybe Anycast)
-            let pfx = parse_maybe_anycast(cell, &mut cs)?;
+            let pfx = parse_maybe_anycast(cell, cs)?;
             // workchain_id:int8
             let workchain = cs.load_u8()? as i8;
             // address:bits256 = MsgAddressInt;
@@ -246,7 +257,7 @@ fn parse_message_addr(cell: &Cell, range: &mut CellSliceRange) -> Result<AddrPar
         // addr_var$11
         0b11 => {
             // anycast:(Maybe Anycast)
-            let pfx = parse_maybe_anycast(cell, &mut cs)?;
+            let pfx = parse_maybe_anycast(cell, cs)?;
             // addr_len:(## 9)
             let len = cs.load_uint(9)? as u16;
             // workchain_id:int32
@@ -444,6 +455,102 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    #[traced_test]
+    fn varint_roundtrip_test() -> anyhow::Result<()> {
+        let tokens = BigInt::from(1_000_000_000_000u64);
+        let max_grams = (BigInt::from(1) << 120) - 1;
+
+        assert_run_vm!("NEWC SWAP STGRAMS ENDC CTOS LDGRAMS ENDS", [int 0] => [int 0]);
+        assert_run_vm!("NEWC SWAP STGRAMS ENDC CTOS LDGRAMS ENDS", [int tokens.clone()] => [int tokens.clone()]);
+        assert_run_vm!("NEWC SWAP STGRAMS ENDC CTOS LDGRAMS ENDS", [int max_grams.clone()] => [int max_grams]);
+        assert_run_vm!("NEWC SWAP STVARINT16 ENDC CTOS LDVARINT16 ENDS", [int -5] => [int -5]);
+        assert_run_vm!("NEWC SWAP STVARUINT32 ENDC CTOS LDVARUINT32 ENDS", [int tokens.clone()] => [int tokens]);
+        assert_run_vm!("NEWC SWAP STVARINT32 ENDC CTOS LDVARINT32 ENDS", [int -1] => [int -1]);
+
+        // Loaded value is followed by the remaining slice
+        assert_run_vm!("NEWC INT 5 STGRAMS INT 7 STUR 8 ENDC CTOS LDGRAMS LDU 8 ENDS", [] => [int 5, int 7]);
+
+        // Length prefix is truncated
+        let mut builder = CellBuilder::new();
+        builder.store_small_uint(1, 3)?;
+        let slice = OwnedCellSlice::new_allow_exotic(builder.build()?);
+        assert_run_vm!("LDGRAMS", [slice slice] => [int 0], exit_code: 9);
+
+        // Length prefix points past the end of the slice
+        let mut builder = CellBuilder::new();
+        builder.store_small_uint(2, 4)?;
+        builder.store_u8(0xff)?;
+        let slice = OwnedCellSlice::new_allow_exotic(builder.build()?);
+        assert_run_vm!("LDGRAMS", [slice slice.clone()] => [int 0], exit_code: 9);
+        assert_run_vm!("LDVARUINT32", [slice slice] => [int 0], exit_code: 9);
+
+        Ok(())
+    }
+
+    #[test]
+    #[traced_test]
+    fn load_message_address() -> anyhow::Result<()> {
+        fn split_at(cs: &OwnedCellSlice, bits: u16) -> (OwnedCellSlice, OwnedCellSlice) {
+            let (mut prefix, mut rest) = (cs.clone(), cs.clone());
+            prefix.range_mut().only_first(bits, 0).unwrap();
+            rest.range_mut().skip_first(bits, 0).unwrap();
+            (prefix, rest)
+        }
+
+        // addr_std
+        let addr = "0:6301b2c75596e6e569a6d13ae4ec70c94f177ece0be19f968ddce73d44e7afc7"
+            .parse::<StdAddr>()?;
+        let mut b = CellBuilder::new();
+        addr.store_into(&mut b, Cell::empty_context())?;
+        b.store_u8(0xab)?;
+        let cs = OwnedCellSlice::new_allow_exotic(b.build()?);
+        let (addr_cs, rest) = split_at(&cs, 267);
+
+        assert_run_vm!("LDMSGADDR", [slice cs.clone()] => [slice addr_cs.clone(), slice rest.clone()]);
+        assert_run_vm!("LDMSGADDRQ", [slice cs.clone()] => [slice addr_cs, slice rest, int -1]);
+
+        // Trailing data is not allowed when parsing
+        assert_run_vm!("PARSEMSGADDR", [slice cs.clone()] => [int 0], exit_code: 9);
+        assert_run_vm!("PARSEMSGADDRQ", [slice cs] => [int 0]);
+
+        // addr_none
+        let mut b = CellBuilder::new();
+        b.store_zeros(2)?;
+        b.store_u8(0xab)?;
+        let cs = OwnedCellSlice::new_allow_exotic(b.build()?);
+        let (addr_cs, rest) = split_at(&cs, 2);
+
+        assert_run_vm!("LDMSGADDR", [slice cs.clone()] => [slice addr_cs.clone(), slice rest.clone()]);
+        assert_run_vm!("LDMSGADDR DROP PARSEMSGADDR", [slice cs] => [[int 0]]);
+
+        // addr_std with an invalid anycast depth
+        let mut b = CellBuilder::new();
+        b.store_small_uint(0b10, 2)?;
+        b.store_bit_one()?;
+        b.store_small_uint(0, 5)?;
+        b.store_u8(0)?;
+        b.store_u256(&HashBytes::ZERO)?;
+        let cs = OwnedCellSlice::new_allow_exotic(b.build()?);
+
+        assert_run_vm!("LDMSGADDR", [slice cs.clone()] => [int 0], exit_code: 9);
+        assert_run_vm!("LDMSGADDRQ", [slice cs.clone()] => [slice cs.clone(), int 0]);
+        assert_run_vm!("PARSEMSGADDR", [slice cs.clone()] => [int 0], exit_code: 9);
+        assert_run_vm!("REWRITESTDADDR", [slice cs] => [int 0], exit_code: 9);
+
+        // Truncated addr_std
+        let mut b = CellBuilder::new();
+        b.store_small_uint(0b100, 3)?;
+        b.store_u8(0)?;
+        b.store_u64(123)?;
+        let cs = OwnedCellSlice::new_allow_exotic(b.build()?);
+
+        assert_run_vm!("LDMSGADDR", [slice cs.clone()] => [int 0], exit_code: 9);
+        assert_run_vm!("LDMSGADDRQ", [slice cs.clone()] => [slice cs, int 0]);
+
+        Ok(())
+    }
+
     #[test]
     #[traced_test]
     fn parse_message_address() -> anyhow::Result<()> {
@@ -1,9 +1,11 @@
 use anyhow::Result;
+use everscale_types::cell::CellSlice;
 use tycho_vm_proc::vm_module;
 
 use crate::dispatch::Opcodes;
 use crate::error::VmResult;
 use crate::state::VmState;
+use crate::util::load_ext_operand;
 
 pub struct DebugOps;
 
@@ -32,7 +34,24 @@ impl DebugOps {
         Ok(0)
     }
 
-    #[op(code = "fexx @ fe01..fe14", fmt = "DEBUG {x}", args(x = args & 0xff))]
+    #[op(code = "fexx @ fe01..fe10", fmt = "DEBUG {x}", args(x = args & 0xff))]
+    fn exec_dump_stack_top(st: &mut VmState, x: u32) -> VmResult<i32> {
+        let Some(debug) = &mut st.debug else {
+            return Ok(0);
+        };
+
+        let depth = st.stack.depth();
+        let n = std::cmp::min(x as usize, depth);
+        write!(&mut *debug, "#DEBUG#: stack({depth} values) top {n} :").unwrap();
+        for value in st.stack.items.iter().rev().take(n) {
+            write!(&mut *debug, " {}", value.display_list()).unwrap();
+        }
+
+        writeln!(&mut *debug).unwrap();
+        Ok(0)
+    }
+
+    #[op(code = "fexx @ fe10..fe14", fmt = "DEBUG {x}", args(x = args & 0xff))]
     #[op(code = "fexx @ fe15..fe20", fmt = "DEBUG {x}", args(x = args & 0xff))]
     #[op(code = "fexx @ fe30..fef0", fmt = "DEBUG {x}", args(x = args & 0xff))]
     fn exec_dummy_debug(_: &mut VmState, x: u32) -> VmResult<i32> {
@@ -82,19 +101,158 @@ impl DebugOps {
     }
 
     #[init]
-    fn init_dummy_debug_str_ext(&self, t: &mut Opcodes) -> Result<()> {
-        t.add_ext(0xfef, 12, 4, exec_dummy_debug_str)
+    fn init_debug_str_ext(&self, t: &mut Opcodes) -> Result<()> {
+        t.add_ext("DEBUGSTR", 0xfef, 12, 4, exec_debug_str, dump_debug_str)
     }
 
-    fn exec_dummy_debug_str(st: &mut VmState, args: u32, bits: u16) -> VmResult<i32> {
+    fn exec_debug_str(st: &mut VmState, args: u32, bits: u16) -> VmResult<i32> {
         let data_bits = ((args & 0xf) + 1) as u16 * 8;
         vm_ensure!(
             st.code.range().has_remaining(bits + data_bits, 0),
             InvalidOpcode
         );
+
+        let mut data_range = st.code.range();
+        let ok = data_range.skip_first(bits, 0).is_ok();
+        debug_assert!(ok);
         let ok = st.code.range_mut().skip_first(bits + data_bits, 0).is_ok();
         debug_assert!(ok);
 
+        // NOTE: The string is always skipped (and paid for) even without a debug output.
+        let Some(debug) = &mut st.debug else {
+            return Ok(0);
+        };
+
+        let mut data = data_range.apply(st.code.cell())?;
+        let mut buffer = [0u8; 16];
+        let bytes = data.load_raw(&mut buffer, data_bits)?;
+
+        writeln!(&mut *debug, "#DEBUG#: {}", String::from_utf8_lossy(bytes)).unwrap();
         Ok(0)
     }
+
+    fn dump_debug_str(code: &mut CellSlice<'_>, args: u32, bits: u16) -> Option<String> {
+        let data_bits = ((args & 0xf) + 1) as u16 * 8;
+        let mut data = load_ext_operand(code, bits, data_bits, 0)?;
+        let mut buffer = [0u8; 16];
+        let bytes = data.load_raw(&mut buffer, data_bits).ok()?;
+        Some(format!("DEBUGSTR {:?}", String::from_utf8_lossy(bytes)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use everscale_types::prelude::*;
+    use tracing_test::traced_test;
+
+    use crate::gas::GasParams;
+    use crate::state::VmState;
+    use crate::tests::run_vm_with_stack;
+    use crate::NoLibraries;
+
+    #[test]
+    #[traced_test]
+    fn debug_output_works() {
+        let mut output = String::new();
+        let (exit_code, vm) = run_vm_with_stack(
+            tvmasm!(
+                "DEBUGSTR x{48454c50313233}",
+                "DEBUG 2",
+                "DEBUG 5",
+                "DUMPSTK",
+                "DEBUGSTR x{6f6b}"
+            ),
+            tuple![],
+            tuple![int 1, int 2, int 3],
+            1000000,
+            &NoLibraries,
+            &mut output,
+        );
+        assert_eq!(exit_code, 0);
+        assert_eq!(vm.stack.depth(), 3);
+        drop(vm);
+
+        assert_eq!(
+            output,
+            "#DEBUG#: HELP123\n\
+            #DEBUG#: stack(3 values) top 2 : 3 2\n\
+            #DEBUG#: stack(3 values) top 3 : 3 2 1\n\
+            #DEBUG#: stack(3 values) : 3 2 1\n\
+            #DEBUG#: ok\n"
+        );
+    }
+
+    #[test]
+    #[traced_test]
+    fn debug_str_without_output() {
+        assert_run_vm!("DEBUGSTR x{48454c50313233} INT 1", [] => [int 1]);
+    }
+
+    #[test]
+    #[traced_test]
+    fn dump_value_works() -> anyhow::Result<()> {
+        // DUMP s0; DUMP s2; DUMP s5
+        let code = {
+            let mut b = CellBuilder::new();
+            b.store_u16(0xfe20)?;
+            b.store_u16(0xfe22)?;
+            b.store_u16(0xfe25)?;
+            b.build()?
+        };
+
+        let mut output = String::new();
+        let (exit_code, vm) = run_vm_with_stack(
+            &Boc::encode(code),
+            tuple![],
+            tuple![int 1, int 2, int 3],
+            1000000,
+            &NoLibraries,
+            &mut output,
+        );
+        assert_eq!(exit_code, 0);
+        assert_eq!(vm.stack.depth(), 3);
+        drop(vm);
+
+        assert_eq!(
+            output,
+            "#DEBUG#: s0 = 3\n\
+            #DEBUG#: s2 = 1\n\
+            #DEBUG#: s5 is absent\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    #[traced_test]
+    fn debug_ops_without_output() -> anyhow::Result<()> {
+        let code = Boc::decode(tvmasm!(
+            "INT 1 INT 2",
+            "DUMPSTK",
+            "DEBUG 1",
+            "DEBUGSTR x{6f6b}",
+            "ADD"
+        ))?;
+
+        let mut output = String::new();
+        let mut vm = VmState::builder()
+            .with_code(code.clone())
+            .with_gas(GasParams::getter())
+            .with_debug(&mut output)
+            .build();
+        assert_eq!(vm.run(), -1);
+        let gas_with_output = vm.gas.consumed();
+        drop(vm);
+        assert!(!output.is_empty());
+
+        // Debug ops are no-ops without an output, but still consume gas
+        let mut vm = VmState::builder()
+            .with_code(code)
+            .with_gas(GasParams::getter())
+            .build();
+        assert_eq!(vm.run(), -1);
+        assert_eq!(vm.gas.consumed(), gas_with_output);
+        crate::tests::compare_stack(&vm.stack.items, &tuple![int 3]);
+
+        Ok(())
+    }
 }
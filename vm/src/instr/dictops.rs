@@ -2,7 +2,8 @@ use everscale_types::cell::{CellBuilder, CellSlice, CellSliceParts, Load, Size};
 use everscale_types::dict::{self, DictBound, SetMode};
 use everscale_types::error::Error;
 use everscale_types::prelude::{Cell, CellFamily, Store};
-use num_bigint::Sign;
+use num_bigint::{BigInt, Sign};
+use num_traits::{One, Zero};
 use tycho_vm_proc::vm_module;
 
 use crate::cont::OrdCont;
@@ -12,8 +13,7 @@ use crate::saferc::SafeRc;
 use crate::stack::RcStackValue;
 use crate::state::VmState;
 use crate::util::{
-    bitsize, in_bitsize_range, load_int_from_slice, store_int_to_builder,
-    store_int_to_builder_unchecked, OwnedCellSlice,
+    bitsize, in_bitsize_range, load_int_from_slice, store_int_to_builder_unchecked, OwnedCellSlice,
 };
 
 pub struct DictOps;
@@ -22,7 +22,14 @@ pub struct DictOps;
 impl DictOps {
     #[init]
     fn init_dict_const(&self, t: &mut Opcodes) -> anyhow::Result<()> {
-        t.add_ext_range(0xf4a400, 0xf4a800, 24, exec_push_const_dict)?;
+        t.add_ext_range(
+            "DICTPUSHCONST",
+            0xf4a400,
+            0xf4a800,
+            24,
+            exec_push_const_dict,
+            dump_push_const_dict,
+        )?;
         Ok(())
     }
 
@@ -108,7 +115,12 @@ impl DictOps {
         Ok(0)
     }
 
-    #[op(code = "f4ss @ f40a..f410", fmt = s.display("GET"), args(s = DictOpArgs(args)))]
+    #[op(
+        code = "f4ss @ f40a..f410",
+        name = "DICTGET",
+        fmt = s.display("GET"),
+        args(s = DictOpArgs(args))
+    )]
     fn exec_dict_get(st: &mut VmState, s: DictOpArgs) -> VmResult<i32> {
         let stack = SafeRc::make_mut(&mut st.stack);
         let n = ok!(stack.pop_smallint_range(0, 1023)) as u16;
@@ -153,31 +165,37 @@ impl DictOps {
 
     #[op(
         code = "f4ss @ f412..f418",
+        name = "DICTSET",
         fmt = s.display("SET"),
         args(s = DictOpArgs(args), b = false, mode = SetMode::Set)
     )]
     #[op(
         code = "f4ss @ f422..f428",
+        name = "DICTREPLACE",
         fmt = s.display("REPLACE"),
         args(s = DictOpArgs(args), b = false, mode = SetMode::Replace)
     )]
     #[op(
         code = "f4ss @ f432..f438",
+        name = "DICTADD",
         fmt = s.display("ADD"),
         args(s = DictOpArgs(args), b = false, mode = SetMode::Add)
     )]
     #[op(
         code = "f4ss @ f441..f444",
+        name = "DICTSETB",
         fmt = s.display_b("SET"),
         args(s = DictOpArgs(args << 1), b = true, mode = SetMode::Set)
     )]
     #[op(
         code = "f4ss @ f449..f44c",
+        name = "DICTREPLACEB",
         fmt = s.display_b("REPLACE"),
         args(s = DictOpArgs(args << 1), b = true, mode = SetMode::Replace)
     )]
     #[op(
         code = "f4ss @ f451..f454",
+        name = "DICTADDB",
         fmt = s.display_b("ADD"),
         args(s = DictOpArgs(args << 1), b = true, mode = SetMode::Add)
     )]
@@ -191,7 +209,7 @@ impl DictOps {
         let mut key = if s.is_int() {
             let int = ok!(stack.pop_int());
             cb = CellBuilder::new();
-            store_int_to_builder(&int, n, s.is_signed(), &mut cb)?;
+            ok!(store_int_key(&int, n, s.is_signed(), &mut cb));
             cb.as_data_slice()
         } else {
             cs = stack.pop_cs()?;
@@ -225,31 +243,37 @@ impl DictOps {
 
     #[op(
         code = "f4ss @ f41a..f420",
+        name = "DICTSETGET",
         fmt = s.display("SETGET"),
         args(s = DictOpArgs(args), b = false, mode = SetMode::Set)
     )]
     #[op(
         code = "f4ss @ f42a..f430",
+        name = "DICTREPLACEGET",
         fmt = s.display("REPLACEGET"),
         args(s = DictOpArgs(args), b = false, mode = SetMode::Replace)
     )]
     #[op(
         code = "f4ss @ f43a..f440",
+        name = "DICTADDGET",
         fmt = s.display("ADDGET"),
         args(s = DictOpArgs(args), b = false, mode = SetMode::Add)
     )]
     #[op(
         code = "f4ss @ f445..f448",
+        name = "DICTSETGETB",
         fmt = s.display_b("SETGET"),
         args(s = DictOpArgs(args << 1), b = true, mode = SetMode::Set)
     )]
     #[op(
         code = "f4ss @ f44d..f450",
+        name = "DICTREPLACEGETB",
         fmt = s.display_b("REPLACEGET"),
         args(s = DictOpArgs(args << 1), b = true, mode = SetMode::Replace)
     )]
     #[op(
         code = "f4ss @ f455..f458",
+        name = "DICTADDGETB",
         fmt = s.display_b("ADDGET"),
         args(s = DictOpArgs(args << 1), b = true, mode = SetMode::Add)
     )]
@@ -263,7 +287,7 @@ impl DictOps {
         let mut key = if s.is_int() {
             let int = ok!(stack.pop_int());
             cb = CellBuilder::new();
-            store_int_to_builder(&int, n, s.is_signed(), &mut cb)?;
+            ok!(store_int_key(&int, n, s.is_signed(), &mut cb));
             cb.as_data_slice()
         } else {
             cs = stack.pop_cs()?;
@@ -301,7 +325,12 @@ impl DictOps {
         Ok(0)
     }
 
-    #[op(code = "f4ss @ f459..f45c", fmt = s.display("DEL"), args(s = ShortDictOpArgs(args)))]
+    #[op(
+        code = "f4ss @ f459..f45c",
+        name = "DICTDEL",
+        fmt = s.display("DEL"),
+        args(s = ShortDictOpArgs(args))
+    )]
     fn exec_dict_delete(st: &mut VmState, s: ShortDictOpArgs) -> VmResult<i32> {
         let stack = SafeRc::make_mut(&mut st.stack);
         let n = ok!(stack.pop_smallint_range(0, 1023)) as u16;
@@ -334,7 +363,12 @@ impl DictOps {
         Ok(0)
     }
 
-    #[op(code = "f4ss @ f462..f468", fmt = s.display("DELGET"), args(s = DictOpArgs(args)))]
+    #[op(
+        code = "f4ss @ f462..f468",
+        name = "DICTDELGET",
+        fmt = s.display("DELGET"),
+        args(s = DictOpArgs(args))
+    )]
     fn exec_dict_deleteget(st: &mut VmState, s: DictOpArgs) -> VmResult<i32> {
         let stack = SafeRc::make_mut(&mut st.stack);
         let n = ok!(stack.pop_smallint_range(0, 1023)) as u16;
@@ -377,7 +411,12 @@ impl DictOps {
         Ok(0)
     }
 
-    #[op(code = "f4ss @ f469..f46c", fmt = s.display("GETOPTREF"), args(s = ShortDictOpArgs(args)))]
+    #[op(
+        code = "f4ss @ f469..f46c",
+        name = "DICTGETOPTREF",
+        fmt = s.display("GETOPTREF"),
+        args(s = ShortDictOpArgs(args))
+    )]
     fn exec_dict_get_optref(st: &mut VmState, s: ShortDictOpArgs) -> VmResult<i32> {
         let stack = SafeRc::make_mut(&mut st.stack);
         let n = ok!(stack.pop_smallint_range(0, 1023)) as u16;
@@ -408,7 +447,12 @@ impl DictOps {
         Ok(0)
     }
 
-    #[op(code = "f4ss @ f46d..f470", fmt = s.display("SETGETOPTREF"), args(s = ShortDictOpArgs(args)))]
+    #[op(
+        code = "f4ss @ f46d..f470",
+        name = "DICTSETGETOPTREF",
+        fmt = s.display("SETGETOPTREF"),
+        args(s = ShortDictOpArgs(args))
+    )]
     fn exec_dict_setget_optref(st: &mut VmState, s: ShortDictOpArgs) -> VmResult<i32> {
         let stack = SafeRc::make_mut(&mut st.stack);
         let n = ok!(stack.pop_smallint_range(0, 1023)) as u16;
@@ -419,7 +463,7 @@ impl DictOps {
         let mut key = if s.is_int() {
             let int = ok!(stack.pop_int());
             cb = CellBuilder::new();
-            store_int_to_builder(&int, n, s.is_signed(), &mut cb)?;
+            ok!(store_int_key(&int, n, s.is_signed(), &mut cb));
             cb.as_data_slice()
         } else {
             cs = stack.pop_cs()?;
@@ -444,7 +488,12 @@ impl DictOps {
         Ok(0)
     }
 
-    #[op(code = "f4ss @ f474..f480", fmt = s, args(s = DictGetNearArgs(args)))]
+    #[op(
+        code = "f4ss @ f474..f480",
+        name = "DICTGETNEAR",
+        fmt = s,
+        args(s = DictGetNearArgs(args))
+    )]
     fn exec_dict_get_near(st: &mut VmState, s: DictGetNearArgs) -> VmResult<i32> {
         let stack = SafeRc::make_mut(&mut st.stack);
 
@@ -509,10 +558,30 @@ impl DictOps {
         Ok(0)
     }
 
-    #[op(code = "f4ss @ f482..f488", fmt = s.display("MIN"), args(s = DictOpArgs(args)))]
-    #[op(code = "f4ss @ f48a..f490", fmt = s.display("MAX"), args(s = DictOpArgs(args)))]
-    #[op(code = "f4ss @ f492..f498", fmt = s.display("REMMIN"), args(s = DictOpArgs(args)))]
-    #[op(code = "f4ss @ f49a..f4a0", fmt = s.display("REMMAX"), args(s = DictOpArgs(args)))]
+    #[op(
+        code = "f4ss @ f482..f488",
+        name = "DICTMIN",
+        fmt = s.display("MIN"),
+        args(s = DictOpArgs(args))
+    )]
+    #[op(
+        code = "f4ss @ f48a..f490",
+        name = "DICTMAX",
+        fmt = s.display("MAX"),
+        args(s = DictOpArgs(args))
+    )]
+    #[op(
+        code = "f4ss @ f492..f498",
+        name = "DICTREMMIN",
+        fmt = s.display("REMMIN"),
+        args(s = DictOpArgs(args))
+    )]
+    #[op(
+        code = "f4ss @ f49a..f4a0",
+        name = "DICTREMMAX",
+        fmt = s.display("REMMAX"),
+        args(s = DictOpArgs(args))
+    )]
     fn exec_dict_get_min(st: &mut VmState, s: DictOpArgs) -> VmResult<i32> {
         let stack = SafeRc::make_mut(&mut st.stack);
 
@@ -571,8 +640,8 @@ impl DictOps {
         Ok(0)
     }
 
-    #[op(code = "f4a$00ss", fmt = s, args(s = DictExecArgs(args)))]
-    #[op(code = "f4b$11ss", fmt = s, args(s = DictExecArgs(args)))]
+    #[op(code = "f4a$00ss", name = "DICTGETEXEC", fmt = s, args(s = DictExecArgs(args)))]
+    #[op(code = "f4b$11ss", name = "DICTGETEXECZ", fmt = s, args(s = DictExecArgs(args)))]
     fn exec_dict_get_exec(st: &mut VmState, s: DictExecArgs) -> VmResult<i32> {
         let stack = SafeRc::make_mut(&mut st.stack);
         let n = ok!(stack.pop_smallint_range(0, 1023)) as u16;
@@ -624,6 +693,7 @@ impl DictOps {
         st.code.set_range(code.range());
 
         vm_log_op!(
+            st,
             "DICTPUSHCONST {n} ({})",
             OwnedCellSlice::from((st.code.cell().clone(), slice_range))
         );
@@ -632,6 +702,18 @@ impl DictOps {
         Ok(0)
     }
 
+    fn dump_push_const_dict(code: &mut CellSlice<'_>, _: u32, bits: u16) -> Option<String> {
+        if !code.has_remaining(bits, 1) {
+            return None;
+        }
+        code.skip_first(bits - 11, 0).ok()?;
+
+        let slice = code.load_prefix(1, 1).ok()?;
+        let dict = slice.get_reference(0).ok()?;
+        let n = code.load_uint(10).ok()?;
+        Some(format!("DICTPUSHCONST {n} ({})", dict.repr_hash()))
+    }
+
     // TODO: Implement a proper subdictionary cut.
     // #[op(code = "f4ss @ f4b1..f4b4", fmt = s.display("GET"), args(s = SubDictOpArgs(args)))]
     // #[op(code = "f4ss @ f4b5..f4b8", fmt = s.display("RPGET"), args(s = SubDictOpArgs(args)))]
@@ -894,6 +976,33 @@ impl std::fmt::Display for DictExecArgs {
 //     }
 // }
 
+/// Stores an integer dictionary key, throwing a range check error
+/// if it doesn't fit into `n` bits.
+fn store_int_key(int: &BigInt, n: u16, signed: bool, cb: &mut CellBuilder) -> VmResult<()> {
+    if !in_bitsize_range(int, signed) || bitsize(int, signed) > n {
+        let (min, max) = int_key_range(n, signed);
+        vm_bail!(DictKeyOutOfRange {
+            min: min.to_string(),
+            max: max.to_string(),
+            actual: int.to_string(),
+        });
+    }
+    store_int_to_builder_unchecked(int, n, signed, cb)?;
+    Ok(())
+}
+
+/// Returns the inclusive range of integers which fit into an `n`-bit key.
+fn int_key_range(n: u16, signed: bool) -> (BigInt, BigInt) {
+    match (n, signed) {
+        (0, _) => (BigInt::zero(), BigInt::zero()),
+        (n, true) => {
+            let half = BigInt::one() << (n - 1);
+            (-half.clone(), half - 1)
+        }
+        (n, false) => (BigInt::zero(), (BigInt::one() << n) - 1),
+    }
+}
+
 fn extract_value_ref(value: CellSliceParts, is_ref: bool) -> VmResult<RcStackValue> {
     let value = OwnedCellSlice::from(value);
     if is_ref {
@@ -911,12 +1020,16 @@ fn to_value_ref(mut cs: CellSlice<'_>) -> VmResult<RcStackValue> {
 
 #[cfg(test)]
 pub mod tests {
+    use everscale_types::boc::Boc;
     use everscale_types::cell::Lazy;
     use num_bigint::BigInt;
     use tracing_test::traced_test;
 
     use self::dict::{Dict, DictKey};
     use super::*;
+    use crate::tests::{compare_stack, run_vm_with_stack};
+    use crate::util::store_int_to_builder;
+    use crate::NoLibraries;
 
     #[test]
     #[traced_test]
@@ -964,6 +1077,95 @@ pub mod tests {
         assert_run_vm!("DICTIGET", [int 3, raw dict.clone(), int 32] => [int 0]);
     }
 
+    #[test]
+    #[traced_test]
+    fn int_key_dict() {
+        let dict = build_dict::<i32, u32, _>(|dict| {
+            dict.set(-1, 10)?;
+            dict.set(-128, 20)?;
+            dict.set(127, 30)?;
+            Ok(())
+        });
+
+        // Signed keys
+        for (key, value) in [(-1, 10), (-128, 20), (127, 30)] {
+            assert_run_vm!(
+                "DICTIGET DROP PLDU 32",
+                [int key, raw dict.clone(), int 32] => [int value],
+            );
+        }
+        assert_run_vm!("DICTIGET", [int -2, raw dict.clone(), int 32] => [int 0]);
+
+        // Keys which don't fit are simply absent for lookups
+        assert_run_vm!("DICTIGET", [int -129, null, int 8] => [int 0]);
+        assert_run_vm!("DICTUGET", [int -1, null, int 8] => [int 0]);
+        assert_run_vm!("DICTUGET", [int 256, null, int 8] => [int 0]);
+
+        // ... but must throw a range check error on insertion
+        assert_run_vm!(
+            "DICTISET",
+            [raw new_slice(1), int 128, null, int 8] => [int 0],
+            exit_code: 5,
+        );
+        assert_run_vm!(
+            "DICTISET",
+            [raw new_slice(1), int -129, null, int 8] => [int 0],
+            exit_code: 5,
+        );
+        assert_run_vm!(
+            "DICTUSET",
+            [raw new_slice(1), int -1, null, int 8] => [int 0],
+            exit_code: 5,
+        );
+        assert_run_vm!(
+            "DICTUSET",
+            [raw new_slice(1), int 256, null, int 8] => [int 0],
+            exit_code: 5,
+        );
+
+        // Negative keys roundtrip through the signed form
+        assert_run_vm!(
+            r#"
+                DICTISET
+                INT -128 SWAP INT 8 DICTIGET DROP PLDI 32
+            "#,
+            [raw new_slice(-7), int -128, null, int 8] => [int -7],
+        );
+        assert_run_vm!(
+            r#"
+                NEWC ENDC INT -5 ROT INT 16 DICTISETREF
+                INT -5 SWAP INT 16 DICTIGETREF DROP HASHCU
+                NEWC ENDC HASHCU EQUAL
+            "#,
+            [null] => [int -1],
+        );
+        assert_run_vm!(
+            r#"
+                DICTUSET
+                INT 255 SWAP INT 8 DICTUGET DROP PLDI 32
+            "#,
+            [raw new_slice(42), int 255, null, int 8] => [int 42],
+        );
+    }
+
+    #[test]
+    fn int_key_range_error() {
+        let check = |int: i32, n: u16, signed: bool, range: &str| {
+            let mut cb = CellBuilder::new();
+            let err = store_int_key(&BigInt::from(int), n, signed, &mut cb).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                format!("dictionary key {int} is out of range {range}")
+            );
+        };
+
+        check(128, 8, true, "-128..=127");
+        check(-129, 8, true, "-128..=127");
+        check(-1, 8, false, "0..=255");
+        check(256, 8, false, "0..=255");
+        check(1, 0, true, "0..=0");
+    }
+
     #[test]
     #[traced_test]
     fn set_dict() {
@@ -991,6 +1193,50 @@ pub mod tests {
         );
     }
 
+    #[test]
+    #[traced_test]
+    fn set_and_get_dict() {
+        let dict = build_dict::<u8, u8, _>(|dict| {
+            dict.set(0x01, 0x0a)?;
+            dict.set(0x02, 0x0b)?;
+            dict.set(0xff, 0x0c)?;
+            Ok(())
+        });
+
+        assert_run_vm!(
+            r#"
+            PUSHSLICE x{0a} PUSHSLICE x{01} ROT INT 8 DICTSET
+            PUSHSLICE x{0b} PUSHSLICE x{02} ROT INT 8 DICTSET
+            PUSHSLICE x{0c} PUSHSLICE x{ff} ROT INT 8 DICTSET
+            "#,
+            [null] => [raw dict.clone()],
+        );
+
+        assert_run_vm!(
+            r#"
+            DUP PUSHSLICE x{01} SWAP INT 8 DICTGET DROP PLDU 8 SWAP
+            DUP PUSHSLICE x{02} SWAP INT 8 DICTGET DROP PLDU 8 SWAP
+            DUP PUSHSLICE x{ff} SWAP INT 8 DICTGET DROP PLDU 8 SWAP
+            PUSHSLICE x{03} SWAP INT 8 DICTGET
+            "#,
+            [raw dict.clone()] => [int 0x0a, int 0x0b, int 0x0c, int 0],
+        );
+
+        // Empty dict
+        assert_run_vm!("PUSHSLICE x{01} SWAP INT 8 DICTGET", [null] => [int 0]);
+
+        // Ref values
+        assert_run_vm!(
+            r#"
+            NEWC INT 5 STUR 8 ENDC PUSHSLICE x{01} ROT INT 8 DICTSETREF
+            NEWC INT 6 STUR 8 ENDC PUSHSLICE x{02} ROT INT 8 DICTSETREF
+            DUP PUSHSLICE x{02} SWAP INT 8 DICTGETREF DROP CTOS PLDU 8 SWAP
+            PUSHSLICE x{01} SWAP INT 8 DICTGETREF DROP CTOS PLDU 8
+            "#,
+            [null] => [int 6, int 5],
+        );
+    }
+
     #[test]
     #[traced_test]
     fn delete_dict() {
@@ -1172,6 +1418,75 @@ pub mod tests {
         );
     }
 
+    #[test]
+    #[traced_test]
+    fn walk_dict() {
+        let dict = build_dict::<i32, i32, _>(|dict| {
+            for key in [7, -1, 0, -5] {
+                dict.set(key, key * 10)?;
+            }
+            Ok(())
+        });
+
+        // Ascending (signed)
+        assert_run_vm!("DICTIMIN ROT DROP", [raw dict.clone(), int 32] => [int -5, int -1]);
+        for (key, next) in [(-5, -1), (-1, 0), (0, 7)] {
+            assert_run_vm!(
+                "DICTIGETNEXT ROT DROP",
+                [int key, raw dict.clone(), int 32] => [int next, int -1],
+            );
+        }
+        assert_run_vm!("DICTIGETNEXT", [int 7, raw dict.clone(), int 32] => [int 0]);
+        assert_run_vm!("DICTIGETNEXTEQ ROT DROP", [int 0, raw dict.clone(), int 32] => [int 0, int -1]);
+
+        // Descending (signed)
+        assert_run_vm!("DICTIMAX ROT DROP", [raw dict.clone(), int 32] => [int 7, int -1]);
+        for (key, prev) in [(7, 0), (0, -1), (-1, -5)] {
+            assert_run_vm!(
+                "DICTIGETPREV ROT DROP",
+                [int key, raw dict.clone(), int 32] => [int prev, int -1],
+            );
+        }
+        assert_run_vm!("DICTIGETPREV", [int -5, raw dict.clone(), int 32] => [int 0]);
+        assert_run_vm!("DICTIGETPREVEQ ROT DROP", [int -1, raw dict.clone(), int 32] => [int -1, int -1]);
+
+        // Same keys interpreted as unsigned integers
+        let as_unsigned = |key: i32| key as u32;
+        assert_run_vm!("DICTUMIN ROT DROP", [raw dict.clone(), int 32] => [int 0, int -1]);
+        assert_run_vm!("DICTUMAX ROT DROP", [raw dict.clone(), int 32] => [int u32::MAX, int -1]);
+        for (key, next) in [(0, 7), (7, -5), (-5, -1)] {
+            assert_run_vm!(
+                "DICTUGETNEXT ROT DROP",
+                [int as_unsigned(key), raw dict.clone(), int 32] => [int as_unsigned(next), int -1],
+            );
+        }
+        assert_run_vm!("DICTUGETNEXT", [int u32::MAX, raw dict.clone(), int 32] => [int 0]);
+
+        // Values are returned with keys
+        assert_run_vm!(
+            "DICTIGETNEXT DROP DROP PLDI 32",
+            [int -1, raw dict.clone(), int 32] => [int 0],
+        );
+        assert_run_vm!(
+            "DICTIMIN DROP DROP PLDI 32",
+            [raw dict, int 32] => [int -50],
+        );
+
+        let dict = build_dict::<i32, Lazy<i32>, _>(|dict| {
+            dict.set(-3, Lazy::new(&30)?)?;
+            dict.set(3, Lazy::new(&-30)?)?;
+            Ok(())
+        });
+        assert_run_vm!("DICTIMINREF", [raw dict.clone(), int 32] => [raw new_cell(30), int -3, int -1]);
+        assert_run_vm!("DICTIMAXREF", [raw dict, int 32] => [raw new_cell(-30), int 3, int -1]);
+
+        // Empty dict
+        assert_run_vm!("DICTMIN", [null, int 32] => [int 0]);
+        assert_run_vm!("DICTIMAX", [null, int 32] => [int 0]);
+        assert_run_vm!("DICTIGETNEXT", [int 0, null, int 32] => [int 0]);
+        assert_run_vm!("DICTIGETPREV", [int 0, null, int 32] => [int 0]);
+    }
+
     #[test]
     #[traced_test]
     fn set_get_dict() {
@@ -1232,7 +1547,76 @@ pub mod tests {
         );
         assert_run_vm!(
             "DICTADDGET",
-            [raw new_slice(5), raw new_slice(3), raw dict.clone(), int 32] => [raw updated_dict2, int -1],
+            [raw new_slice(5), raw new_slice(3), raw dict.clone(), int 32] => [raw updated_dict2.clone(), int -1],
+        );
+
+        // SET always inserts
+        assert_run_vm!(
+            "DICTSETGET",
+            [raw new_slice(5), raw new_slice(3), raw dict.clone(), int 32] => [raw updated_dict2, int 0],
+        );
+
+        // ADD keeps an existing value
+        assert_run_vm!(
+            r#"
+            DICTADDGET
+            SWAP
+            NEWC STSLICE ENDC
+            SWAP
+            "#,
+            [
+                raw new_slice(5),
+                raw new_slice(0),
+                raw dict.clone(),
+                int 32
+            ] => [raw dict.clone(), raw new_cell(2), int 0],
+        );
+    }
+
+    #[test]
+    #[traced_test]
+    fn set_get_delete_get_ref_dict() {
+        let dict = build_dict::<i32, Lazy<i32>, _>(|dict| {
+            dict.set(0, Lazy::new(&2)?)?;
+            dict.set(1, Lazy::new(&4)?)?;
+            Ok(())
+        });
+
+        let updated_dict = build_dict::<i32, Lazy<i32>, _>(|dict| {
+            dict.set(0, Lazy::new(&5)?)?;
+            dict.set(1, Lazy::new(&4)?)?;
+            Ok(())
+        });
+
+        let removed_dict = build_dict::<i32, Lazy<i32>, _>(|dict| {
+            dict.set(1, Lazy::new(&4)?)?;
+            Ok(())
+        });
+
+        assert_run_vm!(
+            "DICTSETGETREF",
+            [raw new_cell(5), raw new_slice(0), raw dict.clone(), int 32] => [raw updated_dict.clone(), raw new_cell(2), int -1],
+        );
+        assert_run_vm!(
+            "DICTREPLACEGETREF",
+            [raw new_cell(5), raw new_slice(0), raw dict.clone(), int 32] => [raw updated_dict, raw new_cell(2), int -1],
+        );
+        assert_run_vm!(
+            "DICTREPLACEGETREF",
+            [raw new_cell(5), raw new_slice(7), raw dict.clone(), int 32] => [raw dict.clone(), int 0],
+        );
+        assert_run_vm!(
+            "DICTADDGETREF",
+            [raw new_cell(5), raw new_slice(0), raw dict.clone(), int 32] => [raw dict.clone(), raw new_cell(2), int 0],
+        );
+
+        assert_run_vm!(
+            "DICTDELGETREF",
+            [raw new_slice(0), raw dict.clone(), int 32] => [raw removed_dict, raw new_cell(2), int -1],
+        );
+        assert_run_vm!(
+            "DICTDELGETREF",
+            [raw new_slice(7), raw dict.clone(), int 32] => [raw dict, int 0],
         );
     }
 
@@ -1349,6 +1733,72 @@ pub mod tests {
         );
     }
 
+    #[test]
+    #[traced_test]
+    fn dict_const_selector() -> anyhow::Result<()> {
+        let bodies: [(u64, &[u8]); 2] = [(1, tvmasm!("INT 10")), (2, tvmasm!("INT 2 INT 3 ADD"))];
+
+        let mut methods = None;
+        for (id, body) in bodies {
+            let body = Boc::decode(body)?;
+            let mut key = CellBuilder::new();
+            key.store_uint(id, 19)?;
+            dict::dict_insert(
+                &mut methods,
+                &mut key.as_data_slice(),
+                19,
+                &body.as_slice()?,
+                SetMode::Set,
+                Cell::empty_context(),
+            )?;
+        }
+        let methods = methods.unwrap();
+
+        // DICTPUSHCONST 19 <op> INT 7
+        let selector = |op: u16| -> anyhow::Result<Vec<u8>> {
+            let mut b = CellBuilder::new();
+            b.store_uint(0xf4a400 | 19, 24)?;
+            b.store_reference(methods.clone())?;
+            b.store_u16(op)?;
+            b.store_u8(0x77)?;
+            Ok(Boc::encode(b.build()?))
+        };
+
+        let run = |code: &[u8], id: i32, expected: Vec<RcStackValue>| {
+            let mut output = String::new();
+            let (exit_code, vm) = run_vm_with_stack(
+                code,
+                tuple![],
+                tuple![int id],
+                1000000,
+                &NoLibraries,
+                &mut output,
+            );
+            assert_eq!(exit_code, 0);
+            compare_stack(&vm.stack.items, &expected);
+        };
+
+        // DICTIGETJMP
+        let code = selector(0xf4a0)?;
+        run(&code, 1, tuple![int 10]);
+        run(&code, 2, tuple![int 5]);
+        run(&code, 3, tuple![int 7]);
+
+        // DICTIGETJMPZ
+        let code = selector(0xf4bc)?;
+        run(&code, 1, tuple![int 10]);
+        run(&code, 2, tuple![int 5]);
+        run(&code, 3, tuple![int 3, int 7]);
+
+        // DICTIGETEXEC
+        let code = selector(0xf4a2)?;
+        run(&code, 1, tuple![int 10, int 7]);
+        run(&code, 2, tuple![int 5, int 7]);
+        run(&code, 3, tuple![int 7]);
+
+        Ok(())
+    }
+
     fn new_slice(value: i32) -> RcStackValue {
         let value = BigInt::from(value);
         let mut builder = CellBuilder::new();
@@ -110,9 +110,13 @@ fn exec_set_gas(st: &mut VmState, gas_limit: u64) -> VmResult<i32> {
 
 #[cfg(test)]
 mod tests {
+    use everscale_types::boc::Boc;
     use num_bigint::BigInt;
     use tracing_test::traced_test;
 
+    use crate::gas::GasParams;
+    use crate::state::VmState;
+
     #[test]
     #[traced_test]
     fn gas_price_ops() {
@@ -157,4 +161,57 @@ mod tests {
         assert_run_vm!("GASTOGRAM", [int u64::MAX] => [int (u64::MAX as u128) * 1000]);
         assert_run_vm!("GASTOGRAM", [int (BigInt::from(1) << 256) - 1] => [int 0], exit_code: 4);
     }
+
+    #[test]
+    #[traced_test]
+    fn accept_external() {
+        fn run_external(code: &[u8]) -> (i32, VmState<'static>) {
+            let mut vm = VmState::builder()
+                .with_code(Boc::decode(code).unwrap())
+                .with_gas(GasParams {
+                    max: 1_000_000,
+                    limit: 0,
+                    credit: 10_000,
+                    price: 1000 << 16,
+                })
+                .build();
+            let exit_code = !vm.run();
+            (exit_code, vm)
+        }
+
+        // Gas credit can be spent without ACCEPT, but the message is not accepted
+        let (exit_code, vm) = run_external(tvmasm!("INT 10 PUSHCONT { NOP } REPEAT"));
+        assert_eq!(exit_code, 0);
+        assert_eq!(vm.gas.credit(), 10_000);
+        assert_eq!(vm.gas.limit(), 0);
+        assert!(vm.gas.consumed() > 0);
+
+        // Gas credit alone is not enough
+        let (exit_code, vm) = run_external(tvmasm!("INT 1000 PUSHCONT { NOP } REPEAT"));
+        assert_eq!(exit_code, -14);
+        assert_eq!(vm.gas.credit(), 10_000);
+
+        // ACCEPT moves credit into the limit
+        let (exit_code, vm) = run_external(tvmasm!("ACCEPT INT 1000 PUSHCONT { NOP } REPEAT"));
+        assert_eq!(exit_code, 0);
+        assert_eq!(vm.gas.credit(), 0);
+        assert_eq!(vm.gas.limit(), 1_000_000);
+
+        // SETGASLIMIT is clamped to the max
+        let (exit_code, vm) = run_external(tvmasm!("INT 2000000 SETGASLIMIT"));
+        assert_eq!(exit_code, 0);
+        assert_eq!(vm.gas.limit(), 1_000_000);
+
+        // COMMIT saves the current state
+        let (exit_code, vm) = run_external(tvmasm!("ACCEPT COMMIT INT 1 THROW 100"));
+        assert_eq!(exit_code, 100);
+        assert!(vm.commited_state.is_some());
+    }
+
+    #[test]
+    #[traced_test]
+    fn gas_consumed() {
+        assert_run_vm!("GASCONSUMED", [] => [int 26]);
+        assert_run_vm!("GASCONSUMED GASCONSUMED SUB", [] => [int -26]);
+    }
 }
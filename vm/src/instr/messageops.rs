@@ -444,7 +444,7 @@ fn add_action(regs: &mut ControlRegs, gas: &GasConsumer, action: OutAction) -> V
 mod tests {
     use everscale_types::cell::{Cell, CellBuilder};
     use everscale_types::models::{
-        Account, AccountState, CurrencyCollection, IntAddr, OwnedMessage,
+        Account, AccountState, CurrencyCollection, IntAddr, OutAction, OwnedMessage,
     };
     use everscale_types::prelude::{Boc, Load};
     use tracing_test::traced_test;
@@ -452,7 +452,9 @@ mod tests {
     use crate::gas::GasParams;
     use crate::smc_info::SmcInfoBase;
     use crate::state::VmState;
+    use crate::tests::run_vm_with_stack;
     use crate::util::OwnedCellSlice;
+    use crate::NoLibraries;
 
     #[test]
     #[traced_test]
@@ -742,6 +744,55 @@ mod tests {
         println!("code {result}");
     }
 
+    #[test]
+    #[traced_test]
+    fn actions_chain() -> anyhow::Result<()> {
+        let mut output = String::new();
+        let (exit_code, vm) = run_vm_with_stack(
+            tvmasm!(
+                r#"
+                NEWC INT 1 STU 8 ENDC INT 0 SENDRAWMSG
+                NEWC INT 2 STU 8 ENDC INT 3 SENDRAWMSG
+                "#
+            ),
+            tuple![],
+            tuple![],
+            1000000,
+            &NoLibraries,
+            &mut output,
+        );
+        assert_eq!(exit_code, 0);
+
+        // The latest action is at the head of the list
+        let mut actions = Vec::new();
+        let mut head = vm.cr.get_d(5).unwrap();
+        while head.reference_count() > 0 {
+            let mut cs = head.as_slice()?;
+            let prev = cs.load_reference_cloned()?;
+            actions.push(OutAction::load_from(&mut cs)?);
+            head = prev;
+        }
+        assert_eq!(head, Cell::empty_cell());
+        assert_eq!(actions.len(), 2);
+
+        for (action, (expected_mode, tag)) in actions.iter().zip([(3, 2u8), (0, 1u8)]) {
+            let OutAction::SendMsg { mode, out_msg } = action else {
+                panic!("unexpected action: {action:?}");
+            };
+            assert_eq!(mode.bits(), expected_mode);
+            assert_eq!(out_msg.inner().as_slice()?.load_u8()?, tag);
+        }
+
+        // Mode must fit into a byte
+        assert_run_vm!(
+            "NEWC ENDC INT 256 SENDRAWMSG",
+            [] => [int 0],
+            exit_code: 5
+        );
+
+        Ok(())
+    }
+
     fn read_account(opt: bool, cell: Cell) -> Result<Box<Account>, everscale_types::error::Error> {
         let s = &mut cell.as_slice()?;
         assert!(!opt || s.load_bit()?);
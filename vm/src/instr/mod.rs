@@ -5,6 +5,7 @@ use anyhow::Result;
 use self::arithops::ArithOps;
 use self::cellops::CellOps;
 use self::cmpops::CmpOps;
+use self::codepageops::CodepageOps;
 use self::configops::ConfigOps;
 use self::contops::ContOps;
 use self::cryptops::CryptOps;
@@ -23,6 +24,7 @@ use crate::dispatch::{DispatchTable, Opcodes};
 mod arithops;
 mod cellops;
 mod cmpops;
+mod codepageops;
 mod configops;
 mod contops;
 mod cryptops;
@@ -54,6 +56,7 @@ pub fn codepage0() -> &'static DispatchTable {
         LogicOps.init(&mut cp)?;
         CellOps.init(&mut cp)?;
         ContOps.init(&mut cp)?;
+        CodepageOps.init(&mut cp)?;
         StackOps.init(&mut cp)?;
         TupleOps.init(&mut cp)?;
         DebugOps.init(&mut cp)?;
@@ -106,7 +106,15 @@ impl RandOps {
         SafeRc::make_mut(&mut c7)[0] = Stack::make_null();
 
         let mut t1v = t1v.into_tuple().expect("t1 was checked as tuple");
-        SafeRc::make_mut(&mut t1v)[SmcInfoBase::RANDSEED_IDX] = int.into_dyn_value();
+        {
+            // NOTE: Params tuple is extended with nulls if it is too short.
+            let t1 = SafeRc::make_mut(&mut t1v);
+            if t1.len() <= SmcInfoBase::RANDSEED_IDX {
+                t1.resize(SmcInfoBase::RANDSEED_IDX + 1, Stack::make_null());
+            }
+            t1[SmcInfoBase::RANDSEED_IDX] = int.into_dyn_value();
+        }
+        // NOTE: Tuple gas is charged for the extended params tuple.
         let t1_len = t1v.len();
 
         // NOTE: Restore c7 and control registers state.
@@ -259,4 +267,56 @@ pub mod test {
             [] => [int new_rand],
         );
     }
+
+    #[test]
+    #[traced_test]
+    fn random_sequence() {
+        let seed = uint256("576f8d6b5ac3bcc80844b7d50b1cc6603444bbe7cfcf8fc0aa1ee3c636d9e339");
+        let c7 = tuple![[null, null, null, null, null, null, int seed.clone()]];
+
+        // Each step hashes the seed with SHA-512 and splits the result
+        // into the new seed and the random value.
+        let mut expected = Vec::new();
+        let mut current = seed;
+        for _ in 0..3 {
+            let mut seed_bytes = [0u8; 32];
+            let bytes = current.magnitude().to_bytes_be();
+            seed_bytes[32 - bytes.len()..].copy_from_slice(&bytes);
+
+            let hash = sha2::Sha512::digest(seed_bytes);
+            current = BigInt::from_bytes_be(Sign::Plus, &hash[..32]);
+            expected.push(BigInt::from_bytes_be(Sign::Plus, &hash[32..]));
+        }
+
+        assert_run_vm!(
+            "RANDU256 RANDU256 RANDU256 GETPARAM 6",
+            c7: c7.clone(),
+            [] => [
+                int expected[0].clone(),
+                int expected[1].clone(),
+                int expected[2].clone(),
+                int current,
+            ]
+        );
+
+        // RAND scales the value into the range
+        let range = BigInt::from(1000);
+        let scaled = (&range * &expected[0]) >> 256;
+        assert_run_vm!("INT 1000 RAND", c7: c7.clone(), [] => [int scaled]);
+        assert_run_vm!("INT 0 RAND", c7: c7.clone(), [] => [int 0]);
+
+        // Same seed gives the same sequence
+        assert_run_vm!(
+            "INT 42 SETRAND RANDU256 INT 42 SETRAND RANDU256 EQUAL",
+            c7: c7,
+            [] => [int -1]
+        );
+
+        // Short params tuple is extended
+        assert_run_vm!("INT 42 SETRAND GETPARAM 6", c7: tuple![[null]], [] => [int 42]);
+        // 26 (INT) + 26 (SETRAND) + 7 (extended params) + 1 (c7) + 26 (GASCONSUMED)
+        assert_run_vm!("INT 42 SETRAND GASCONSUMED", c7: tuple![[null]], [] => [int 86]);
+        assert_run_vm!("INT 42 SETRAND", c7: tuple![int 1], [] => [int 0], exit_code: 7);
+        assert_run_vm!("INT -1 SETRAND", c7: tuple![[null]], [] => [int 0], exit_code: 5);
+    }
 }
@@ -29,7 +29,12 @@ impl SizeOps {
         };
         let bound = match bound {
             Some(bound) if !bound.is_negative() => SafeRc::unwrap_or_clone(bound),
-            _ => vm_bail!(IntegerOverflow),
+            Some(bound) => vm_bail!(IntegerOutOfRange {
+                min: 0,
+                max: isize::MAX,
+                actual: bound.to_string(),
+            }),
+            None => vm_bail!(IntegerOverflow),
         };
         let limit = bound.to_u64().unwrap_or(u64::MAX);
         let mut storage = StorageStatExt::with_limit(&st.gas, limit);
@@ -168,6 +173,25 @@ mod tests {
 
     use crate::OwnedCellSlice;
 
+    #[test]
+    #[traced_test]
+    fn data_size_small_tree() {
+        let left = CellBuilder::build_from(1u8).unwrap();
+        let right = CellBuilder::build_from(2u16).unwrap();
+
+        // Identical children are counted once, but each reference is counted
+        let shared = CellBuilder::build_from((left.clone(), left.clone())).unwrap();
+        assert_run_vm!("CDATASIZE", [cell shared.clone(), int 10] => [int 2, int 8, int 2]);
+        assert_run_vm!("CDATASIZE", [cell shared.clone(), int 2] => [int 2, int 8, int 2]);
+        assert_run_vm!("CDATASIZE", [cell shared, int 1] => [int 0], exit_code: 8);
+
+        let tree = CellBuilder::build_from((3u32, left, right)).unwrap();
+        assert_run_vm!("CDATASIZE", [cell tree.clone(), int 3] => [int 3, int 32 + 8 + 16, int 2]);
+        assert_run_vm!("CDATASIZEQ", [cell tree.clone(), int 2] => [int 0]);
+        assert_run_vm!("CDATASIZE", [cell tree.clone(), int -1] => [int 0], exit_code: 5);
+        assert_run_vm!("SDATASIZEQ", [slice OwnedCellSlice::new_allow_exotic(tree), int -1] => [int 0], exit_code: 5);
+    }
+
     #[test]
     #[traced_test]
     fn data_size() {
@@ -17,6 +17,8 @@ impl StackOps {
 
     #[op(code = "01", fmt = "SWAP")]
     fn exec_swap(st: &mut VmState) -> VmResult<i32> {
+        // NOTE: Check depth first to avoid cloning a shared stack on underflow.
+        vm_ensure!(st.stack.depth() >= 2, stack_underflow(2));
         ok!(SafeRc::make_mut(&mut st.stack).swap(0, 1));
         Ok(0)
     }
@@ -26,7 +28,14 @@ impl StackOps {
     #[op(code = "11jj", fmt = "XCHG s{j}", args(i = 0))]
     #[op(code = "1j @ 12..", fmt = "XCHG s1,s{j}", args(i = 1))]
     fn exec_xchg(st: &mut VmState, i: u32, j: u32) -> VmResult<i32> {
-        ok!(SafeRc::make_mut(&mut st.stack).swap(i as _, j as _));
+        // NOTE: `i <= j` for all encodings of this opcode.
+        vm_ensure!(
+            st.stack.depth() > j as usize,
+            stack_underflow(j as usize + 1)
+        );
+        if i != j {
+            ok!(SafeRc::make_mut(&mut st.stack).swap(i as _, j as _));
+        }
         Ok(0)
     }
 
@@ -98,7 +107,7 @@ impl StackOps {
         Ok(0)
     }
 
-    #[op(code = "52ij", fmt = ("PUXC s{i},s{}", j as i32 - 1))]
+    #[op(code = "52ij", name = "PUXC", fmt = ("PUXC s{i},s{}", j as i32 - 1))]
     fn exec_puxc(st: &mut VmState, i: u32, j: u32) -> VmResult<i32> {
         let stack = SafeRc::make_mut(&mut st.stack);
         ok!(stack.push_nth(i as _));
@@ -128,7 +137,7 @@ impl StackOps {
     }
 
     // XCHG PUSH XCHG
-    #[op(code = "542ijk", fmt = ("XCPUXC s{i},s{j},s{}", k as i32 - 1))]
+    #[op(code = "542ijk", name = "XCPUXC", fmt = ("XCPUXC s{i},s{j},s{}", k as i32 - 1))]
     fn exec_xcpuxc(st: &mut VmState, i: u32, j: u32, k: u32) -> VmResult<i32> {
         let stack = SafeRc::make_mut(&mut st.stack);
         ok!(stack.swap(1, i as _));
@@ -149,7 +158,7 @@ impl StackOps {
     }
 
     // PUSH XCHG XCHG
-    #[op(code = "544ijk", fmt = ("PUXC2 s{i},s{},s{}", j as i32 - 1, k as i32 - 1))]
+    #[op(code = "544ijk", name = "PUXC2", fmt = ("PUXC2 s{i},s{},s{}", j as i32 - 1, k as i32 - 1))]
     fn exec_puxc2(st: &mut VmState, i: u32, j: u32, k: u32) -> VmResult<i32> {
         let stack = SafeRc::make_mut(&mut st.stack);
         ok!(stack.push_nth(i as _));
@@ -160,7 +169,11 @@ impl StackOps {
     }
 
     // PUSH XCHG PUSH
-    #[op(code = "545ijk", fmt = ("PUXCPU s{i},s{},s{}", j as i32 - 1, k as i32 - 1))]
+    #[op(
+        code = "545ijk",
+        name = "PUXCPU",
+        fmt = ("PUXCPU s{i},s{},s{}", j as i32 - 1, k as i32 - 1)
+    )]
     fn exec_puxcpu(st: &mut VmState, i: u32, j: u32, k: u32) -> VmResult<i32> {
         let stack = SafeRc::make_mut(&mut st.stack);
         ok!(stack.push_nth(i as _));
@@ -171,7 +184,7 @@ impl StackOps {
     }
 
     // PUSH PUSH XCHG
-    #[op(code = "546ijk", fmt = ("PU2XC s{i},s{},s{}", j as i32 - 1, k as i32 - 2))]
+    #[op(code = "546ijk", name = "PU2XC", fmt = ("PU2XC s{i},s{},s{}", j as i32 - 1, k as i32 - 2))]
     fn exec_pu2xc(st: &mut VmState, i: u32, j: u32, k: u32) -> VmResult<i32> {
         let stack = SafeRc::make_mut(&mut st.stack);
         ok!(stack.push_nth(i as _));
@@ -194,10 +207,7 @@ impl StackOps {
 
     #[op(code = "55ij", fmt = "BLKSWAP {i},{j}", args(i = 1 + ((args >> 4) & 0xf), j = 1 + (args & 0xf)))]
     fn exec_blkswap(st: &mut VmState, i: u32, j: u32) -> VmResult<i32> {
-        let stack = SafeRc::make_mut(&mut st.stack);
-        ok!(stack.reverse_range(j as _, i as _));
-        ok!(stack.reverse_range(0, j as _));
-        ok!(stack.reverse_range(0, (i + j) as _));
+        ok!(SafeRc::make_mut(&mut st.stack).rotate_top((i + j) as _, j as _));
         Ok(0)
     }
 
@@ -206,33 +216,25 @@ impl StackOps {
 
     #[op(code = "58", fmt = "ROT")]
     fn exec_rot(st: &mut VmState) -> VmResult<i32> {
-        let stack = SafeRc::make_mut(&mut st.stack);
-        ok!(stack.swap(1, 2));
-        ok!(stack.swap(0, 1));
+        ok!(SafeRc::make_mut(&mut st.stack).rotate_top(3, 2));
         Ok(0)
     }
 
     #[op(code = "59", fmt = "ROTREV")]
     fn exec_rotrev(st: &mut VmState) -> VmResult<i32> {
-        let stack = SafeRc::make_mut(&mut st.stack);
-        ok!(stack.swap(0, 1));
-        ok!(stack.swap(1, 2));
+        ok!(SafeRc::make_mut(&mut st.stack).rotate_top(3, 1));
         Ok(0)
     }
 
     #[op(code = "5a", fmt = "2SWAP")]
     fn exec_2swap(st: &mut VmState) -> VmResult<i32> {
-        let stack = SafeRc::make_mut(&mut st.stack);
-        ok!(stack.swap(1, 3));
-        ok!(stack.swap(0, 2));
+        ok!(SafeRc::make_mut(&mut st.stack).rotate_top(4, 2));
         Ok(0)
     }
 
     #[op(code = "5b", fmt = "2DROP")]
     fn exec_2drop(st: &mut VmState) -> VmResult<i32> {
-        let stack = SafeRc::make_mut(&mut st.stack);
-        ok!(stack.pop());
-        ok!(stack.pop());
+        ok!(SafeRc::make_mut(&mut st.stack).pop_many(2));
         Ok(0)
     }
 
@@ -286,15 +288,12 @@ impl StackOps {
     fn exec_roll(st: &mut VmState) -> VmResult<i32> {
         let stack = SafeRc::make_mut(&mut st.stack);
 
-        let mut i = ok!(stack.pop_smallint_range(0, max_stack_size(st.version)));
-        if let Some(diff) = i.checked_sub(STACK_FEE_THRESHOLD) {
+        let x = ok!(stack.pop_smallint_range(0, max_stack_size(st.version)));
+        if let Some(diff) = x.checked_sub(STACK_FEE_THRESHOLD) {
             st.gas.try_consume(diff as u64)?;
         }
 
-        while i >= 1 {
-            ok!(stack.swap(i as _, (i - 1) as _));
-            i -= 1;
-        }
+        ok!(stack.rotate_top(x as usize + 1, x as _));
         Ok(0)
     }
 
@@ -307,9 +306,7 @@ impl StackOps {
             st.gas.try_consume(diff as u64)?;
         }
 
-        for i in 0..x {
-            ok!(stack.swap(i as _, (i + 1) as _));
-        }
+        ok!(stack.rotate_top(x as usize + 1, 1));
         Ok(0)
     }
 
@@ -327,9 +324,7 @@ impl StackOps {
                 }
             }
 
-            ok!(stack.reverse_range(y as _, x as _));
-            ok!(stack.reverse_range(0, y as _));
-            ok!(stack.reverse_range(0, (x + y) as _));
+            ok!(stack.rotate_top((x + y) as _, y as _));
         }
         Ok(0)
     }
@@ -382,7 +377,7 @@ impl StackOps {
     fn exec_chkdepth(st: &mut VmState) -> VmResult<i32> {
         let stack = SafeRc::make_mut(&mut st.stack);
         let x = ok!(stack.pop_smallint_range(0, max_stack_size(st.version))) as usize;
-        vm_ensure!(x <= stack.depth(), StackUnderflow(x));
+        vm_ensure!(x <= stack.depth(), stack_underflow(x));
         Ok(0)
     }
 
@@ -392,7 +387,7 @@ impl StackOps {
 
         let x = ok!(stack.pop_smallint_range(0, max_stack_size(st.version))) as usize;
         let Some(d) = stack.depth().checked_sub(x) else {
-            vm_bail!(StackUnderflow(x));
+            vm_bail!(stack_underflow(x));
         };
 
         if d > 0 {
@@ -412,7 +407,7 @@ impl StackOps {
         let stack = SafeRc::make_mut(&mut st.stack);
         let x = ok!(stack.pop_smallint_range(0, max_stack_size(st.version))) as usize;
         let Some(d) = stack.depth().checked_sub(x) else {
-            vm_bail!(StackUnderflow(x));
+            vm_bail!(stack_underflow(x));
         };
         stack.items.truncate(d);
         Ok(0)
@@ -425,7 +420,7 @@ impl StackOps {
         let depth = stack.depth();
         let offset = j as usize;
         let count = i as usize;
-        vm_ensure!((count + offset) <= depth, StackUnderflow(count + offset));
+        vm_ensure!((count + offset) <= depth, stack_underflow(count + offset));
 
         stack.items.drain(depth - (count + offset)..depth - offset);
         Ok(0)
@@ -449,8 +444,15 @@ const STACK_FEE_THRESHOLD: u32 = 255;
 
 #[cfg(test)]
 mod tests {
+    use everscale_types::boc::Boc;
+    use num_bigint::BigInt;
     use tracing_test::traced_test;
 
+    use crate::error::VmError;
+    use crate::gas::GasParams;
+    use crate::state::VmState;
+    use crate::tests::{int_stack, run_code};
+
     #[test]
     #[traced_test]
     fn blkdrop2() {
@@ -492,4 +494,188 @@ mod tests {
             [int 1, int 2, int 3, int 4] => [int 3, int 4]
         );
     }
+
+    #[test]
+    #[traced_test]
+    fn swap_and_xchg() {
+        assert_run_vm!("SWAP", [int 1, int 2] => [int 2, int 1]);
+        assert_run_vm!("SWAP SWAP", [int 1, int 2] => [int 1, int 2]);
+        assert_run_vm!("SWAP", [int 1] => [int 0], exit_code: 2);
+        assert_run_vm!("SWAP", [] => [int 0], exit_code: 2);
+
+        assert_run_vm!("XCHG s2", [int 1, int 2, int 3] => [int 3, int 2, int 1]);
+        assert_run_vm!("XCHG s1,s2", [int 1, int 2, int 3] => [int 2, int 1, int 3]);
+        assert_run_vm!("XCHG s2,s3", [int 1, int 2, int 3, int 4] => [int 2, int 1, int 3, int 4]);
+        assert_run_vm!("XCHG s3", [int 1, int 2, int 3] => [int 0], exit_code: 2);
+        assert_run_vm!("XCHG s1,s3", [int 1, int 2, int 3] => [int 0], exit_code: 2);
+
+        // XCHG s0,s0 is a no-op but still requires a non-empty stack
+        assert_run_vm!("@inline x{1100}", [int 1, int 2] => [int 1, int 2]);
+        assert_run_vm!("@inline x{1100}", [] => [int 0], exit_code: 2);
+    }
+
+    #[test]
+    #[traced_test]
+    fn rotations() {
+        assert_run_vm!("ROT", [int 1, int 2, int 3] => [int 2, int 3, int 1]);
+        assert_run_vm!("ROTREV", [int 1, int 2, int 3] => [int 3, int 1, int 2]);
+        assert_run_vm!("ROT ROTREV", [int 1, int 2, int 3] => [int 1, int 2, int 3]);
+        assert_run_vm!("ROTREV", [int 0, int 1, int 2, int 3] => [int 0, int 3, int 1, int 2]);
+        assert_run_vm!("ROT", [int 1, int 2] => [int 0], exit_code: 2);
+        assert_run_vm!("ROTREV", [int 1, int 2] => [int 0], exit_code: 2);
+
+        // 2ROT
+        assert_run_vm!(
+            "BLKSWAP 2, 4",
+            [int 1, int 2, int 3, int 4, int 5, int 6] => [int 3, int 4, int 5, int 6, int 1, int 2]
+        );
+        assert_run_vm!(
+            "INT 1 INT 2 BLKSWX",
+            [int 1, int 2, int 3] => [int 2, int 3, int 1]
+        );
+
+        assert_run_vm!("INT 3 ROLL", [int 1, int 2, int 3, int 4] => [int 2, int 3, int 4, int 1]);
+        assert_run_vm!("INT 3 ROLLREV", [int 1, int 2, int 3, int 4] => [int 4, int 1, int 2, int 3]);
+        assert_run_vm!("INT 0 ROLL", [int 1] => [int 1]);
+        assert_run_vm!("INT 0 ROLL", [] => [int 0], exit_code: 2);
+        assert_run_vm!("INT 3 ROLL", [int 1, int 2, int 3] => [int 0], exit_code: 2);
+        assert_run_vm!("INT 3 ROLLREV", [int 1, int 2, int 3] => [int 0], exit_code: 2);
+    }
+
+    #[test]
+    #[traced_test]
+    fn pair_ops() {
+        assert_run_vm!("2SWAP", [int 1, int 2, int 3, int 4] => [int 3, int 4, int 1, int 2]);
+        assert_run_vm!("2SWAP", [int 1, int 2, int 3] => [int 0], exit_code: 2);
+
+        assert_run_vm!("2DROP", [int 1, int 2, int 3] => [int 1]);
+        assert_run_vm!("2DROP", [int 1, int 2] => []);
+        assert_run_vm!("2DROP", [int 1] => [int 0], exit_code: 2);
+
+        assert_run_vm!("2DUP", [int 1, int 2] => [int 1, int 2, int 1, int 2]);
+        assert_run_vm!("2DUP", [int 1] => [int 0], exit_code: 2);
+
+        assert_run_vm!(
+            "2OVER",
+            [int 1, int 2, int 3, int 4] => [int 1, int 2, int 3, int 4, int 1, int 2]
+        );
+        assert_run_vm!("2OVER", [int 1, int 2, int 3] => [int 0], exit_code: 2);
+    }
+
+    #[test]
+    #[traced_test]
+    fn pair_ops_underflow_keeps_stack() {
+        let codes: [&[u8]; 4] = [
+            tvmasm!("2SWAP"),
+            tvmasm!("2DROP"),
+            tvmasm!("2DUP"),
+            tvmasm!("2OVER"),
+        ];
+        for code in codes {
+            let mut vm = VmState::builder()
+                .with_code(Boc::decode(code).unwrap())
+                .with_stack(tuple![int 1])
+                .with_gas(GasParams::getter())
+                .build();
+            let e = vm.step().unwrap_err();
+            assert!(
+                matches!(*e, VmError::StackUnderflow { op: Some(_), .. }),
+                "{e}"
+            );
+            assert_eq!(vm.stack.depth(), 1);
+        }
+    }
+
+    #[test]
+    #[traced_test]
+    fn xchg_long() {
+        // XCHG s0,s200
+        let (exit_code, vm) = run_code(
+            Boc::decode(tvmasm!("@inline x{11c8}")).unwrap(),
+            int_stack(256),
+        );
+        assert_eq!(exit_code, 0);
+        assert_eq!(vm.stack.depth(), 256);
+        for (i, item) in vm.stack.items.iter().enumerate() {
+            let expected = match i {
+                55 => 255,
+                255 => 55,
+                _ => i,
+            };
+            assert_eq!(item.try_as_int().unwrap(), &BigInt::from(expected));
+        }
+
+        // XCHG s0,s255
+        let (exit_code, vm) = run_code(
+            Boc::decode(tvmasm!("@inline x{11ff}")).unwrap(),
+            int_stack(256),
+        );
+        assert_eq!(exit_code, 0);
+        assert_eq!(vm.stack.items[0].try_as_int().unwrap(), &BigInt::from(255));
+        assert_eq!(vm.stack.items[255].try_as_int().unwrap(), &BigInt::from(0));
+
+        let (exit_code, _) = run_code(
+            Boc::decode(tvmasm!("@inline x{11c8}")).unwrap(),
+            int_stack(200),
+        );
+        assert_eq!(exit_code, 2);
+    }
+
+    #[test]
+    #[traced_test]
+    fn push_pop_long() {
+        let int_at = |vm: &VmState<'_>, i: usize| vm.stack.items[i].try_as_int().unwrap().clone();
+
+        // PUSH s100
+        let (exit_code, vm) = run_code(
+            Boc::decode(tvmasm!("@inline x{5664}")).unwrap(),
+            int_stack(150),
+        );
+        assert_eq!(exit_code, 0);
+        assert_eq!(vm.stack.depth(), 151);
+        assert_eq!(int_at(&vm, 150), BigInt::from(49));
+        assert_eq!(int_at(&vm, 49), BigInt::from(49));
+
+        // POP s100
+        let (exit_code, vm) = run_code(
+            Boc::decode(tvmasm!("@inline x{5764}")).unwrap(),
+            int_stack(150),
+        );
+        assert_eq!(exit_code, 0);
+        assert_eq!(vm.stack.depth(), 149);
+        assert_eq!(int_at(&vm, 49), BigInt::from(149));
+        assert_eq!(int_at(&vm, 148), BigInt::from(148));
+
+        // Long forms also work for small indices
+        let (exit_code, vm) = run_code(
+            Boc::decode(tvmasm!("@inline x{5601}")).unwrap(),
+            int_stack(2),
+        );
+        assert_eq!(exit_code, 0);
+        assert_eq!(int_at(&vm, 2), BigInt::from(0));
+
+        // Index must be less than the stack depth
+        let cases: [(&[u8], usize, i32); 3] = [
+            (tvmasm!("@inline x{5664}"), 100, 2),
+            (tvmasm!("@inline x{5764}"), 100, 2),
+            (tvmasm!("@inline x{56ff}"), 256, 0),
+        ];
+        for (code, depth, expected) in cases {
+            let (exit_code, _) = run_code(Boc::decode(code).unwrap(), int_stack(depth));
+            assert_eq!(exit_code, expected);
+        }
+    }
+
+    #[test]
+    #[traced_test]
+    fn swap_inside_continuation() {
+        assert_run_vm!(
+            r#"
+                PUSHCONT { SWAP }
+                CALLX
+                XCHG s2
+            "#,
+            [int 1, int 2, int 3] => [int 2, int 3, int 1]
+        );
+    }
 }
@@ -302,7 +302,7 @@ impl TupleOps {
 
 fn make_tuple_impl(stack: &mut Stack, n: usize, gas: &GasConsumer) -> VmResult<i32> {
     let Some(offset) = stack.depth().checked_sub(n) else {
-        vm_bail!(StackUnderflow(n));
+        vm_bail!(stack_underflow(n));
     };
     let tuple = SafeRc::new(stack.items.drain(offset..offset + n).collect::<Vec<_>>());
     gas.try_consume_tuple_gas(tuple.len() as u64)?;
@@ -490,6 +490,119 @@ mod tests {
         assert_run_vm!("NULLROTRIFNOT NULLROTRIFNOT", [int 1, int -1] => [int 1, int -1]);
     }
 
+    #[test]
+    #[traced_test]
+    fn basic_tuple_ops() {
+        assert_run_vm!("TUPLE 3", [int 1, int 2, int 3] => [[int 1, int 2, int 3]]);
+        assert_run_vm!("TUPLE 0 TLEN", [int 1] => [int 1, int 0]);
+        assert_run_vm!("TUPLE 3", [int 1, int 2] => [int 0], exit_code: 2);
+
+        assert_run_vm!("INDEX 0", [[int 1, int 2, int 3]] => [int 1]);
+        assert_run_vm!("INDEX 1", [[int 1, int 2, int 3]] => [int 2]);
+        assert_run_vm!("INDEX 2", [[int 1, int 2, int 3]] => [int 3]);
+        assert_run_vm!("INDEX 3", [[int 1, int 2, int 3]] => [int 0], exit_code: 5);
+        assert_run_vm!("INDEX 0", [int 1] => [int 0], exit_code: 7);
+
+        assert_run_vm!(
+            "SETINDEX 1",
+            [[int 1, int 2, int 3], int 5] => [[int 1, int 5, int 3]]
+        );
+        assert_run_vm!("SETINDEX 3", [[int 1, int 2, int 3], int 5] => [int 0], exit_code: 5);
+
+        assert_run_vm!("UNTUPLE 3", [[int 1, int 2, int 3]] => [int 1, int 2, int 3]);
+        assert_run_vm!("UNTUPLE 2", [[int 1, int 2, int 3]] => [int 0], exit_code: 7);
+
+        assert_run_vm!("TLEN", [[int 1, int 2, int 3]] => [int 3]);
+        assert_run_vm!("TLEN", [int 1] => [int 0], exit_code: 7);
+        assert_run_vm!("ISTUPLE", [[int 1]] => [int -1]);
+        assert_run_vm!("ISTUPLE", [int 1] => [int 0]);
+
+        // Tuples are limited to 255 items
+        assert_run_vm!("INT 256 TUPLEVAR", [] => [int 0], exit_code: 5);
+        assert_run_vm!("INT 255 TUPLEVAR TLEN", [] => [int 0], exit_code: 2);
+    }
+
+    #[test]
+    #[traced_test]
+    fn quiet_tuple_ops() {
+        assert_run_vm!("INDEXQ 1", [[int 1, int 2]] => [int 2]);
+        assert_run_vm!("INDEXQ 2", [[int 1, int 2]] => [null]);
+        assert_run_vm!("INDEXQ 0", [null] => [null]);
+        assert_run_vm!("INDEXQ 0", [int 1] => [int 0], exit_code: 7);
+
+        assert_run_vm!("SETINDEXQ 0", [[int 1, int 2], int 5] => [[int 5, int 2]]);
+        assert_run_vm!("SETINDEXQ 3", [[int 1], int 5] => [[int 1, null, null, int 5]]);
+        assert_run_vm!("SETINDEXQ 3", [[int 1], null] => [[int 1]]);
+        assert_run_vm!("SETINDEXQ 2", [null, int 5] => [[null, null, int 5]]);
+        assert_run_vm!("SETINDEXQ 2", [null, null] => [null]);
+    }
+
+    #[test]
+    #[traced_test]
+    fn tuple_push_pop() {
+        assert_run_vm!("TPUSH", [[int 1], int 2] => [[int 1, int 2]]);
+        assert_run_vm!("NIL INT 3 TPUSH", [] => [[int 3]]);
+        assert_run_vm!("TPOP", [[int 1, int 2]] => [[int 1], int 2]);
+        assert_run_vm!("NIL TPOP", [] => [int 0], exit_code: 7);
+
+        // Growing past 255 items throws
+        assert_run_vm!(
+            "NIL INT 255 PUSHCONT { INT 0 TPUSH } REPEAT TLEN",
+            [] => [int 255]
+        );
+        assert_run_vm!(
+            "NIL INT 255 PUSHCONT { INT 0 TPUSH } REPEAT INT 0 TPUSH",
+            [] => [int 0],
+            exit_code: 7,
+        );
+    }
+
+    #[test]
+    #[traced_test]
+    fn var_tuple_ops() {
+        assert_run_vm!("INT 3 TUPLEVAR", [int 1, int 2, int 3] => [[int 1, int 2, int 3]]);
+        assert_run_vm!("INT 1 INDEXVAR", [[int 1, int 2, int 3]] => [int 2]);
+        assert_run_vm!("INT 3 INDEXVAR", [[int 1, int 2, int 3]] => [int 0], exit_code: 5);
+        assert_run_vm!(
+            "INT 2 SETINDEXVAR",
+            [[int 1, int 2, int 3], int 5] => [[int 1, int 2, int 5]]
+        );
+
+        assert_run_vm!("INT 3 UNTUPLEVAR", [[int 1, int 2, int 3]] => [int 1, int 2, int 3]);
+        assert_run_vm!("INT 2 UNTUPLEVAR", [[int 1, int 2, int 3]] => [int 0], exit_code: 7);
+        assert_run_vm!("INT -1 UNTUPLEVAR", [[int 1]] => [int 0], exit_code: 5);
+
+        // Explode onto the stack and rebuild it back
+        assert_run_vm!("EXPLODE 3", [[int 1, int 2]] => [int 1, int 2, int 2]);
+        assert_run_vm!("EXPLODE 1", [[int 1, int 2]] => [int 0], exit_code: 7);
+        assert_run_vm!(
+            "INT 5 EXPLODEVAR TUPLEVAR",
+            [[int 1, int 2, int 3]] => [[int 1, int 2, int 3]]
+        );
+        assert_run_vm!("INT 256 EXPLODEVAR", [[int 1]] => [int 0], exit_code: 5);
+    }
+
+    #[test]
+    #[traced_test]
+    fn tuple_aliases() {
+        // NIL/SINGLE/PAIR/TRIPLE are the `TUPLE n` opcodes for small `n`
+        assert_run_vm!("NIL TLEN", [] => [int 0]);
+        assert_run_vm!("SINGLE", [int 1] => [[int 1]]);
+        assert_run_vm!("PAIR", [int 1, int 2] => [[int 1, int 2]]);
+        assert_run_vm!("TRIPLE", [int 1, int 2, int 3] => [[int 1, int 2, int 3]]);
+
+        assert_run_vm!("FIRST", [[int 1, int 2, int 3]] => [int 1]);
+        assert_run_vm!("SECOND", [[int 1, int 2, int 3]] => [int 2]);
+        assert_run_vm!("THIRD", [[int 1, int 2, int 3]] => [int 3]);
+        assert_run_vm!("THIRD", [[int 1, int 2]] => [int 0], exit_code: 5);
+
+        assert_run_vm!("PAIR UNPAIR", [int 1, int 2] => [int 1, int 2]);
+        assert_run_vm!("TRIPLE UNTRIPLE", [int 1, int 2, int 3] => [int 1, int 2, int 3]);
+        assert_run_vm!("UNPAIR", [[int 1, int 2, int 3]] => [int 0], exit_code: 7);
+        assert_run_vm!("UNTRIPLE", [[int 1, int 2]] => [int 0], exit_code: 7);
+        assert_run_vm!("UNPAIR", [int 1] => [int 0], exit_code: 7);
+    }
+
     #[test]
     #[traced_test]
     fn index2() {
@@ -0,0 +1,290 @@
+use std::cmp::Ordering;
+
+use num_bigint::BigInt;
+
+/// A fixed-size signed integer in the range of TVM integers `[-2^256, 2^256)`.
+///
+/// Stored as a 320-bit two's complement number so that common
+/// arithmetic does not require heap allocations.
+#[derive(Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Int257([u64; LIMBS]);
+
+impl Int257 {
+    pub const ZERO: Self = Self([0; LIMBS]);
+    pub const ONE: Self = Self([1, 0, 0, 0, 0]);
+    pub const MINUS_ONE: Self = Self([u64::MAX; LIMBS]);
+    /// `2^256 - 1`
+    pub const MAX: Self = Self([u64::MAX, u64::MAX, u64::MAX, u64::MAX, 0]);
+    /// `-2^256`
+    pub const MIN: Self = Self([0, 0, 0, 0, u64::MAX]);
+
+    pub const fn from_i64(value: i64) -> Self {
+        let ext = if value < 0 { u64::MAX } else { 0 };
+        Self([value as u64, ext, ext, ext, ext])
+    }
+
+    pub const fn from_u64(value: u64) -> Self {
+        Self([value, 0, 0, 0, 0])
+    }
+
+    pub const fn from_i128(value: i128) -> Self {
+        let ext = if value < 0 { u64::MAX } else { 0 };
+        Self([value as u64, (value >> 64) as u64, ext, ext, ext])
+    }
+
+    pub const fn from_u128(value: u128) -> Self {
+        Self([value as u64, (value >> 64) as u64, 0, 0, 0])
+    }
+
+    #[inline]
+    pub const fn is_zero(&self) -> bool {
+        matches!(self.0, [0, 0, 0, 0, 0])
+    }
+
+    #[inline]
+    pub const fn is_negative(&self) -> bool {
+        self.0[LIMBS - 1] >> 63 != 0
+    }
+
+    pub fn to_i64(&self) -> Option<i64> {
+        let value = self.0[0] as i64;
+        let ext = if value < 0 { u64::MAX } else { 0 };
+        self.0[1..].iter().all(|limb| *limb == ext).then_some(value)
+    }
+
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        let mut res = [0; LIMBS];
+        let mut carry = false;
+        for (i, res) in res.iter_mut().enumerate() {
+            let (value, c1) = self.0[i].overflowing_add(rhs.0[i]);
+            let (value, c2) = value.overflowing_add(carry as u64);
+            *res = value;
+            carry = c1 | c2;
+        }
+        Self(res).checked()
+    }
+
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        let mut res = [0; LIMBS];
+        let mut borrow = false;
+        for (i, res) in res.iter_mut().enumerate() {
+            let (value, b1) = self.0[i].overflowing_sub(rhs.0[i]);
+            let (value, b2) = value.overflowing_sub(borrow as u64);
+            *res = value;
+            borrow = b1 | b2;
+        }
+        Self(res).checked()
+    }
+
+    pub fn checked_neg(self) -> Option<Self> {
+        Self::ZERO.checked_sub(self)
+    }
+
+    /// Returns `None` if the high limb is not a sign extension of the bit 256.
+    fn checked(self) -> Option<Self> {
+        // NOTE: Both operands are at most 257 bits wide, so the result
+        // of a single addition or subtraction always fits into 320 bits.
+        matches!(self.0[LIMBS - 1], 0 | u64::MAX).then_some(self)
+    }
+
+    fn to_le_bytes(self) -> [u8; LIMBS * 8] {
+        let mut bytes = [0; LIMBS * 8];
+        for (chunk, limb) in bytes.chunks_exact_mut(8).zip(self.0) {
+            chunk.copy_from_slice(&limb.to_le_bytes());
+        }
+        bytes
+    }
+}
+
+impl Ord for Int257 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.is_negative(), other.is_negative()) {
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            // NOTE: Two's complement numbers of the same sign
+            // are ordered the same way as unsigned numbers.
+            _ => self.0.iter().rev().cmp(other.0.iter().rev()),
+        }
+    }
+}
+
+impl PartialOrd for Int257 {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+macro_rules! impl_from_int {
+    ($($ty:ty => $f:ident),*$(,)?) => {
+        $(impl From<$ty> for Int257 {
+            #[inline]
+            fn from(value: $ty) -> Self {
+                Self::$f(value as _)
+            }
+        })*
+    };
+}
+
+impl_from_int! {
+    i8 => from_i64,
+    i16 => from_i64,
+    i32 => from_i64,
+    i64 => from_i64,
+    isize => from_i64,
+    i128 => from_i128,
+    u8 => from_u64,
+    u16 => from_u64,
+    u32 => from_u64,
+    u64 => from_u64,
+    usize => from_u64,
+    u128 => from_u128,
+}
+
+impl From<Int257> for BigInt {
+    fn from(value: Int257) -> Self {
+        BigInt::from_signed_bytes_le(&value.to_le_bytes())
+    }
+}
+
+impl TryFrom<&BigInt> for Int257 {
+    type Error = Int257OutOfRange;
+
+    fn try_from(value: &BigInt) -> Result<Self, Self::Error> {
+        let bytes = value.to_signed_bytes_le();
+        if bytes.len() > LIMBS * 8 {
+            return Err(Int257OutOfRange);
+        }
+
+        let ext = if value.sign() == num_bigint::Sign::Minus {
+            0xff
+        } else {
+            0
+        };
+        let mut full = [ext; LIMBS * 8];
+        full[..bytes.len()].copy_from_slice(&bytes);
+
+        let mut limbs = [0; LIMBS];
+        for (limb, chunk) in limbs.iter_mut().zip(full.chunks_exact(8)) {
+            *limb = u64::from_le_bytes(chunk.try_into().unwrap());
+        }
+
+        Self(limbs).checked().ok_or(Int257OutOfRange)
+    }
+}
+
+impl TryFrom<BigInt> for Int257 {
+    type Error = Int257OutOfRange;
+
+    #[inline]
+    fn try_from(value: BigInt) -> Result<Self, Self::Error> {
+        Self::try_from(&value)
+    }
+}
+
+impl std::fmt::Display for Int257 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(value) = self.to_i64() {
+            std::fmt::Display::fmt(&value, f)
+        } else {
+            std::fmt::Display::fmt(&BigInt::from(*self), f)
+        }
+    }
+}
+
+impl std::fmt::Debug for Int257 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(self, f)
+    }
+}
+
+/// Integer does not fit into 257 bits.
+#[derive(Debug, Clone, Copy, thiserror::Error)]
+#[error("integer does not fit into 257 bits")]
+pub struct Int257OutOfRange;
+
+const LIMBS: usize = 5;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bigint_roundtrip() {
+        let max = (BigInt::from(1) << 256) - 1;
+        let min = -(BigInt::from(1) << 256);
+
+        for value in [
+            BigInt::from(0),
+            BigInt::from(1),
+            BigInt::from(-1),
+            BigInt::from(i64::MAX),
+            BigInt::from(i64::MIN),
+            BigInt::from(u64::MAX),
+            BigInt::from(1) << 200,
+            -(BigInt::from(1) << 200),
+            max.clone(),
+            min.clone(),
+        ] {
+            let int = Int257::try_from(&value).unwrap();
+            assert_eq!(BigInt::from(int), value);
+            assert_eq!(int.to_string(), value.to_string());
+        }
+
+        assert_eq!(Int257::try_from(&max).unwrap(), Int257::MAX);
+        assert_eq!(Int257::try_from(&min).unwrap(), Int257::MIN);
+        assert!(Int257::try_from(max + 1).is_err());
+        assert!(Int257::try_from(min - 1).is_err());
+    }
+
+    #[test]
+    fn checked_arithmetic() {
+        let a = Int257::from(-5);
+        let b = Int257::from(7);
+        assert_eq!(a.checked_add(b), Some(Int257::from(2)));
+        assert_eq!(a.checked_sub(b), Some(Int257::from(-12)));
+        assert_eq!(b.checked_neg(), Some(Int257::from(-7)));
+
+        let wide = Int257::from(u64::MAX).checked_add(Int257::ONE).unwrap();
+        assert_eq!(wide.to_i64(), None);
+        assert_eq!(BigInt::from(wide), BigInt::from(1) << 64);
+        assert_eq!(wide, Int257::from(1u128 << 64));
+        assert_eq!(
+            BigInt::from(Int257::from(i128::MIN)),
+            BigInt::from(i128::MIN)
+        );
+        assert_eq!(
+            BigInt::from(Int257::from(u128::MAX)),
+            BigInt::from(u128::MAX)
+        );
+
+        assert_eq!(Int257::MAX.checked_add(Int257::ONE), None);
+        assert_eq!(Int257::MIN.checked_sub(Int257::ONE), None);
+        assert_eq!(Int257::MIN.checked_neg(), None);
+        assert_eq!(
+            Int257::MAX.checked_neg().unwrap().checked_sub(Int257::ONE),
+            Some(Int257::MIN)
+        );
+    }
+
+    #[test]
+    fn ordering() {
+        let mut values = [
+            Int257::MAX,
+            Int257::ONE,
+            Int257::MIN,
+            Int257::ZERO,
+            Int257::MINUS_ONE,
+            Int257::from(i64::MIN),
+        ];
+        values.sort();
+        assert_eq!(values, [
+            Int257::MIN,
+            Int257::from(i64::MIN),
+            Int257::MINUS_ONE,
+            Int257::ZERO,
+            Int257::ONE,
+            Int257::MAX,
+        ]);
+    }
+}
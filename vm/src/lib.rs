@@ -15,7 +15,11 @@ macro_rules! ok {
 
 #[cfg(feature = "tracing")]
 macro_rules! vm_log_op {
-    ($($tt:tt)*) => { $crate::__log_op(format_args!($($tt)*)) };
+    ($st:expr, $($tt:tt)*) => {
+        if $st.log_ops {
+            $crate::__log_op(format_args!($($tt)*))
+        }
+    };
 }
 
 #[cfg(feature = "tracing")]
@@ -185,21 +189,30 @@ pub use self::cont::{
     QuitCont, RcCont, RepeatCont, UntilCont, WhileCont,
 };
 pub use self::dispatch::{
-    DispatchTable, FnExecInstrArg, FnExecInstrFull, FnExecInstrSimple, Opcode, Opcodes,
+    DispatchTable, EmbeddedConts, FnDumpInstrArg, FnDumpInstrFull, FnDumpInstrSimple,
+    FnExecInstrArg, FnExecInstrFull, FnExecInstrSimple, Opcode, OpcodeInfo, Opcodes,
+};
+pub use self::error::{
+    CodepageError, DecodeError, StackDecodeError, VmError, VmException, VmResult,
+};
+pub use self::gas::{
+    count_unique_cells, GasConsumer, GasEvent, GasParams, GasPrices, GasReason, GasSnapshot,
+    GasTrace, LibraryProvider, NoLibraries,
 };
-pub use self::error::{VmError, VmException, VmResult};
-pub use self::gas::{GasConsumer, GasParams, LibraryProvider, NoLibraries};
 pub use self::instr::{codepage, codepage0};
+pub use self::int257::{Int257, Int257OutOfRange};
 pub use self::saferc::{SafeDelete, SafeRc, SafeRcMakeMut};
 pub use self::smc_info::{
-    CustomSmcInfo, SmcInfo, SmcInfoBase, SmcInfoTonV4, SmcInfoTonV6, UnpackedConfig, VmVersion,
+    check_c7_layout, C7LayoutError, CustomSmcInfo, SmcInfo, SmcInfoBase, SmcInfoTonV4,
+    SmcInfoTonV6, UnpackedConfig, VmVersion,
 };
 pub use self::stack::{
-    NaN, RcStackValue, Stack, StackValue, StackValueType, StaticStackValue, Tuple, TupleExt,
+    FromStack, FromStackValue, InlineInt, IntoStackInt, NaN, RcStackValue, Stack, StackValue,
+    StackValueType, StaticStackValue, Tuple, TupleExt,
 };
 pub use self::state::{
-    BehaviourModifiers, CommitedState, InitSelectorParams, IntoCode, SaveCr, VmState,
-    VmStateBuilder,
+    BehaviourModifiers, CommitedState, InitSelectorParams, IntoCode, SaveCr, StepHook, VmRunResult,
+    VmSnapshot, VmState, VmStateBuilder,
 };
 pub use self::util::OwnedCellSlice;
 
@@ -208,6 +221,7 @@ mod dispatch;
 mod error;
 mod gas;
 mod instr;
+mod int257;
 mod saferc;
 mod smc_info;
 mod stack;
@@ -257,6 +271,7 @@ mod tests {
 
     use everscale_types::models::{CurrencyCollection, SimpleLib, StdAddr};
     use everscale_types::prelude::*;
+    use num_bigint::BigInt;
     use tracing_test::traced_test;
 
     use super::*;
@@ -297,6 +312,28 @@ mod tests {
         (exit_code, vm)
     }
 
+    /// Runs the code with the specified stack and getter gas params.
+    pub fn run_code<T, I>(code: T, stack: I) -> (i32, VmState<'static>)
+    where
+        T: IntoCode,
+        I: IntoIterator<Item = RcStackValue>,
+    {
+        let mut vm = VmState::builder()
+            .with_code(code)
+            .with_stack(stack)
+            .with_gas(GasParams::getter())
+            .build();
+
+        let exit_code = !vm.run();
+
+        (exit_code, vm)
+    }
+
+    /// Integers from `0` to `depth - 1` (the last one is on top).
+    pub fn int_stack(depth: usize) -> impl Iterator<Item = RcStackValue> {
+        (0..depth).map(|i| SafeRc::new_dyn_value(BigInt::from(i)))
+    }
+
     #[track_caller]
     pub fn compare_stack(actual: &Tuple, expected: &Tuple) {
         let cx = Cell::empty_context();
@@ -359,6 +396,512 @@ mod tests {
         println!("Exit code: {exit_code}");
     }
 
+    #[test]
+    #[traced_test]
+    fn assembled_code_works() -> anyhow::Result<()> {
+        let code = Boc::decode(tvmasm!("PUSHINT 2 PUSHINT 2 ADD"))?;
+
+        let mut vm = VmState::builder()
+            .with_code(code)
+            .with_gas(GasParams::getter())
+            .build();
+        assert_eq!(vm.run(), -1);
+        compare_stack(&vm.stack.items, &tuple![int 4]);
+
+        assert_run_vm!("PUSHINT 2 PUSHINT 2 ADD", [] => [int 4]);
+        Ok(())
+    }
+
+    #[test]
+    #[traced_test]
+    fn auto_commit_can_be_disabled() -> anyhow::Result<()> {
+        let data = CellBuilder::build_from(123u32)?;
+
+        let run = |code: &[u8], auto_commit: bool| {
+            let mut vm = VmState::builder()
+                .with_code(Boc::decode(code).unwrap())
+                .with_data(data.clone())
+                .with_gas(GasParams::getter())
+                .with_modifiers(BehaviourModifiers {
+                    auto_commit,
+                    ..Default::default()
+                })
+                .build();
+            let exit_code = !vm.run();
+            (
+                exit_code,
+                vm.commited_state.map(|state| *state.c4.repr_hash()),
+            )
+        };
+
+        // Implicit commit on a successful exit
+        let (exit_code, c4) = run(tvmasm!("NOP"), true);
+        assert_eq!(exit_code, 0);
+        assert_eq!(c4.as_ref(), Some(data.repr_hash()));
+
+        // No implicit commit
+        let (exit_code, c4) = run(tvmasm!("NOP"), false);
+        assert_eq!(exit_code, 0);
+        assert!(c4.is_none());
+
+        // Explicit commit is still applied
+        let (exit_code, c4) = run(tvmasm!("NEWC ENDC POP c4 COMMIT"), false);
+        assert_eq!(exit_code, 0);
+        assert_eq!(c4.as_ref(), Some(Cell::empty_cell_ref().repr_hash()));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    #[traced_test]
+    fn sampled_step_tracing() {
+        let code = tvmasm!("INT 1 INT 2 INT 3 INT 4 INT 5 INT 6 INT 7 INT 8 INT 9 INT 10");
+        let mut vm = VmState::builder()
+            .with_code(Boc::decode(code).unwrap())
+            .with_gas(GasParams::getter())
+            .with_modifiers(BehaviourModifiers {
+                log_step_interval: 5,
+                ..Default::default()
+            })
+            .build();
+        assert_eq!(!vm.run(), 0);
+
+        // Only steps 5 and 10 out of 11 are traced
+        logs_assert(|lines: &[&str]| {
+            match lines
+                .iter()
+                .filter(|line| line.contains("execute "))
+                .count()
+            {
+                2 => Ok(()),
+                n => Err(format!("expected 2 traced ops, got {n}")),
+            }
+        });
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    #[traced_test]
+    fn structured_op_tracing() {
+        let code = tvmasm!("INT 5 NEWC ENDC DROP");
+        let mut vm = VmState::builder()
+            .with_code(Boc::decode(code).unwrap())
+            .with_gas(GasParams::getter())
+            .build();
+        assert_eq!(!vm.run(), 0);
+
+        assert!(logs_contain(
+            r#"op result opcode="PUSHINT {x}" instr="PUSHINT 5" gas=18 stack_depth=1 ok=true"#
+        ));
+        // ENDC also pays for the new cell
+        assert!(logs_contain(
+            r#"opcode="ENDC" instr="ENDC" gas=518 stack_depth=2"#
+        ));
+    }
+
+    #[test]
+    #[traced_test]
+    fn get_method_result_decode() -> anyhow::Result<()> {
+        // Method id is pushed on top of the arguments
+        let mut vm = VmState::builder()
+            .with_code(Boc::decode(tvmasm!("NEWC ENDC"))?)
+            .with_init_selector(false)
+            .with_stack(tuple![int 1])
+            .with_gas(GasParams::getter())
+            .build();
+
+        let res = vm.run_get_method(42)?;
+        assert!(res.is_success());
+
+        let (arg, method_id, cell) = res.decode::<(BigInt, BigInt, Cell)>()?;
+        assert_eq!(arg, BigInt::from(1));
+        assert_eq!(method_id, BigInt::from(42));
+        assert_eq!(cell.repr_hash(), Cell::empty_cell_ref().repr_hash());
+
+        let (_, _, cell) = res.decode::<(RcStackValue, RcStackValue, Option<Cell>)>()?;
+        assert!(cell.is_some());
+
+        assert!(matches!(
+            res.decode::<(BigInt,)>().unwrap_err(),
+            StackDecodeError::DepthMismatch {
+                expected: 1,
+                actual: 3
+            }
+        ));
+        match res.decode::<(BigInt, Cell, BigInt)>().unwrap_err() {
+            StackDecodeError::InvalidValue { index, source } => {
+                assert_eq!(index, 1);
+                assert!(matches!(*source, VmError::InvalidType { .. }));
+            }
+            e => panic!("unexpected error: {e:?}"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    #[traced_test]
+    fn run_to_completion_result() -> anyhow::Result<()> {
+        let mut vm = VmState::builder()
+            .with_code(Boc::decode(tvmasm!("INT 1 NEWC ENDC POP c4"))?)
+            .with_gas(GasParams::getter())
+            .build();
+
+        let res = vm.run_to_completion();
+        assert!(res.is_success());
+        assert_eq!(res.steps, vm.steps);
+        assert_eq!(res.gas_used, vm.gas.consumed());
+        assert_eq!(res.stack.depth(), 1);
+        let committed = res.committed.expect("must be committed on success");
+        assert_eq!(committed.c4.repr_hash(), Cell::empty_cell_ref().repr_hash());
+
+        // No automatic commit on failure
+        let mut vm = VmState::builder()
+            .with_code(Boc::decode(tvmasm!("INT 1 THROW 100"))?)
+            .with_gas(GasParams::getter())
+            .build();
+
+        let res = vm.run_to_completion();
+        assert!(!res.is_success());
+        assert_eq!(res.exit_code, 100);
+        assert!(res.committed.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    #[traced_test]
+    fn step_hook_sees_each_step() -> anyhow::Result<()> {
+        let calls = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+
+        let mut vm = VmState::builder()
+            .with_code(Boc::decode(tvmasm!(
+                "INT 1 INT 2 PUSHCONT { ADD } EXECUTE INT 3"
+            ))?)
+            .with_gas(GasParams::getter())
+            .with_step_hook({
+                let calls = calls.clone();
+                move |st: &VmState<'_>| calls.borrow_mut().push((st.steps, st.stack.depth()))
+            })
+            .build();
+
+        assert_eq!(vm.run(), -1);
+
+        let calls = calls.borrow();
+        assert_eq!(calls.len() as u64, vm.steps);
+        assert_eq!(calls.first(), Some(&(1, 0)));
+        assert!(calls
+            .iter()
+            .enumerate()
+            .all(|(i, (n, _))| *n == i as u64 + 1));
+        assert_eq!(calls.iter().map(|(_, depth)| *depth).max(), Some(3));
+
+        Ok(())
+    }
+
+    #[test]
+    #[traced_test]
+    fn custom_gas_prices() -> anyhow::Result<()> {
+        // 4 instructions (8 bits each) and one built cell
+        let code = Boc::decode(tvmasm!("INT 1 NEWC ENDC DROP"))?;
+        let run = |prices: GasPrices| {
+            let mut vm = VmState::builder()
+                .with_code(code.clone())
+                .with_gas(GasParams::getter())
+                .with_gas_prices(prices)
+                .build();
+            assert_eq!(vm.run(), -1);
+            vm.gas.consumed()
+        };
+
+        let default = run(GasPrices::DEFAULT);
+
+        let expensive_cells = run(GasPrices {
+            build_cell: 1000,
+            ..GasPrices::DEFAULT
+        });
+        assert_eq!(expensive_cells - default, 1000 - 500);
+
+        let expensive_ops = run(GasPrices {
+            instruction: 20,
+            instruction_bit: 2,
+            ..GasPrices::DEFAULT
+        });
+        assert_eq!(expensive_ops - default, 4 * ((20 + 8 * 2) - (10 + 8)));
+
+        Ok(())
+    }
+
+    #[test]
+    #[traced_test]
+    fn snapshot_restore() -> anyhow::Result<()> {
+        let mut vm = VmState::builder()
+            .with_code(Boc::decode(tvmasm!("INT 1 INT 2 INT 3 ADD ADD"))?)
+            .with_gas(GasParams::getter())
+            .build();
+
+        for _ in 0..2 {
+            assert_eq!(vm.step().unwrap(), 0);
+        }
+        let snapshot = vm.snapshot();
+        let gas_used = vm.gas.consumed();
+        compare_stack(&vm.stack.items, &tuple![int 1, int 2]);
+
+        for _ in 0..2 {
+            assert_eq!(vm.step().unwrap(), 0);
+        }
+        compare_stack(&vm.stack.items, &tuple![int 1, int 5]);
+
+        vm.restore(snapshot);
+        assert_eq!(vm.steps, 2);
+        assert_eq!(vm.gas.consumed(), gas_used);
+        compare_stack(&vm.stack.items, &tuple![int 1, int 2]);
+
+        // Execution continues from the snapshot point
+        assert_eq!(vm.run(), -1);
+        compare_stack(&vm.stack.items, &tuple![int 6]);
+
+        Ok(())
+    }
+
+    #[test]
+    #[traced_test]
+    fn snapshot_restore_loaded_cells() -> anyhow::Result<()> {
+        let mut vm = VmState::builder()
+            .with_code(Boc::decode(tvmasm!("NEWC ENDC DUP CTOS DROP CTOS DROP"))?)
+            .with_gas(GasParams::getter())
+            .build();
+
+        for _ in 0..2 {
+            assert_eq!(vm.step().unwrap(), 0);
+        }
+        let snapshot = vm.snapshot();
+
+        assert_eq!(vm.run(), -1);
+        let gas_used = vm.gas.consumed();
+
+        // Cells loaded after the snapshot are charged as new again
+        vm.restore(snapshot);
+        assert_eq!(vm.run(), -1);
+        assert_eq!(vm.gas.consumed(), gas_used);
+
+        Ok(())
+    }
+
+    #[test]
+    fn gas_trace() -> anyhow::Result<()> {
+        let code = Boc::decode(tvmasm!("INT 1 INT 2 ADD NEWC ENDC CTOS DROP"))?;
+
+        let events = std::cell::RefCell::new(Vec::<GasEvent>::new());
+        let mut vm = VmState::builder()
+            .with_code(code)
+            .with_gas(GasParams::getter())
+            .build();
+        vm.gas.set_gas_trace(|e| events.borrow_mut().push(e));
+
+        assert_eq!(vm.run(), -1);
+        compare_stack(&vm.stack.items, &tuple![int 3]);
+
+        let events = events.borrow();
+        let traced = events
+            .iter()
+            .map(|e| e.gas_before - e.gas_after)
+            .sum::<u64>();
+        assert_eq!(traced, vm.gas.limit() - vm.gas.remaining());
+        assert_eq!(traced, vm.gas.consumed());
+
+        assert!(events.windows(2).all(|w| w[0].gas_after == w[1].gas_before));
+        assert!(events
+            .iter()
+            .any(|e| e.reason == GasReason::CellBuild && e.opcode_name == Some("ENDC")));
+        assert!(events
+            .iter()
+            .any(|e| e.reason == GasReason::CellLoad && e.opcode_name == Some("CTOS")));
+
+        // Implicit RET has no opcode
+        let last = events.last().unwrap();
+        assert_eq!(last.reason, GasReason::Instruction);
+        assert_eq!(last.opcode_name, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn cell_load_gas_schedule() -> anyhow::Result<()> {
+        // Cell size doesn't affect the load price
+        let leaf = {
+            let mut b = CellBuilder::new();
+            b.store_raw(&[0xaa; 128], 1023)?;
+            b.build()?
+        };
+        let mid = CellBuilder::build_from((0xbbu8, leaf))?;
+        let root = CellBuilder::build_from((0xccu8, mid.clone(), mid))?;
+
+        let code = Boc::decode(tvmasm!(
+            r#"
+            CTOS LDREF SWAP CTOS PLDREF CTOS DROP
+            PLDREF CTOS PLDREF CTOS DROP
+            "#
+        ))?;
+        let mut vm = VmState::builder()
+            .with_code(code)
+            .with_stack(tuple![cell root])
+            .with_gas(GasParams::getter())
+            .build();
+        assert_eq!(vm.run(), -1);
+
+        // NOTE: Expected gas is computed by hand from the TON gas schedule,
+        // it is not taken from a mainnet transaction.
+
+        // CTOS, LDREF, SWAP, DROP
+        let short_ops = 18 * (5 + 1 + 1 + 2);
+        // PLDREF
+        let long_ops = 26 * 3;
+        let implicit_ret = 5;
+        // root, mid and leaf are loaded once for 100,
+        // mid and leaf are reloaded for 25
+        let loads = 3 * 100 + 2 * 25;
+
+        assert_eq!(
+            vm.gas.consumed(),
+            short_ops + long_ops + implicit_ret + loads
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    #[traced_test]
+    fn serialize_state() -> anyhow::Result<()> {
+        let code = Boc::decode(tvmasm!(
+            r#"
+            NEWC ENDC CTOS INT 1 INT 2 PAIR INT 5
+            PUSHCONT { INT 2 MUL INT 3 ADD } CALLX
+            INT 1
+            "#
+        ))?;
+        let mut vm = VmState::builder()
+            .with_code(code)
+            .with_data(CellBuilder::build_from(0xdeadbeefu32)?)
+            .with_gas(GasParams::getter())
+            .build();
+
+        // Stop inside the called continuation
+        for _ in 0..10 {
+            assert_eq!(vm.step().unwrap(), 0);
+        }
+        assert!(vm.cr.c[0].as_ref().unwrap().get_control_data().is_some());
+
+        let serialized = vm.serialize()?;
+        let mut restored = VmState::deserialize(
+            serialized.as_ref(),
+            VmState::builder().with_gas(GasParams::getter()),
+        )?;
+        assert_eq!(restored.serialize()?.repr_hash(), serialized.repr_hash());
+        assert_eq!(restored.cr.d[0], vm.cr.d[0]);
+
+        assert_eq!(vm.run(), -1);
+        assert_eq!(restored.run(), -1);
+        compare_stack(&restored.stack.items, &vm.stack.items);
+        assert_eq!(restored.stack.items[2].as_int(), Some(&BigInt::from(13)));
+
+        // Unknown codepage
+        let mut cont = serialized.parse::<OrdCont>()?;
+        cont.data.cp = Some(0x1234);
+        let cell = CellBuilder::build_from(cont)?;
+        let res = VmState::deserialize(cell.as_ref(), VmState::builder());
+        assert!(res.is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    #[traced_test]
+    fn step_limit() -> anyhow::Result<()> {
+        let code = Boc::decode(tvmasm!("PUSHCONT { INT 1 DROP } AGAIN"))?;
+        let mut vm = VmState::builder()
+            .with_code(code)
+            .with_gas(GasParams::unlimited())
+            .with_step_limit(1000)
+            .build();
+
+        assert_eq!(vm.run(), VmException::Fatal as i32);
+        assert_eq!(vm.steps, 1000);
+        assert!(vm.commited_state.is_none());
+
+        // Limit is not reached
+        let code = Boc::decode(tvmasm!("INT 1 INT 2 ADD"))?;
+        let mut vm = VmState::builder()
+            .with_code(code)
+            .with_gas(GasParams::getter())
+            .with_step_limit(4)
+            .build();
+        assert_eq!(vm.run(), -1);
+        compare_stack(&vm.stack.items, &tuple![int 3]);
+
+        Ok(())
+    }
+
+    #[test]
+    #[traced_test]
+    fn strict_c7() -> anyhow::Result<()> {
+        let code = Boc::decode(tvmasm!("NOW"))?;
+        let build = |info: CustomSmcInfo| {
+            VmState::builder()
+                .with_code(code.clone())
+                .with_smc_info(info)
+                .with_gas(GasParams::getter())
+                .with_strict_c7(true)
+                .try_build()
+        };
+
+        // Well-formed c7
+        let info = SmcInfoBase::new().with_now(123).require_ton_v4();
+        let mut vm = build(CustomSmcInfo {
+            version: VmVersion::Ton(4),
+            c7: info.build_c7(),
+        })?;
+        assert_eq!(vm.run(), -1);
+        compare_stack(&vm.stack.items, &tuple![int 123]);
+
+        // No c7 at all
+        let res = VmState::builder().with_strict_c7(true).try_build();
+        assert!(matches!(res, Err(C7LayoutError::MissingSmcInfo)));
+
+        // Too short for the version
+        let res = build(CustomSmcInfo {
+            version: VmVersion::Ton(6),
+            c7: SmcInfoBase::new().build_c7(),
+        });
+        assert!(matches!(res, Err(C7LayoutError::MissingItem { index: 10 })));
+
+        // Invalid item type
+        let res = build(CustomSmcInfo {
+            version: VmVersion::Ton(1),
+            c7: SafeRc::new(tuple![[int SmcInfoBase::MAGIC, int 0, int 0, null]]),
+        });
+        assert!(matches!(
+            res,
+            Err(C7LayoutError::InvalidItem {
+                index: 3,
+                actual: StackValueType::Null
+            })
+        ));
+
+        // Invalid magic
+        let c7 = SmcInfoBase::new().build_c7();
+        let mut t1 = c7[0].as_tuple().unwrap().to_vec();
+        t1[0] = RcStackValue::new_dyn_value(BigInt::from(1));
+        let res = build(CustomSmcInfo {
+            version: VmVersion::Ton(1),
+            c7: SafeRc::new(tuple![raw SafeRc::new(t1)]),
+        });
+        assert!(matches!(res, Err(C7LayoutError::InvalidMagic)));
+
+        Ok(())
+    }
+
     #[test]
     #[traced_test]
     fn library_cells_works() -> anyhow::Result<()> {
@@ -10,7 +10,7 @@ use sha2::Digest;
 
 use crate::error::VmResult;
 use crate::saferc::{SafeDelete, SafeRc};
-use crate::stack::{RcStackValue, Stack, Tuple};
+use crate::stack::{RcStackValue, Stack, StackValueType, Tuple};
 use crate::util::OwnedCellSlice;
 
 /// Version of a VM context.
@@ -180,6 +180,25 @@ impl SmcInfoBase {
         self
     }
 
+    /// Sets the account address, checking that its workchain
+    /// and account id form a valid internal address.
+    pub fn try_with_account_addr(mut self, addr: IntAddr) -> Result<Self, Error> {
+        if let IntAddr::Var(addr) = &addr {
+            let len = addr.address_len.into_inner() as usize;
+            if addr.address.len() * 8 < len {
+                return Err(Error::InvalidData);
+            }
+
+            // NOTE: Such addresses must be stored as `addr_std`.
+            if len == 256 && i8::try_from(addr.workchain).is_ok() {
+                return Err(Error::InvalidData);
+            }
+        }
+
+        self.addr = addr;
+        Ok(self)
+    }
+
     pub fn with_config(mut self, params: BlockchainConfigParams) -> Self {
         self.config = Some(params);
         self
@@ -571,6 +590,81 @@ impl SmcInfo for CustomSmcInfo {
     }
 }
 
+/// Checks that `c7` contains a well-formed smart contract info
+/// tuple with all items required by the specified VM version.
+pub fn check_c7_layout(c7: &Tuple, version: VmVersion) -> Result<(), C7LayoutError> {
+    use StackValueType as T;
+
+    const BASE_ITEMS: [&[StackValueType]; SmcInfoBase::C7_ITEM_COUNT] = [
+        &[T::Int],           // magic
+        &[T::Int],           // actions
+        &[T::Int],           // msgs_sent
+        &[T::Int],           // unixtime
+        &[T::Int],           // block_lt
+        &[T::Int],           // trans_lt
+        &[T::Int],           // rand_seed
+        &[T::Tuple],         // balance_remaining
+        &[T::Slice],         // myself
+        &[T::Cell, T::Null], // global_config
+    ];
+    const V4_ITEMS: [&[StackValueType]; 4] = [
+        &[T::Cell, T::Null],  // code
+        &[T::Tuple],          // in_msg_value
+        &[T::Int],            // storage_fees
+        &[T::Tuple, T::Null], // prev_blocks_info
+    ];
+    const V6_ITEMS: [&[StackValueType]; 3] = [
+        &[T::Tuple, T::Null], // unpacked_config
+        &[T::Int],            // due_payment
+        &[T::Int, T::Null],   // precompiled_gas_usage
+    ];
+
+    let Some(t1) = c7.first() else {
+        return Err(C7LayoutError::MissingSmcInfo);
+    };
+    let Some(t1) = t1.as_tuple() else {
+        return Err(C7LayoutError::InvalidSmcInfo(t1.ty()));
+    };
+
+    let v4_items: &[_] = if version.is_ton(4..) { &V4_ITEMS } else { &[] };
+    let v6_items: &[_] = if version.is_ton(6..) { &V6_ITEMS } else { &[] };
+    let expected = BASE_ITEMS.iter().chain(v4_items).chain(v6_items);
+
+    for (index, allowed) in expected.enumerate() {
+        let Some(item) = t1.get(index) else {
+            return Err(C7LayoutError::MissingItem { index });
+        };
+        let actual = item.ty();
+        if !allowed.contains(&actual) {
+            return Err(C7LayoutError::InvalidItem { index, actual });
+        }
+    }
+
+    if t1[0].as_int() != Some(&BigInt::from(SmcInfoBase::MAGIC)) {
+        return Err(C7LayoutError::InvalidMagic);
+    }
+
+    Ok(())
+}
+
+/// Invalid `c7` layout.
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum C7LayoutError {
+    #[error("c7 has no smart contract info tuple")]
+    MissingSmcInfo,
+    #[error("smart contract info must be a tuple, found {0:?}")]
+    InvalidSmcInfo(StackValueType),
+    #[error("smart contract info item {index} is missing")]
+    MissingItem { index: usize },
+    #[error("smart contract info item {index} has an invalid type {actual:?}")]
+    InvalidItem {
+        index: usize,
+        actual: StackValueType,
+    },
+    #[error("invalid smart contract info magic")]
+    InvalidMagic,
+}
+
 fn balance_as_tuple(balance: &CurrencyCollection) -> SafeRc<Tuple> {
     SafeRc::new(vec![
         SafeRc::new_dyn_value(BigInt::from(balance.tokens.into_inner())),
@@ -1,3 +1,4 @@
+use std::cell::OnceCell;
 use std::mem::ManuallyDrop;
 use std::rc::Rc;
 
@@ -7,7 +8,8 @@ use num_bigint::BigInt;
 use num_traits::{One, ToPrimitive, Zero};
 
 use crate::cont::{load_cont, Cont, RcCont};
-use crate::error::{VmError, VmResult};
+use crate::error::{StackDecodeError, VmError, VmResult};
+use crate::int257::Int257;
 use crate::saferc::{SafeDelete, SafeRc, SafeRcMakeMut};
 use crate::util::{
     bitsize, ensure_empty_slice, load_int_from_slice, store_int_to_builder, OwnedCellSlice,
@@ -161,6 +163,20 @@ impl Stack {
         self.items.len()
     }
 
+    /// Formats all items from the bottom to the top as `[ a b c ]`.
+    ///
+    /// Items are formatted the same way as in `DUMPSTK`.
+    pub fn dump(&self) -> String {
+        use std::fmt::Write;
+
+        let mut result = String::from("[");
+        for item in &self.items {
+            write!(&mut result, " {}", item.display_list()).unwrap();
+        }
+        result.push_str(" ]");
+        result
+    }
+
     /// Reserves capacity for at least `additional` more elements to be inserted.
     pub fn reserve(&mut self, additional: usize) {
         self.items.reserve(additional);
@@ -173,7 +189,7 @@ impl Stack {
     pub fn push_raw<T: StackValue + ?Sized + 'static>(&mut self, item: SafeRc<T>) -> VmResult<()> {
         vm_ensure!(
             self.depth() < Self::MAX_DEPTH,
-            StackUnderflow(Self::MAX_DEPTH)
+            stack_underflow(Self::MAX_DEPTH)
         );
 
         self.items.push(item.into_dyn_value());
@@ -199,8 +215,11 @@ impl Stack {
 
     pub fn push_nth(&mut self, idx: usize) -> VmResult<()> {
         let depth = self.depth();
-        vm_ensure!(idx < depth, StackUnderflow(idx));
-        vm_ensure!(depth + 1 < Self::MAX_DEPTH, StackUnderflow(Self::MAX_DEPTH));
+        vm_ensure!(idx < depth, stack_underflow(idx + 1));
+        vm_ensure!(
+            depth + 1 < Self::MAX_DEPTH,
+            stack_underflow(Self::MAX_DEPTH)
+        );
         self.items.push(self.items[depth - idx - 1].clone());
         Ok(())
     }
@@ -221,9 +240,8 @@ impl Stack {
         self.push_raw(Self::make_zero())
     }
 
-    pub fn push_int<T: Into<BigInt>>(&mut self, value: T) -> VmResult<()> {
-        // TODO: Inline some numbers as thread-local constants to avoid some allocations
-        self.push(value.into())
+    pub fn push_int<T: IntoStackInt>(&mut self, value: T) -> VmResult<()> {
+        self.push_raw(value.into_stack_int())
     }
 
     pub fn push_raw_int(&mut self, value: SafeRc<BigInt>, quiet: bool) -> VmResult<()> {
@@ -238,24 +256,32 @@ impl Stack {
 
     pub fn move_from_stack(&mut self, other: &mut Self, n: usize) -> VmResult<()> {
         let Some(new_other_len) = other.depth().checked_sub(n) else {
-            vm_bail!(StackUnderflow(n));
+            vm_bail!(stack_underflow(n));
         };
         self.items.extend(other.items.drain(new_other_len..));
         Ok(())
     }
 
-    pub fn split_top(&mut self, n: usize) -> VmResult<SafeRc<Self>> {
+    /// Moves the top `n` items into a new stack, preserving their order.
+    ///
+    /// The remaining items are left untouched.
+    pub fn take_top(&mut self, n: usize) -> VmResult<SafeRc<Self>> {
         let Some(new_depth) = self.depth().checked_sub(n) else {
-            vm_bail!(StackUnderflow(n));
+            vm_bail!(stack_underflow(n));
         };
         Ok(SafeRc::new(Self {
             items: self.items.drain(new_depth..).collect(),
         }))
     }
 
+    #[inline]
+    pub fn split_top(&mut self, n: usize) -> VmResult<SafeRc<Self>> {
+        self.take_top(n)
+    }
+
     pub fn split_top_ext(&mut self, n: usize, drop: usize) -> VmResult<SafeRc<Self>> {
         let Some(new_depth) = self.depth().checked_sub(n + drop) else {
-            vm_bail!(StackUnderflow(n + drop));
+            vm_bail!(stack_underflow(n + drop));
         };
         let res = SafeRc::new(Self {
             items: self.items.drain(new_depth + drop..).collect(),
@@ -266,7 +292,7 @@ impl Stack {
 
     pub fn pop(&mut self) -> VmResult<RcStackValue> {
         let Some(item) = self.items.pop() else {
-            vm_bail!(StackUnderflow(0));
+            vm_bail!(stack_underflow(1));
         };
         Ok(item)
     }
@@ -396,37 +422,47 @@ impl Stack {
 
     pub fn pop_many(&mut self, n: usize) -> VmResult<()> {
         let Some(new_len) = self.depth().checked_sub(n) else {
-            vm_bail!(StackUnderflow(n));
+            vm_bail!(stack_underflow(n));
         };
         self.items.truncate(new_len);
         Ok(())
     }
 
     pub fn drop_bottom(&mut self, n: usize) -> VmResult<()> {
-        vm_ensure!(n <= self.depth(), StackUnderflow(n));
+        vm_ensure!(n <= self.depth(), stack_underflow(n));
         self.items.drain(..n);
         Ok(())
     }
 
     pub fn swap(&mut self, lhs: usize, rhs: usize) -> VmResult<()> {
         let depth = self.depth();
-        vm_ensure!(lhs < depth, StackUnderflow(lhs));
-        vm_ensure!(rhs < depth, StackUnderflow(rhs));
+        vm_ensure!(lhs < depth, stack_underflow(lhs + 1));
+        vm_ensure!(rhs < depth, stack_underflow(rhs + 1));
         self.items.swap(depth - lhs - 1, depth - rhs - 1);
         Ok(())
     }
 
     pub fn reverse_range(&mut self, offset: usize, n: usize) -> VmResult<()> {
         let depth = self.depth();
-        vm_ensure!(offset < depth, StackUnderflow(offset));
-        vm_ensure!(offset + n <= depth, StackUnderflow(offset + n));
+        vm_ensure!(offset < depth, stack_underflow(offset + 1));
+        vm_ensure!(offset + n <= depth, stack_underflow(offset + n));
         self.items[depth - offset - n..depth - offset].reverse();
         Ok(())
     }
 
+    /// Rotates the top `n` values so that the top `k` of them are moved
+    /// below the others (like [`slice::rotate_right`]).
+    pub fn rotate_top(&mut self, n: usize, k: usize) -> VmResult<()> {
+        let depth = self.depth();
+        vm_ensure!(n <= depth, stack_underflow(n));
+        debug_assert!(k <= n);
+        self.items[depth - n..].rotate_right(k);
+        Ok(())
+    }
+
     pub fn fetch(&self, idx: usize) -> VmResult<&RcStackValue> {
         let depth = self.depth();
-        vm_ensure!(idx < depth, StackUnderflow(idx));
+        vm_ensure!(idx < depth, stack_underflow(idx + 1));
         Ok(&self.items[depth - idx - 1])
     }
 
@@ -567,6 +603,11 @@ pub trait StackValue: SafeDelete + std::fmt::Debug {
         Err(invalid_type(self.ty(), StackValueType::Int))
     }
 
+    /// Returns the integer if it is stored inline (see [`InlineInt`]).
+    fn as_int257(&self) -> Option<Int257> {
+        None
+    }
+
     fn as_cell(&self) -> Option<&Cell> {
         None
     }
@@ -896,6 +937,125 @@ impl SafeRcMakeMut for BigInt {
     }
 }
 
+// === Int (inline) ===
+
+/// Integer stack value stored inline as [`Int257`].
+///
+/// Ops without a fast path for inline integers use
+/// a lazily created [`BigInt`] copy of the value.
+#[derive(Debug, Clone)]
+pub struct InlineInt {
+    value: Int257,
+    big: OnceCell<BigInt>,
+}
+
+impl InlineInt {
+    #[inline]
+    pub const fn new(value: Int257) -> Self {
+        Self {
+            value,
+            big: OnceCell::new(),
+        }
+    }
+
+    #[inline]
+    pub const fn value(&self) -> Int257 {
+        self.value
+    }
+
+    fn big(&self) -> &BigInt {
+        self.big.get_or_init(|| BigInt::from(self.value))
+    }
+
+    fn into_big(self) -> BigInt {
+        match self.big.into_inner() {
+            Some(big) => big,
+            None => BigInt::from(self.value),
+        }
+    }
+}
+
+impl StackValue for InlineInt {
+    #[inline]
+    fn rc_into_dyn(self: Rc<Self>) -> Rc<dyn StackValue> {
+        self
+    }
+
+    fn ty(&self) -> StackValueType {
+        StackValueType::Int
+    }
+
+    fn store_as_stack_value(
+        &self,
+        builder: &mut CellBuilder,
+        context: &dyn CellContext,
+    ) -> Result<(), Error> {
+        match self.value.to_i64() {
+            Some(value) => {
+                // vm_stk_tinyint#01 value:int64 = VmStackValue;
+                ok!(builder.store_u8(0x01));
+                builder.store_u64(value as u64)
+            }
+            None => self.big().store_as_stack_value(builder, context),
+        }
+    }
+
+    fn fmt_dump(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(&self.value, f)
+    }
+
+    fn as_int(&self) -> Option<&BigInt> {
+        Some(self.big())
+    }
+
+    fn rc_into_int(self: Rc<Self>) -> VmResult<Rc<BigInt>> {
+        Ok(Rc::new(match Rc::try_unwrap(self) {
+            Ok(value) => value.into_big(),
+            Err(rc) => BigInt::from(rc.value),
+        }))
+    }
+
+    fn as_int257(&self) -> Option<Int257> {
+        Some(self.value)
+    }
+}
+
+impl SafeRcMakeMut for InlineInt {
+    #[inline]
+    fn rc_make_mut(rc: &mut Rc<Self>) -> &mut Self {
+        Rc::make_mut(rc)
+    }
+}
+
+/// Integer which can be pushed to the stack (see [`Stack::push_int`]).
+///
+/// Primitive integers are stored inline, [`BigInt`] is pushed as is.
+pub trait IntoStackInt {
+    fn into_stack_int(self) -> RcStackValue;
+}
+
+impl IntoStackInt for BigInt {
+    #[inline]
+    fn into_stack_int(self) -> RcStackValue {
+        SafeRc::new_dyn_value(self)
+    }
+}
+
+macro_rules! impl_into_stack_int {
+    ($($ty:ty),*$(,)?) => {
+        $(impl IntoStackInt for $ty {
+            #[inline]
+            fn into_stack_int(self) -> RcStackValue {
+                SafeRc::new_dyn_value(InlineInt::new(Int257::from(self)))
+            }
+        })*
+    };
+}
+
+impl_into_stack_int! {
+    Int257, i8, i16, i32, i64, isize, i128, u8, u16, u32, u64, usize, u128
+}
+
 // === Cell ===
 
 impl StackValue for Cell {
@@ -1214,37 +1374,7 @@ impl StackValue for Tuple {
         builder: &mut CellBuilder,
         context: &dyn CellContext,
     ) -> Result<(), Error> {
-        if self.len() > u16::MAX as usize {
-            return Err(Error::IntOverflow);
-        }
-
-        let mut head = None::<Cell>;
-        let mut tail = None::<Cell>;
-
-        for item in self {
-            std::mem::swap(&mut head, &mut tail);
-
-            if tail.is_some() && head.is_some() {
-                if let (Some(t), Some(h)) = (tail.take(), head.take()) {
-                    head = Some(ok!(CellBuilder::build_from_ext((t, h), context)));
-                }
-            }
-
-            let mut builder = CellBuilder::new();
-            ok!(item.store_as_stack_value(&mut builder, context));
-            tail = Some(ok!(builder.build_ext(context)));
-        }
-
-        // vm_stk_tuple#07 len:(## 16) data:(VmTuple len) = VmStackValue;
-        ok!(builder.store_u8(0x07));
-        ok!(builder.store_u16(self.len() as _));
-        if let Some(head) = head {
-            ok!(builder.store_reference(head));
-        }
-        if let Some(tail) = tail {
-            ok!(builder.store_reference(tail));
-        }
-        Ok(())
+        store_tuple(self, builder, context, 0)
     }
 
     fn fmt_dump(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -1271,6 +1401,59 @@ impl StackValue for Tuple {
     }
 }
 
+/// Max nesting level of tuples which can be serialized.
+///
+/// NOTE: Each nested tuple adds at least one cell level,
+/// so deeper tuples would exceed the max cell depth anyway.
+const MAX_TUPLE_DEPTH: u16 = 1024;
+
+// NOTE: Nested tuples are handled explicitly here to limit
+// the recursion depth for maliciously deep values.
+fn store_tuple(
+    items: &[RcStackValue],
+    builder: &mut CellBuilder,
+    context: &dyn CellContext,
+    depth: u16,
+) -> Result<(), Error> {
+    if depth >= MAX_TUPLE_DEPTH {
+        return Err(Error::DepthOverflow);
+    }
+    if items.len() > u16::MAX as usize {
+        return Err(Error::IntOverflow);
+    }
+
+    let mut head = None::<Cell>;
+    let mut tail = None::<Cell>;
+
+    for item in items {
+        std::mem::swap(&mut head, &mut tail);
+
+        if tail.is_some() && head.is_some() {
+            if let (Some(t), Some(h)) = (tail.take(), head.take()) {
+                head = Some(ok!(CellBuilder::build_from_ext((t, h), context)));
+            }
+        }
+
+        let mut builder = CellBuilder::new();
+        match item.as_tuple() {
+            Some(inner) => ok!(store_tuple(inner, &mut builder, context, depth + 1)),
+            None => ok!(item.store_as_stack_value(&mut builder, context)),
+        }
+        tail = Some(ok!(builder.build_ext(context)));
+    }
+
+    // vm_stk_tuple#07 len:(## 16) data:(VmTuple len) = VmStackValue;
+    ok!(builder.store_u8(0x07));
+    ok!(builder.store_u16(items.len() as _));
+    if let Some(head) = head {
+        ok!(builder.store_reference(head));
+    }
+    if let Some(tail) = tail {
+        ok!(builder.store_reference(tail));
+    }
+    Ok(())
+}
+
 impl StaticStackValue for Tuple {
     type DynRef<'a> = &'a [RcStackValue];
 
@@ -1355,6 +1538,89 @@ pub(crate) fn load_slice_as_stack_value(slice: &mut CellSlice) -> Result<OwnedCe
     Ok(OwnedCellSlice::from((cell, range)))
 }
 
+// === FromStack ===
+
+/// A value which can be extracted from a single stack item.
+pub trait FromStackValue: Sized {
+    fn from_stack_value(value: &RcStackValue) -> VmResult<Self>;
+}
+
+impl<T: StaticStackValue + Clone> FromStackValue for T {
+    fn from_stack_value(value: &RcStackValue) -> VmResult<Self> {
+        let value = ok!(T::from_dyn(Rc::clone(&*value.0)));
+        Ok(T::clone(&value))
+    }
+}
+
+impl<T: StaticStackValue + Clone> FromStackValue for Option<T> {
+    fn from_stack_value(value: &RcStackValue) -> VmResult<Self> {
+        if value.is_null() {
+            Ok(None)
+        } else {
+            T::from_stack_value(value).map(Some)
+        }
+    }
+}
+
+impl FromStackValue for RcStackValue {
+    #[inline]
+    fn from_stack_value(value: &RcStackValue) -> VmResult<Self> {
+        Ok(value.clone())
+    }
+}
+
+/// A typed representation of the whole stack.
+///
+/// Values are listed from the bottom of the stack to the top.
+pub trait FromStack: Sized {
+    fn from_stack(stack: &Stack) -> Result<Self, StackDecodeError>;
+}
+
+impl FromStack for () {
+    fn from_stack(stack: &Stack) -> Result<Self, StackDecodeError> {
+        check_stack_depth(stack, 0)
+    }
+}
+
+fn check_stack_depth(stack: &Stack, expected: usize) -> Result<(), StackDecodeError> {
+    if stack.depth() == expected {
+        Ok(())
+    } else {
+        Err(StackDecodeError::DepthMismatch {
+            expected,
+            actual: stack.depth(),
+        })
+    }
+}
+
+fn decode_stack_value<T: FromStackValue>(
+    stack: &Stack,
+    index: usize,
+) -> Result<T, StackDecodeError> {
+    T::from_stack_value(&stack.items[index])
+        .map_err(|source| StackDecodeError::InvalidValue { index, source })
+}
+
+macro_rules! impl_from_stack_for_tuples {
+    ($($n:literal => ($($ty:ident: $idx:tt),+)),*$(,)?) => {$(
+        impl<$($ty: FromStackValue),+> FromStack for ($($ty,)+) {
+            fn from_stack(stack: &Stack) -> Result<Self, StackDecodeError> {
+                ok!(check_stack_depth(stack, $n));
+                Ok(($(ok!(decode_stack_value::<$ty>(stack, $idx)),)+))
+            }
+        }
+    )*};
+}
+
+impl_from_stack_for_tuples! {
+    1 => (T0: 0),
+    2 => (T0: 0, T1: 1),
+    3 => (T0: 0, T1: 1, T2: 2),
+    4 => (T0: 0, T1: 1, T2: 2, T3: 3),
+    5 => (T0: 0, T1: 1, T2: 2, T3: 3, T4: 4),
+    6 => (T0: 0, T1: 1, T2: 2, T3: 3, T4: 4, T5: 5),
+}
+
 // === SafeRc ===
 
 impl RcStackValue {
@@ -1432,12 +1698,263 @@ macro_rules! impl_safe_delete {
 }
 
 impl_safe_delete! {
-    (), NaN, BigInt, Cell, OwnedCellSlice, CellBuilder, Tuple
+    (), NaN, BigInt, InlineInt, Cell, OwnedCellSlice, CellBuilder, Tuple
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cont::QuitCont;
+
+    #[test]
+    fn dump_works() {
+        assert_eq!(Stack::default().dump(), "[ ]");
+
+        let cell = Cell::empty_cell();
+        let int = |i: i32| SafeRc::new_dyn_value(BigInt::from(i));
+        let pair = |a: RcStackValue, b: RcStackValue| SafeRc::new_dyn_value(vec![a, b]);
+
+        let stack = Stack::with_items(vec![
+            int(123),
+            int(-5),
+            Stack::make_nan(),
+            Stack::make_null(),
+            SafeRc::new_dyn_value(vec![int(1), int(2), SafeRc::new_dyn_value(Tuple::new())]),
+            pair(int(1), pair(int(2), Stack::make_null())),
+            SafeRc::new_dyn_value(cell.clone()),
+            SafeRc::new_dyn_value(OwnedCellSlice::new_allow_exotic(cell.clone())),
+        ]);
+
+        let hash = cell.repr_hash();
+        assert_eq!(
+            stack.dump(),
+            format!(
+                "[ 123 -5 NaN () [1 2 []] (1 2) C{{{hash}}} \
+                CS{{Cell {{{hash}}} bits: 0..0; refs: 0..0}} ]"
+            )
+        );
+    }
+
+    #[test]
+    fn take_top_works() {
+        let mut stack = (0..5)
+            .map(|i| SafeRc::new_dyn_value(BigInt::from(i)))
+            .collect::<Stack>();
+
+        let top = stack.take_top(2).unwrap();
+        let ints = |stack: &Stack| {
+            stack
+                .items
+                .iter()
+                .map(|item| item.try_as_int().unwrap().clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ints(&top), [BigInt::from(3), BigInt::from(4)]);
+        assert_eq!(ints(&stack), [
+            BigInt::from(0),
+            BigInt::from(1),
+            BigInt::from(2)
+        ]);
+
+        assert!(stack.take_top(4).is_err());
+        assert_eq!(stack.depth(), 3);
+
+        let top = stack.take_top(0).unwrap();
+        assert_eq!(top.depth(), 0);
+        assert_eq!(stack.depth(), 3);
+    }
+
+    #[test]
+    fn rotate_top_works() {
+        let mut stack = (0..5)
+            .map(|i| SafeRc::new_dyn_value(BigInt::from(i)))
+            .collect::<Stack>();
+        let ints = |stack: &Stack| {
+            stack
+                .items
+                .iter()
+                .map(|item| item.try_as_int().unwrap().to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+
+        stack.rotate_top(3, 1).unwrap();
+        assert_eq!(ints(&stack), "0 1 4 2 3");
+        stack.rotate_top(5, 2).unwrap();
+        assert_eq!(ints(&stack), "2 3 0 1 4");
+        stack.rotate_top(5, 0).unwrap();
+        stack.rotate_top(0, 0).unwrap();
+        assert_eq!(ints(&stack), "2 3 0 1 4");
+
+        assert!(stack.rotate_top(6, 1).is_err());
+        assert_eq!(ints(&stack), "2 3 0 1 4");
+    }
+
+    #[test]
+    fn typed_pops_work() {
+        let mut stack = Stack::with_items(tuple![
+            raw SafeRc::new_dyn_value(QuitCont { exit_code: 0 }),
+            [int 1, int 2],
+            builder CellBuilder::new(),
+            slice OwnedCellSlice::new_allow_exotic(Cell::empty_cell()),
+            cell Cell::empty_cell(),
+            int 0,
+            int -1,
+        ]);
+
+        assert!(stack.pop_bool().unwrap());
+        assert!(!stack.pop_bool().unwrap());
+        let cell = stack.pop_cell().unwrap();
+        assert_eq!(cell.repr_hash(), Cell::empty_cell().repr_hash());
+        assert_eq!(stack.pop_cs().unwrap().range().size_bits(), 0);
+        assert_eq!(stack.pop_builder().unwrap().size_bits(), 0);
+        assert_eq!(stack.pop_tuple().unwrap().len(), 2);
+        assert!(stack.pop_cont().is_ok());
+
+        let err = stack.pop_cell().unwrap_err();
+        assert!(matches!(*err, VmError::StackUnderflow { required: 1, .. }));
+
+        // Wrong types are consumed and reported
+        type PopFn = fn(&mut Stack) -> VmResult<()>;
+        let pops: [(StackValueType, PopFn); 6] = [
+            (StackValueType::Int, |s| s.pop_bool().map(|_| ())),
+            (StackValueType::Cell, |s| s.pop_cell().map(|_| ())),
+            (StackValueType::Slice, |s| s.pop_cs().map(|_| ())),
+            (StackValueType::Builder, |s| s.pop_builder().map(|_| ())),
+            (StackValueType::Tuple, |s| s.pop_tuple().map(|_| ())),
+            (StackValueType::Cont, |s| s.pop_cont().map(|_| ())),
+        ];
+        for (ty, pop) in pops {
+            let mut stack = Stack::with_items(tuple![null]);
+            let err = pop(&mut stack).unwrap_err();
+            assert!(
+                matches!(
+                    *err,
+                    VmError::InvalidType { expected, actual: StackValueType::Null }
+                        if expected == ty
+                ),
+                "unexpected error for {ty:?}: {err:?}"
+            );
+            assert_eq!(stack.depth(), 0);
+        }
+    }
+
+    #[test]
+    fn tuple_serialization_is_deterministic() {
+        fn make_value() -> RcStackValue {
+            SafeRc::new_dyn_value(tuple![
+                int 1,
+                [int 2, [int 3, null], nan],
+                raw SafeRc::new_dyn_value(Cell::default()),
+                [],
+                int -4,
+            ])
+        }
+
+        fn serialize(value: &RcStackValue) -> Cell {
+            let mut b = CellBuilder::new();
+            value
+                .store_as_stack_value(&mut b, Cell::empty_context())
+                .unwrap();
+            b.build().unwrap()
+        }
+
+        let first = serialize(&make_value());
+        for _ in 0..10 {
+            let cell = serialize(&make_value());
+            assert_eq!(cell.repr_hash(), first.repr_hash());
+            assert_eq!(Boc::encode(&cell), Boc::encode(&first));
+        }
+
+        // Items must keep their order after a roundtrip
+        let parsed = Stack::load_stack_value_from_cell(first.as_ref()).unwrap();
+        assert_eq!(
+            format!("{}", parsed.display_list()),
+            format!("{}", make_value().display_list())
+        );
+        assert_eq!(serialize(&parsed).repr_hash(), first.repr_hash());
+
+        // Same for the whole stack
+        let stack = Stack::with_items(vec![make_value(), Stack::make_null(), make_value()]);
+        let first = CellBuilder::build_from(&stack).unwrap();
+        for _ in 0..10 {
+            let cell = CellBuilder::build_from(&stack).unwrap();
+            assert_eq!(cell.repr_hash(), first.repr_hash());
+        }
+
+        let parsed = first.parse::<Stack>().unwrap();
+        let cell = CellBuilder::build_from(&parsed).unwrap();
+        assert_eq!(cell.repr_hash(), first.repr_hash());
+    }
+
+    #[test]
+    fn inline_int_works() {
+        let mut stack = Stack::default();
+        stack.push_int(5).unwrap();
+        stack.push_int(BigInt::from(5)).unwrap();
+        assert_eq!(stack.items[0].as_int257(), Some(Int257::from(5)));
+        assert_eq!(stack.items[1].as_int257(), None);
+
+        // Fallback to BigInt
+        assert_eq!(*stack.pop_int().unwrap(), BigInt::from(5));
+        assert_eq!(*stack.pop_int().unwrap(), BigInt::from(5));
+
+        // Inline integers are serialized the same way as BigInt
+        let serialize = |value: RcStackValue| {
+            let mut b = CellBuilder::new();
+            value
+                .store_as_stack_value(&mut b, Cell::empty_context())
+                .unwrap();
+            b.build().unwrap()
+        };
+        for value in [
+            Int257::ZERO,
+            Int257::from(i64::MIN),
+            Int257::from(u64::MAX),
+            Int257::MAX,
+            Int257::MIN,
+        ] {
+            let inline = serialize(SafeRc::new_dyn_value(InlineInt::new(value)));
+            let big = serialize(SafeRc::new_dyn_value(BigInt::from(value)));
+            assert_eq!(inline, big);
+            assert_eq!(
+                format!(
+                    "{}",
+                    Stack::load_stack_value_from_cell(inline.as_ref())
+                        .unwrap()
+                        .display_list()
+                ),
+                value.to_string()
+            );
+        }
+    }
+
+    #[test]
+    fn deep_tuple_serialization_fails_cleanly() {
+        fn make_nested(depth: usize) -> RcStackValue {
+            let mut value = SafeRc::new_dyn_value(tuple![int 1]);
+            for _ in 1..depth {
+                value = SafeRc::new_dyn_value(vec![value]);
+            }
+            value
+        }
+
+        fn serialize(value: &RcStackValue) -> Result<Cell, Error> {
+            let mut b = CellBuilder::new();
+            ok!(value.store_as_stack_value(&mut b, Cell::empty_context()));
+            b.build()
+        }
+
+        let value = make_nested(100);
+        let cell = serialize(&value).unwrap();
+        let parsed = Stack::load_stack_value_from_cell(cell.as_ref()).unwrap();
+        assert_eq!(serialize(&parsed).unwrap().repr_hash(), cell.repr_hash());
+
+        for depth in [MAX_TUPLE_DEPTH as usize + 1, 1_000_000] {
+            let value = make_nested(depth);
+            assert!(matches!(serialize(&value), Err(Error::DepthOverflow)));
+        }
+    }
 
     #[test]
     fn stack_store_load_works() {
@@ -3,20 +3,18 @@ use bitflags::bitflags;
 use everscale_types::cell::*;
 use everscale_types::error::Error;
 use num_bigint::BigInt;
-#[cfg(feature = "tracing")]
-use tracing::instrument;
 
 use crate::cont::{
     AgainCont, ArgContExt, ControlData, ControlRegs, ExcQuitCont, OrdCont, QuitCont, RcCont,
     RepeatCont, UntilCont, WhileCont,
 };
 use crate::dispatch::DispatchTable;
-use crate::error::{VmException, VmResult};
-use crate::gas::{GasConsumer, GasParams, LibraryProvider, NoLibraries};
+use crate::error::{CodepageError, StackDecodeError, VmError, VmException, VmResult};
+use crate::gas::{GasConsumer, GasParams, GasPrices, GasSnapshot, LibraryProvider, NoLibraries};
 use crate::instr::{codepage, codepage0};
 use crate::saferc::SafeRc;
-use crate::smc_info::{SmcInfo, VmVersion};
-use crate::stack::{RcStackValue, Stack};
+use crate::smc_info::{check_c7_layout, C7LayoutError, SmcInfo, VmVersion};
+use crate::stack::{FromStack, RcStackValue, Stack};
 use crate::util::OwnedCellSlice;
 
 /// Execution state builder.
@@ -28,10 +26,15 @@ pub struct VmStateBuilder<'a> {
     pub libraries: Option<&'a dyn LibraryProvider>,
     pub c7: Option<SafeRc<Vec<RcStackValue>>>,
     pub gas: GasParams,
+    pub gas_prices: GasPrices,
     pub init_selector: InitSelectorParams,
     pub version: Option<VmVersion>,
     pub modifiers: BehaviourModifiers,
     pub debug: Option<&'a mut dyn std::fmt::Write>,
+    pub on_step: Option<StepHook<'a>>,
+    pub step_limit: Option<u64>,
+    pub strict_c7: bool,
+    pub codepages: &'a [DispatchTable],
 }
 
 impl<'a> VmStateBuilder<'a> {
@@ -39,13 +42,47 @@ impl<'a> VmStateBuilder<'a> {
         Self::default()
     }
 
-    pub fn build(mut self) -> VmState<'a> {
+    /// Builds the execution state.
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`strict_c7`] is enabled and `c7` has an invalid layout.
+    /// Use [`try_build`] to handle this case.
+    ///
+    /// [`strict_c7`]: Self::with_strict_c7
+    /// [`try_build`]: Self::try_build
+    pub fn build(self) -> VmState<'a> {
+        match self.try_build() {
+            Ok(state) => state,
+            Err(e) => panic!("invalid c7: {e}"),
+        }
+    }
+
+    /// Builds the execution state, checking the `c7` layout if [`strict_c7`] is enabled.
+    ///
+    /// [`strict_c7`]: Self::with_strict_c7
+    pub fn try_build(self) -> Result<VmState<'a>, C7LayoutError> {
+        if self.strict_c7 {
+            let version = self.version.unwrap_or(VmState::DEFAULT_VERSION);
+            match &self.c7 {
+                Some(c7) => ok!(check_c7_layout(c7, version)),
+                None => return Err(C7LayoutError::MissingSmcInfo),
+            }
+        }
+        Ok(self.build_unchecked())
+    }
+
+    fn build_unchecked(mut self) -> VmState<'a> {
         static NO_LIBRARIES: NoLibraries = NoLibraries;
 
         let quit0 = QUIT0.with(SafeRc::clone);
         let quit1 = QUIT1.with(SafeRc::clone);
         let cp = codepage0();
 
+        let mut gas =
+            GasConsumer::with_libraries(self.gas, self.libraries.unwrap_or(&NO_LIBRARIES));
+        gas.set_prices(self.gas_prices);
+
         let (code, throw_on_code_access) = match self.code {
             Some(code) => (code, false),
             None => (Default::default(), true),
@@ -85,11 +122,16 @@ impl<'a> VmStateBuilder<'a> {
             steps: 0,
             quit0,
             quit1,
-            gas: GasConsumer::with_libraries(self.gas, self.libraries.unwrap_or(&NO_LIBRARIES)),
+            gas,
             cp,
+            codepages: self.codepages,
             debug: self.debug,
+            on_step: self.on_step,
+            step_limit: self.step_limit,
             modifiers: self.modifiers,
             version: self.version.unwrap_or(VmState::DEFAULT_VERSION),
+            #[cfg(feature = "tracing")]
+            log_ops: true,
         }
     }
 
@@ -103,11 +145,54 @@ impl<'a> VmStateBuilder<'a> {
         self
     }
 
+    /// Sets gas prices of basic operations (TON prices by default).
+    pub fn with_gas_prices(mut self, prices: GasPrices) -> Self {
+        self.gas_prices = prices;
+        self
+    }
+
     pub fn with_debug<T: std::fmt::Write>(mut self, stderr: &'a mut T) -> Self {
         self.debug = Some(stderr);
         self
     }
 
+    /// Sets a callback which is called before each step.
+    pub fn with_step_hook<F>(mut self, hook: F) -> Self
+    where
+        F: FnMut(&VmState<'a>) + 'a,
+    {
+        self.on_step = Some(Box::new(hook));
+        self
+    }
+
+    /// Limits the number of executed steps regardless of gas.
+    ///
+    /// Execution is aborted with a fatal error when the limit is reached.
+    pub fn with_step_limit(mut self, limit: u64) -> Self {
+        self.step_limit = Some(limit);
+        self
+    }
+
+    /// Sets additional codepages which can be selected by their id
+    /// (e.g. with `SETCP`).
+    ///
+    /// Fails if some codepage has id 0 (which is always the default one)
+    /// or if ids are not unique.
+    pub fn with_codepages(mut self, codepages: &'a [DispatchTable]) -> Result<Self, CodepageError> {
+        for (i, table) in codepages.iter().enumerate() {
+            let id = table.id();
+            if id == 0 {
+                return Err(CodepageError::ReservedId);
+            }
+            if codepages[..i].iter().any(|other| other.id() == id) {
+                return Err(CodepageError::DuplicateId(id));
+            }
+        }
+
+        self.codepages = codepages;
+        Ok(self)
+    }
+
     pub fn with_code<T: IntoCode>(mut self, code: T) -> Self {
         self.code = code.into_code().ok();
         self
@@ -150,6 +235,13 @@ impl<'a> VmStateBuilder<'a> {
         self.version = Some(version);
         self
     }
+
+    /// Validates the `c7` layout at build time instead of failing
+    /// on the first access to an invalid smart contract info item.
+    pub fn with_strict_c7(mut self, strict: bool) -> Self {
+        self.strict_c7 = strict;
+        self
+    }
 }
 
 /// Anything that can be used as a VM code source.
@@ -218,12 +310,27 @@ pub struct VmState<'a> {
     pub quit0: SafeRc<QuitCont>,
     pub quit1: SafeRc<QuitCont>,
     pub gas: GasConsumer<'a>,
-    pub cp: &'static DispatchTable,
+    pub cp: &'a DispatchTable,
+    /// Additional codepages (see [`VmStateBuilder::with_codepages`]).
+    pub codepages: &'a [DispatchTable],
     pub debug: Option<&'a mut dyn std::fmt::Write>,
+    pub on_step: Option<StepHook<'a>>,
+    /// Max number of steps (see [`VmStateBuilder::with_step_limit`]).
+    pub step_limit: Option<u64>,
     pub modifiers: BehaviourModifiers,
     pub version: VmVersion,
+    /// Whether ops of the current step are logged
+    /// (see [`BehaviourModifiers::log_step_interval`]).
+    #[cfg(feature = "tracing")]
+    pub(crate) log_ops: bool,
 }
 
+/// A callback which is called before each VM step.
+///
+/// Receives the state with the already incremented step counter,
+/// but before the current instruction is dispatched.
+pub type StepHook<'a> = Box<dyn FnMut(&VmState<'a>) + 'a>;
+
 impl<'a> VmState<'a> {
     pub const DEFAULT_VERSION: VmVersion = VmVersion::LATEST_TON;
 
@@ -237,21 +344,44 @@ impl<'a> VmState<'a> {
         VmStateBuilder::default()
     }
 
-    #[cfg_attr(
-        feature = "tracing",
-        instrument(
-            level = "trace",
-            name = "vm_step",
-            fields(n = self.steps),
-            skip_all,
-        )
-    )]
     pub fn step(&mut self) -> VmResult<i32> {
+        if let Some(limit) = self.step_limit {
+            vm_ensure!(self.steps < limit, StepLimitExceeded(limit));
+        }
+
+        #[cfg(feature = "tracing")]
+        let _span = {
+            let interval = self.modifiers.log_step_interval as u64;
+            let sampled = interval <= 1 || (self.steps + 1) % interval == 0;
+            self.log_ops = sampled;
+            sampled.then(|| tracing::trace_span!("vm_step", n = self.steps).entered())
+        };
+
         self.steps += 1;
+        if let Some(mut hook) = self.on_step.take() {
+            // NOTE: Temporarily take the hook to pass the state immutably.
+            hook(self);
+            self.on_step = Some(hook);
+        }
+
+        if self.gas.has_gas_trace() {
+            let cp = self.cp;
+            let name = (!self.code.range().is_data_empty()).then(|| {
+                let (opcode, _) = DispatchTable::get_opcode_from_slice(&self.code.apply());
+                cp.lookup(opcode).name()
+            });
+            self.gas.set_trace_context(self.steps, name);
+        }
+
         if !self.code.range().is_data_empty() {
+            #[cfg(feature = "tracing")]
+            if _span.is_some() && tracing::enabled!(tracing::Level::TRACE) {
+                return self.dispatch_traced();
+            }
+
             self.cp.dispatch(self)
         } else if !self.code.range().is_refs_empty() {
-            vm_log_op!("implicit JMPREF");
+            vm_log_op!(self, "implicit JMPREF");
 
             let next_cell = self.code.apply().get_reference_cloned(0)?;
 
@@ -261,13 +391,39 @@ impl<'a> VmState<'a> {
             let cont = SafeRc::from(OrdCont::simple(code, self.cp.id()));
             self.jump(cont)
         } else {
-            vm_log_op!("implicit RET");
+            vm_log_op!(self, "implicit RET");
 
             self.gas.try_consume_implicit_ret_gas()?;
             self.ret()
         }
     }
 
+    /// Executes the next opcode and logs its effects as structured fields.
+    #[cfg(feature = "tracing")]
+    fn dispatch_traced(&mut self) -> VmResult<i32> {
+        let cp = self.cp;
+        let (op, opcode, bits, instr) = {
+            let mut code = self.code.apply();
+            let (opcode, bits) = DispatchTable::get_opcode_from_slice(&code);
+            let op = cp.lookup(opcode);
+            let instr = op.dump(&mut code, opcode, bits);
+            (op, opcode, bits, instr)
+        };
+
+        let gas_before = self.gas.consumed();
+        let res = DispatchTable::dispatch_op(op, self, opcode, bits);
+
+        tracing::trace!(
+            opcode = op.name(),
+            instr = instr.as_deref().unwrap_or("<invalid>"),
+            gas = self.gas.consumed().saturating_sub(gas_before),
+            stack_depth = self.stack.depth(),
+            ok = res.is_ok(),
+            "op result"
+        );
+        res
+    }
+
     pub fn run(&mut self) -> i32 {
         if self.throw_on_code_access {
             // No negation for unhandled exceptions (to make their faking impossible).
@@ -282,6 +438,11 @@ impl<'a> VmState<'a> {
                     self.steps += 1;
                     self.throw_out_of_gas()
                 }
+                Err(e) if matches!(*e, VmError::StepLimitExceeded(_)) => {
+                    vm_log_trace!(steps = self.steps, "step limit exceeded");
+                    // No negation for unhandled exceptions (to make their faking impossible).
+                    return VmException::Fatal as u8 as i32;
+                }
                 Err(e) => {
                     let exception = e.as_exception();
                     vm_log_trace!(e = ?exception, "handling exception: {e:?}");
@@ -303,7 +464,7 @@ impl<'a> VmState<'a> {
         }
 
         // Try commit on ~(0) and ~(-1) exit codes
-        if res | 1 == -1 && !self.try_commit() {
+        if self.modifiers.auto_commit && res | 1 == -1 && !self.try_commit() {
             vm_log_trace!("automatic commit failed");
             self.stack = SafeRc::new(Stack {
                 items: vec![Stack::make_zero()],
@@ -314,6 +475,125 @@ impl<'a> VmState<'a> {
         res
     }
 
+    /// Pushes the method id on top of the arguments and runs the get-method.
+    ///
+    /// The state is expected to be built with the code as `c3`
+    /// (see [`VmStateBuilder::with_init_selector`]) without the implicit `0`.
+    pub fn run_get_method(&mut self, method_id: i32) -> VmResult<VmRunResult> {
+        ok!(SafeRc::make_mut(&mut self.stack).push_int(method_id));
+        Ok(self.run_to_completion())
+    }
+
+    /// Runs the VM and collects all execution effects into a single result.
+    ///
+    /// Automatic commit on `~0` and `~-1` exit codes is applied by [`run`],
+    /// so `committed` contains either that state or the last explicit `COMMIT`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use everscale_asm_macros::tvmasm;
+    /// use everscale_types::boc::Boc;
+    /// use num_bigint::BigInt;
+    /// use tycho_vm::{GasParams, SmcInfoBase, VmState};
+    ///
+    /// let code = Boc::decode(tvmasm!("INT 2 INT 3 ADD")).unwrap();
+    ///
+    /// let mut vm = VmState::builder()
+    ///     .with_smc_info(SmcInfoBase::new())
+    ///     .with_code(code)
+    ///     .with_gas(GasParams::getter())
+    ///     .build();
+    ///
+    /// let result = vm.run_to_completion();
+    /// assert!(result.is_success());
+    /// assert!(result.committed.is_some());
+    /// assert_eq!(result.stack.depth(), 1);
+    /// assert_eq!(result.stack.items[0].as_int(), Some(&BigInt::from(5)));
+    /// ```
+    ///
+    /// [`run`]: Self::run
+    pub fn run_to_completion(&mut self) -> VmRunResult {
+        let exit_code = !self.run();
+        VmRunResult {
+            exit_code,
+            committed: self.commited_state.clone(),
+            steps: self.steps,
+            gas_used: self.gas.consumed(),
+            stack: self.stack.clone(),
+        }
+    }
+
+    /// Captures the current state for speculative execution.
+    ///
+    /// The snapshot shares the stack and continuations with the state,
+    /// only the set of loaded cells is copied.
+    pub fn snapshot(&self) -> VmSnapshot<'a> {
+        VmSnapshot {
+            code: self.code.clone(),
+            stack: self.stack.clone(),
+            cr: self.cr.clone(),
+            commited_state: self.commited_state.clone(),
+            steps: self.steps,
+            gas: self.gas.snapshot(),
+            cp: self.cp,
+        }
+    }
+
+    /// Rewinds the state to the snapshot.
+    pub fn restore(&mut self, snapshot: VmSnapshot<'a>) {
+        self.code = snapshot.code;
+        self.stack = snapshot.stack;
+        self.cr = snapshot.cr;
+        self.commited_state = snapshot.commited_state;
+        self.steps = snapshot.steps;
+        self.gas.restore(&snapshot.gas);
+        self.cp = snapshot.cp;
+    }
+
+    /// Serializes the current execution point as an ordinary continuation.
+    ///
+    /// The continuation contains the remaining code, the codepage, the stack
+    /// and all control registers. Jumping to it resumes the execution.
+    ///
+    /// Fails if the state is too big or too deep to fit into cells.
+    pub fn serialize(&self) -> Result<Cell, Error> {
+        let cont = OrdCont {
+            data: ControlData {
+                nargs: None,
+                stack: Some(self.stack.clone()),
+                save: self.cr.clone(),
+                cp: Some(self.cp.id()),
+            },
+            code: self.code.clone(),
+        };
+        CellBuilder::build_from(cont)
+    }
+
+    /// Restores the state serialized by [`serialize`].
+    ///
+    /// All other params (gas, libraries, version, etc.) are taken
+    /// from the builder. Control registers which are not present
+    /// in the continuation are also initialized by the builder.
+    ///
+    /// [`serialize`]: Self::serialize
+    pub fn deserialize(cell: &DynCell, builder: VmStateBuilder<'a>) -> Result<Self, Error> {
+        let cont = ok!(cell.parse::<OrdCont>());
+
+        let mut state = builder.build_unchecked();
+        let Some(cp) = cont.data.cp.and_then(|cp| state.codepage(cp)) else {
+            return Err(Error::InvalidData);
+        };
+        state.code = cont.code;
+        state.throw_on_code_access = false;
+        state.cp = cp;
+        state.cr.merge(&cont.data.save);
+        if let Some(stack) = cont.data.stack {
+            state.stack = stack;
+        }
+        Ok(state)
+    }
+
     pub fn try_commit(&mut self) -> bool {
         if let (Some(c4), Some(c5)) = (&self.cr.d[0], &self.cr.d[1]) {
             if c4.level() == 0
@@ -344,8 +624,23 @@ impl<'a> VmState<'a> {
         std::mem::replace(&mut self.stack, Self::EMPTY_STACK.with(SafeRc::clone))
     }
 
+    /// Loads the cell as a slice and charges gas for it.
+    ///
+    /// With [`BehaviourModifiers::limit_load_depth`] set, cells deeper
+    /// than [`MAX_DATA_DEPTH`] are rejected with a cell overflow.
+    ///
+    /// [`MAX_DATA_DEPTH`]: Self::MAX_DATA_DEPTH
+    pub fn load_cell_as_slice(&self, cell: Cell, mode: LoadMode) -> VmResult<OwnedCellSlice> {
+        let cs = self.gas.load_cell_as_slice(cell, mode)?;
+        vm_ensure!(
+            !self.modifiers.limit_load_depth || cs.cell().repr_depth() <= Self::MAX_DATA_DEPTH,
+            CellError(Error::CellOverflow)
+        );
+        Ok(cs)
+    }
+
     pub fn ref_to_cont(&mut self, code: Cell) -> VmResult<RcCont> {
-        let code = self.gas.load_cell_as_slice(code, LoadMode::Full)?;
+        let code = ok!(self.load_cell_as_slice(code, LoadMode::Full));
         Ok(SafeRc::from(OrdCont::simple(code, self.cp.id())))
     }
 
@@ -521,7 +816,7 @@ impl<'a> VmState<'a> {
             vm_ensure!(
                 pass_args.unwrap_or_default() as usize <= current_depth
                     && control_data.nargs.unwrap_or_default() as usize <= current_depth,
-                StackUnderflow(std::cmp::max(
+                stack_underflow(std::cmp::max(
                     pass_args.unwrap_or_default(),
                     control_data.nargs.unwrap_or_default()
                 ) as _)
@@ -530,7 +825,7 @@ impl<'a> VmState<'a> {
             if let Some(pass_args) = pass_args {
                 vm_ensure!(
                     control_data.nargs.unwrap_or_default() <= pass_args,
-                    StackUnderflow(pass_args as _)
+                    stack_underflow(pass_args as _)
                 );
             }
 
@@ -654,7 +949,7 @@ impl<'a> VmState<'a> {
             vm_ensure!(
                 pass_args.unwrap_or_default() as usize <= current_depth
                     && control_data.nargs.unwrap_or_default() as usize <= current_depth,
-                StackUnderflow(std::cmp::max(
+                stack_underflow(std::cmp::max(
                     pass_args.unwrap_or_default(),
                     control_data.nargs.unwrap_or_default()
                 ) as usize)
@@ -663,7 +958,7 @@ impl<'a> VmState<'a> {
             if let Some(pass_args) = pass_args {
                 vm_ensure!(
                     control_data.nargs.unwrap_or_default() <= pass_args,
-                    StackUnderflow(pass_args as usize)
+                    stack_underflow(pass_args as usize)
                 );
             }
 
@@ -708,7 +1003,7 @@ impl<'a> VmState<'a> {
         } else if let Some(pass_args) = pass_args {
             // Try to leave only `pass_args` number of arguments in the current stack
             let Some(depth_diff) = self.stack.depth().checked_sub(pass_args as _) else {
-                vm_bail!(StackUnderflow(pass_args as _));
+                vm_bail!(stack_underflow(pass_args as _));
             };
 
             if depth_diff > 0 {
@@ -821,13 +1116,21 @@ impl<'a> VmState<'a> {
     }
 
     pub fn force_cp(&mut self, cp: u16) -> VmResult<()> {
-        let Some(cp) = codepage(cp) else {
+        let Some(cp) = self.codepage(cp) else {
             vm_bail!(InvalidOpcode);
         };
         self.cp = cp;
         Ok(())
     }
 
+    /// Resolves the default or one of the additional codepages.
+    pub fn codepage(&self, cp: u16) -> Option<&'a DispatchTable> {
+        match codepage(cp) {
+            Some(cp) => Some(cp),
+            None => self.codepages.iter().find(|table| table.id() == cp),
+        }
+    }
+
     fn take_c0(&mut self) -> VmResult<RcCont> {
         let Some(cont) =
             std::mem::replace(&mut self.cr.c[0], Some(self.quit0.clone().into_dyn_cont()))
@@ -848,14 +1151,38 @@ impl<'a> VmState<'a> {
 }
 
 /// Falgs to control VM behaviour.
-#[derive(Default, Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy)]
 pub struct BehaviourModifiers {
     pub stop_on_accept: bool,
     pub chksig_always_succeed: bool,
     pub signature_with_id: Option<i32>,
+    /// Whether to commit the state when VM exits with `0` or `-1`.
+    pub auto_commit: bool,
+    /// Trace only every N-th step (`0` or `1` to trace all steps).
+    /// Exceptions are always traced.
+    pub log_step_interval: u32,
+    /// Whether to reject loading cells deeper than [`VmState::MAX_DATA_DEPTH`].
+    ///
+    /// Disabled by default since TON doesn't limit the depth of loaded cells.
+    pub limit_load_depth: bool,
+}
+
+impl Default for BehaviourModifiers {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            stop_on_accept: false,
+            chksig_always_succeed: false,
+            signature_with_id: None,
+            auto_commit: true,
+            log_step_interval: 0,
+            limit_load_depth: false,
+        }
+    }
 }
 
 /// Execution effects.
+#[derive(Clone)]
 pub struct CommitedState {
     /// Contract data.
     pub c4: Cell,
@@ -863,6 +1190,46 @@ pub struct CommitedState {
     pub c5: Cell,
 }
 
+/// Full VM execution result.
+pub struct VmRunResult {
+    /// Non-negated exit code.
+    pub exit_code: i32,
+    /// Committed contract state, if any.
+    pub committed: Option<CommitedState>,
+    /// Total number of executed steps.
+    pub steps: u64,
+    /// Total gas consumed.
+    pub gas_used: u64,
+    /// Resulting stack.
+    pub stack: SafeRc<Stack>,
+}
+
+impl VmRunResult {
+    /// Returns `true` if the VM exited with code 0 or 1.
+    pub fn is_success(&self) -> bool {
+        self.exit_code == 0 || self.exit_code == 1
+    }
+
+    /// Decodes the resulting stack into a typed value.
+    ///
+    /// Fails if the stack depth or any value type doesn't match.
+    pub fn decode<T: FromStack>(&self) -> Result<T, StackDecodeError> {
+        T::from_stack(&self.stack)
+    }
+}
+
+/// A copy of the VM state made by [`VmState::snapshot`].
+#[derive(Clone)]
+pub struct VmSnapshot<'a> {
+    code: OwnedCellSlice,
+    stack: SafeRc<Stack>,
+    cr: ControlRegs,
+    commited_state: Option<CommitedState>,
+    steps: u64,
+    gas: GasSnapshot,
+    cp: &'a DispatchTable,
+}
+
 bitflags! {
     /// A mask to specify which control registers are saved.
     pub struct SaveCr: u8 {
@@ -258,6 +258,50 @@ pub fn remove_trailing(slice: &mut CellSlice<'_>) -> Result<(), everscale_types:
     slice.skip_last(n + (n != bits) as u16, 0)
 }
 
+/// Skips the opcode of an extended instruction and splits its inline operand.
+pub fn load_ext_operand<'a>(
+    code: &mut CellSlice<'a>,
+    bits: u16,
+    data_bits: u16,
+    refs: u8,
+) -> Option<CellSlice<'a>> {
+    if !code.has_remaining(bits + data_bits, refs) {
+        return None;
+    }
+    code.skip_first(bits, 0).ok()?;
+    code.load_prefix(data_bits, refs).ok()
+}
+
+/// Formats an inline operand as `x{...}` followed by hashes of its references.
+pub fn dump_slice_operand(slice: &CellSlice<'_>) -> String {
+    let mut res = format!("x{{{}}}", slice.display_data());
+    dump_refs_into(&mut res, slice);
+    res
+}
+
+/// Formats an extended instruction which is followed only by references.
+pub fn dump_ref_operand(
+    code: &mut CellSlice<'_>,
+    bits: u16,
+    refs: u8,
+    name: &str,
+) -> Option<String> {
+    let operand = load_ext_operand(code, bits, 0, refs)?;
+    let mut res = name.to_owned();
+    dump_refs_into(&mut res, &operand);
+    Some(res)
+}
+
+fn dump_refs_into(res: &mut String, slice: &CellSlice<'_>) {
+    use std::fmt::Write;
+
+    for i in 0..slice.size_refs() {
+        if let Ok(cell) = slice.get_reference(i) {
+            write!(res, " ({})", cell.repr_hash()).unwrap();
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use num_traits::ToPrimitive;
@@ -0,0 +1,67 @@
+use everscale_types::models::{
+    CurrencyCollection, IntAddr, OutAction, RelaxedMsgInfo, SendMsgFlags, StdAddr,
+};
+use everscale_types::prelude::*;
+
+use crate::Contract;
+
+/// EverWallet code deployed on the mainnet.
+const CODE: &[u8] = include_bytes!("ever_wallet_code.boc");
+
+/// External message of a mainnet transaction which sends 1 EVER
+/// from the wallet to itself.
+const EXT_IN_MSG: &[u8] = include_bytes!("ever_wallet_ext_in.boc");
+
+const ADDRESS: &str = "0:6301b2c75596e6e569a6d13ae4ec70c94f177ece0be19f968ddce73d44e7afc7";
+const PUBKEY: &str = "c8b0d6c608cb03aca12897e618b59f5ddbd10b7e295845c47a4d67720b5e3575";
+
+fn ever_wallet(last_timestamp: u64) -> Contract {
+    let mut contract = Contract::new(CODE, wallet_data(last_timestamp));
+    contract.address = ADDRESS.parse().unwrap();
+    contract
+}
+
+fn wallet_data(last_timestamp: u64) -> Cell {
+    let pubkey = PUBKEY.parse::<HashBytes>().unwrap();
+    CellBuilder::build_from((pubkey, last_timestamp)).unwrap()
+}
+
+#[test]
+fn ever_wallet_transfer() {
+    let mut contract = ever_wallet(1732048243270);
+
+    let msg = Boc::decode(EXT_IN_MSG).unwrap();
+    let result = contract.run_ext_in_msg(msg.clone());
+    assert_eq!(result.exit_code, 0);
+
+    // The message timestamp is saved for the replay protection
+    let committed = result.committed.expect("state must be committed");
+    assert_eq!(committed.c4, wallet_data(1732048340734));
+    assert_eq!(contract.data, committed.c4);
+
+    // Single outbound message
+    let mut actions = committed.c5.as_slice().unwrap();
+    assert_eq!(actions.load_reference_cloned().unwrap(), Cell::empty_cell());
+    let OutAction::SendMsg { mode, out_msg } = OutAction::load_from(&mut actions).unwrap() else {
+        panic!("expected a send message action");
+    };
+    assert!(actions.is_empty());
+    assert_eq!(
+        mode,
+        SendMsgFlags::PAY_FEE_SEPARATELY | SendMsgFlags::IGNORE_ERROR
+    );
+
+    let out_msg = out_msg.load().unwrap();
+    let RelaxedMsgInfo::Int(info) = out_msg.info else {
+        panic!("expected an internal message");
+    };
+    assert_eq!(info.dst, IntAddr::Std(ADDRESS.parse::<StdAddr>().unwrap()));
+    assert_eq!(info.value, CurrencyCollection::new(1_000_000_000));
+    assert!(!info.bounce);
+
+    // The same message can't be replayed
+    let result = contract.run_ext_in_msg(msg);
+    assert_ne!(result.exit_code, 0);
+    assert!(result.committed.is_none());
+    assert_eq!(contract.data, wallet_data(1732048340734));
+}
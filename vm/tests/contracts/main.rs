@@ -0,0 +1,89 @@
+use everscale_types::models::{CurrencyCollection, IntAddr, OwnedMessage, StdAddr};
+use everscale_types::prelude::*;
+use tycho_vm::{GasParams, OwnedCellSlice, RcStackValue, SmcInfoBase, VmRunResult, VmState};
+
+mod ever_wallet;
+mod seqno;
+
+/// Contract account state used to run its code.
+pub struct Contract {
+    pub address: StdAddr,
+    pub code: Cell,
+    pub data: Cell,
+}
+
+impl Contract {
+    pub fn new(code: &[u8], data: Cell) -> Self {
+        Self {
+            address: StdAddr::new(0, HashBytes::ZERO),
+            code: Boc::decode(code).expect("invalid contract code"),
+            data,
+        }
+    }
+
+    /// Runs an external message with the specified body.
+    ///
+    /// Updates the contract data on success.
+    pub fn run_external(&mut self, body: Cell) -> VmRunResult {
+        self.run_external_impl(Cell::empty_cell(), OwnedCellSlice::new_allow_exotic(body))
+    }
+
+    /// Runs the specified external message.
+    ///
+    /// Updates the contract data on success.
+    pub fn run_ext_in_msg(&mut self, msg: Cell) -> VmRunResult {
+        let parsed = msg.parse::<OwnedMessage>().expect("invalid message");
+        self.run_external_impl(msg, OwnedCellSlice::from(parsed.body))
+    }
+
+    fn run_external_impl(&mut self, msg: Cell, body: OwnedCellSlice) -> VmRunResult {
+        let stack = tycho_vm::tuple![
+            int 1_000_000_000,
+            int 0,
+            cell msg,
+            slice body,
+            int -1,
+        ];
+        let gas = GasParams {
+            max: 1_000_000,
+            limit: 0,
+            credit: 10_000,
+            ..GasParams::getter()
+        };
+
+        let result = self.run(stack, gas);
+        if result.exit_code == 0 {
+            if let Some(committed) = &result.committed {
+                self.data = committed.c4.clone();
+            }
+        }
+        result
+    }
+
+    /// Runs a get-method with the specified id.
+    pub fn run_getter(&self, method_id: u32) -> VmRunResult {
+        self.run(tycho_vm::tuple![int method_id], GasParams::getter())
+    }
+
+    fn run(&self, stack: Vec<RcStackValue>, gas: GasParams) -> VmRunResult {
+        let smc_info = SmcInfoBase::new()
+            .with_now(1732048342)
+            .with_block_lt(55398352000001)
+            .with_tx_lt(55398317000004)
+            .with_account_balance(CurrencyCollection::new(1_000_000_000))
+            .with_account_addr(IntAddr::Std(self.address.clone()))
+            .require_ton_v4()
+            .with_code(self.code.clone());
+
+        let mut vm = VmState::builder()
+            .with_smc_info(smc_info)
+            .with_strict_c7(true)
+            .with_stack(stack)
+            .with_code(self.code.clone())
+            .with_data(self.data.clone())
+            .with_gas(gas)
+            .build();
+
+        vm.run_to_completion()
+    }
+}
@@ -0,0 +1,98 @@
+use everscale_asm_macros::tvmasm;
+use everscale_types::prelude::*;
+use num_bigint::BigInt;
+
+use crate::Contract;
+
+const SEQNO_METHOD_ID: u32 = 85143;
+
+/// A wallet-style contract which accepts an external message
+/// only if its body contains the current seqno.
+fn seqno_contract(seqno: u32) -> Contract {
+    let code = tvmasm! {
+        r#"
+        DUP
+        EQINT -1
+        PUSHCONT {
+            DROP
+            LDU 32
+            ENDS
+            PUSH c4
+            CTOS
+            LDU 32
+            ENDS
+            TUCK
+            EQUAL
+            THROWIFNOT 33
+            ACCEPT
+            INC
+            NEWC
+            STU 32
+            ENDC
+            POP c4
+        }
+        IFJMP
+        PUSHINT 85143
+        EQUAL
+        PUSHCONT {
+            PUSH c4
+            CTOS
+            PLDU 32
+        }
+        IFJMP
+        THROW 11
+        "#
+    };
+
+    Contract::new(code, seqno_cell(seqno))
+}
+
+fn seqno_cell(seqno: u32) -> Cell {
+    CellBuilder::build_from(seqno).unwrap()
+}
+
+fn get_seqno(contract: &Contract) -> BigInt {
+    let result = contract.run_getter(SEQNO_METHOD_ID);
+    assert_eq!(result.exit_code, 0);
+    assert_eq!(result.stack.depth(), 1);
+    result.stack.items[0].as_int().cloned().unwrap()
+}
+
+#[test]
+fn seqno_increment() {
+    let mut contract = seqno_contract(0);
+    assert_eq!(get_seqno(&contract), BigInt::from(0));
+
+    for seqno in 0..3 {
+        let result = contract.run_external(seqno_cell(seqno));
+        assert_eq!(result.exit_code, 0);
+        assert!(result.gas_used > 0);
+
+        let committed = result.committed.expect("state must be committed");
+        assert_eq!(committed.c4, seqno_cell(seqno + 1));
+    }
+
+    assert_eq!(contract.data, seqno_cell(3));
+    assert_eq!(get_seqno(&contract), BigInt::from(3));
+}
+
+#[test]
+fn seqno_mismatch() {
+    let mut contract = seqno_contract(5);
+
+    for seqno in [0, 4, 6] {
+        let result = contract.run_external(seqno_cell(seqno));
+        assert_eq!(result.exit_code, 33);
+        assert!(result.committed.is_none());
+    }
+
+    assert_eq!(contract.data, seqno_cell(5));
+    assert_eq!(get_seqno(&contract), BigInt::from(5));
+}
+
+#[test]
+fn unknown_getter() {
+    let contract = seqno_contract(0);
+    let result = contract.run_getter(12345);
+    assert_eq!(result.exit_code, 11);
+}